@@ -0,0 +1,458 @@
+//! Move generation as it was before `MoveList`, kept to compare against.
+//!
+//! A copy of the old `get_moves` path: every piece collects its targets into a `Vec`
+//! through `check_continous` and `movement_targets`, and each candidate move is tried
+//! on a copy of the board to see whether it leaves the king attacked. Only the parts
+//! perft needs are kept. Pieces share their movement rules now, so cloning the board
+//! is cheaper than it used to be and the numbers flatter the old code a little.
+
+use maltebl_chess::board_logic::*;
+use maltebl_chess::piece_logic::*;
+use maltebl_chess::to_notation;
+
+#[derive(Clone)]
+pub struct LegacyBoard {
+    board: Board,
+    white_king: (usize, usize),
+    black_king: (usize, usize),
+    passant_connection: Option<((usize, usize), (usize, usize))>,
+}
+
+#[derive(PartialEq)]
+enum SpecialMove {
+    Pawn2Step,
+    CastlingLeft,
+    CastlingRight,
+}
+
+impl LegacyBoard {
+    /// Copies the pieces of `board`, which must not have an en passant capture pending
+    pub fn new(board: &ChessBoard) -> LegacyBoard {
+        LegacyBoard {
+            board: board.get_board(),
+            white_king: board.king_position(Color::White).unwrap(),
+            black_king: board.king_position(Color::Black).unwrap(),
+            passant_connection: None,
+        }
+    }
+
+    pub fn perft(&self, color: Color, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let next_color = if color == Color::White {
+            Color::Black
+        } else {
+            Color::White
+        };
+        self.legal_moves(color)
+            .iter()
+            .map(|mov| {
+                let mut next = self.clone();
+                next.make_move(mov).unwrap();
+                next.perft(next_color, depth - 1)
+            })
+            .sum()
+    }
+
+    fn promote(
+        &mut self,
+        position: (usize, usize),
+        piece_type: PieceType,
+    ) -> Result<String, String> {
+        if let Some(piece) = self.ref_piece(position) {
+            if piece.piece_type == PieceType::Pawn
+                && position.1 == if piece.color == Color::White { 7 } else { 0 }
+                && piece_type != PieceType::King
+            {
+                self.board[position.1][position.0] = Some(piece_make(piece.color, piece_type));
+                Ok(format!(
+                    "Promoted piece at {:?} to {}",
+                    position,
+                    self.ref_piece(position).unwrap()
+                ))
+            } else {
+                Err("Tried to promote unit at wrong place or of wrong type".to_string())
+            }
+        } else {
+            Err("Tried to promote an empty space!".to_string())
+        }
+    }
+
+    fn move_piece(
+        &mut self,
+        position: (usize, usize),
+        mov: (usize, usize),
+    ) -> Result<String, String> {
+        if self.ref_piece(position).is_none() {
+            return Err("Tried to move empty space!".to_string());
+        }
+        let mut possible_moves = self.get_moves(position);
+        possible_moves.retain(|(move_, _)| *move_ == mov);
+        if !possible_moves.is_empty() {
+            let (movement, special_move) = possible_moves.pop().unwrap();
+            if let Some(special_move) = special_move {
+                match special_move {
+                    SpecialMove::Pawn2Step => {
+                        let (pos_x, pos_y) = movement;
+                        self.force_move(position, movement)?;
+                        self.passant_connection =
+                            Some(((pos_x, (position.1 + pos_y) / 2), (pos_x, pos_y)));
+
+                        Ok(format!(
+                            "{} {}",
+                            to_notation(position).ok().unwrap(),
+                            to_notation(mov).ok().unwrap()
+                        ))
+                    }
+                    SpecialMove::CastlingLeft => {
+                        let color = self.ref_piece(position).unwrap().color;
+                        let pos_y = if color == Color::White { 0 } else { 7 };
+                        self.force_move(position, (2, pos_y))?;
+                        self.force_move((0, pos_y), (3, pos_y))?;
+                        self.passant_connection = None;
+                        Ok("O-O-O".to_string())
+                    }
+                    SpecialMove::CastlingRight => {
+                        let color = self.ref_piece(position).unwrap().color;
+                        let pos_y = if color == Color::White { 0 } else { 7 };
+                        self.force_move(position, (6, pos_y))?;
+                        self.force_move((7, pos_y), (5, pos_y))?;
+                        self.passant_connection = None;
+                        Ok("O-O".to_string())
+                    }
+                }
+            } else {
+                self.force_move(position, movement)?;
+                let piece = self.ref_piece(movement).unwrap();
+                let mut result = format!(
+                    "{}{} {}",
+                    piece.piece_type,
+                    to_notation(position).ok().unwrap(),
+                    to_notation(mov).ok().unwrap()
+                );
+                if piece.piece_type == PieceType::Pawn {
+                    if movement.1 == if piece.color == Color::White { 7 } else { 0 } {
+                        result = format!(
+                            "{} {} Promotion",
+                            to_notation(position).ok().unwrap(),
+                            to_notation(mov).ok().unwrap()
+                        );
+                    }
+                    if let Some((passant_pos, pawn_pos)) = self.passant_connection {
+                        if movement == passant_pos && piece.piece_type == PieceType::Pawn {
+                            self.board[pawn_pos.1][pawn_pos.0] = None;
+                        }
+                    }
+                }
+                self.passant_connection = None;
+                Ok(result)
+            }
+        } else {
+            Err(format!(
+                "Tried to do illegal move! piece at {:?} cannot move to {:?}",
+                position, mov.0,
+            ))
+        }
+    }
+
+    fn get_moves(&self, position: (usize, usize)) -> Vec<((usize, usize), Option<SpecialMove>)> {
+        let mut all_moves: Vec<((usize, usize), Option<SpecialMove>)> = Vec::new();
+        for mov in self.regular_moves(position) {
+            if !self.self_check(position, mov) {
+                all_moves.push((mov, None));
+            }
+        }
+        for mov in self.special_moves(position) {
+            if mov.1 == SpecialMove::Pawn2Step {
+                if !self.self_check(position, mov.0) {
+                    all_moves.push((mov.0, Some(mov.1)));
+                }
+            } else {
+                all_moves.push((mov.0, Some(mov.1)));
+            }
+        }
+
+        all_moves
+    }
+
+    fn regular_moves(&self, position: (usize, usize)) -> Vec<(usize, usize)> {
+        if let Some(piece) = self.ref_piece(position) {
+            self.movement_targets(position, piece, false)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn movement_targets(
+        &self,
+        position: (usize, usize),
+        piece: &Piece,
+        attacks: bool,
+    ) -> Vec<(usize, usize)> {
+        let mut results: Vec<(usize, usize)> = Vec::new();
+        for rule in piece.movement.rules() {
+            if (rule.initial_only && piece.has_moved) || (attacks && !rule.captures) {
+                continue;
+            }
+            for direction in rule.directions(piece.color) {
+                let mut current = position;
+                let mut distance = 0;
+                while rule.range != Some(distance) {
+                    current = match step_from(current, direction) {
+                        Some(target) => target,
+                        None => break,
+                    };
+                    distance += 1;
+                    match self.ref_piece(current) {
+                        Some(target) => {
+                            if rule.captures && (attacks || target.color != piece.color) {
+                                results.push(current);
+                            }
+                            break;
+                        }
+                        None => {
+                            if attacks
+                                || rule.moves
+                                || (rule.captures && self.is_passant_capture(piece, current))
+                            {
+                                results.push(current);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        results.sort();
+        results.dedup();
+        results
+    }
+
+    fn is_passant_capture(&self, piece: &Piece, target: (usize, usize)) -> bool {
+        match self.passant_connection {
+            Some((passant_pos, pawn_pos)) => {
+                piece.piece_type == PieceType::Pawn
+                    && passant_pos == target
+                    && matches!(self.ref_piece(pawn_pos), Some(pawn) if pawn.color != piece.color)
+            }
+            None => false,
+        }
+    }
+
+    fn special_moves(&self, position: (usize, usize)) -> Vec<((usize, usize), SpecialMove)> {
+        let mut special_moves: Vec<((usize, usize), SpecialMove)> = Vec::new();
+        if let Some(piece) = self.ref_piece(position) {
+            if !piece.has_moved {
+                if piece.piece_type == PieceType::Pawn {
+                    let color_modifier = if piece.color == Color::White { 2 } else { -2 };
+                    let passing = step_from(position, (0, color_modifier / 2));
+                    if let Ok(target_space) = self.check_move(position, (0, color_modifier)) {
+                        if target_space.1.is_none()
+                            && matches!(passing, Some(space) if self.ref_piece(space).is_none())
+                        {
+                            special_moves.push((target_space.0, SpecialMove::Pawn2Step));
+                        }
+                    }
+                }
+                if piece.piece_type == PieceType::King && !self.is_threatened(position, piece.color)
+                {
+                    let color_modifier = if piece.color == Color::White { 0 } else { 7 };
+                    let mut spaces = self.check_continous(position, (1, 0));
+                    if let Some(space) = spaces.pop() {
+                        if let Some(rook) = space.1 {
+                            if rook.piece_type == PieceType::Rook
+                                && rook.color == piece.color
+                                && !rook.has_moved
+                            {
+                                let mut can_castle = true;
+                                for spots in spaces {
+                                    if self.is_threatened(spots.0, piece.color) {
+                                        can_castle = false;
+                                    }
+                                }
+                                if can_castle {
+                                    special_moves
+                                        .push(((6, color_modifier), SpecialMove::CastlingRight));
+                                }
+                            }
+                        }
+                    }
+                    let mut spaces = self.check_continous(position, (-1, 0));
+                    if let Some(space) = spaces.pop() {
+                        if let Some(rook) = space.1 {
+                            if rook.piece_type == PieceType::Rook
+                                && rook.color == piece.color
+                                && !rook.has_moved
+                            {
+                                let mut can_castle = true;
+                                // b1 and b8 may be attacked, the king doesn't cross them
+                                for spots in spaces.iter().filter(|spot| spot.0 .0 >= 2) {
+                                    if self.is_threatened(spots.0, piece.color) {
+                                        can_castle = false;
+                                    }
+                                }
+                                if can_castle {
+                                    special_moves
+                                        .push(((2, color_modifier), SpecialMove::CastlingLeft));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        special_moves
+    }
+
+    fn check_continous(
+        &self,
+        position: (usize, usize),
+        direction: (isize, isize),
+    ) -> Vec<((usize, usize), Option<&Piece>)> {
+        let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
+        if let Ok(target_space) = self.check_move(position, direction) {
+            legal_spaces.push(target_space);
+            if target_space.1.is_none() {
+                legal_spaces.extend(self.check_continous(target_space.0, direction));
+            }
+            if self.passant_connection.is_some()
+                && self.passant_connection.unwrap().0 == target_space.0
+            {
+                legal_spaces.extend(self.check_continous(target_space.0, direction));
+            }
+        }
+        legal_spaces
+    }
+
+    fn check_move(
+        &self,
+        position: (usize, usize),
+        moves: (isize, isize),
+    ) -> Result<((usize, usize), Option<&Piece>), String> {
+        let new_x = position.0 as isize + moves.0;
+        let new_y = position.1 as isize + moves.1;
+        if !(0..8).contains(&new_x) || !(0..8).contains(&new_y) {
+            return Err("not valid movement".to_string());
+        }
+        let new_pos = (new_x as usize, new_y as usize);
+        let target_space = self.ref_piece(new_pos);
+        if let Some(connection) = self.passant_connection {
+            if new_pos == connection.0 {
+                let target_space = self.ref_piece(connection.1);
+                return Ok((new_pos, target_space));
+            }
+        }
+        if target_space.is_some() {
+            Ok((new_pos, target_space))
+        } else {
+            Ok((new_pos, None))
+        }
+    }
+
+    fn self_check(&self, move_from: (usize, usize), move_to: (usize, usize)) -> bool {
+        let mut test = self.clone();
+        let piece = test.ref_piece(move_from).unwrap();
+        let color = piece.color;
+        test.force_move(move_from, move_to)
+            .expect("Error during checking for self-check:");
+        test.is_checked(color)
+    }
+
+    fn force_move(
+        &mut self,
+        piece_pos: (usize, usize),
+        new_pos: (usize, usize),
+    ) -> Result<String, String> {
+        if let Some(mut piece) = self.board[piece_pos.1][piece_pos.0].take() {
+            if piece.piece_type == PieceType::King {
+                if piece.color == Color::White {
+                    self.white_king = new_pos;
+                } else {
+                    self.black_king = new_pos;
+                }
+            }
+            piece.moved();
+            self.board[new_pos.1][new_pos.0] = Some(piece);
+            Ok(format!("Moved from {:?} to {:?}", piece_pos, new_pos))
+        } else {
+            Err(format!("Can't force move, no piece at {:?}", piece_pos))
+        }
+    }
+
+    fn is_threatened(&self, pos: (usize, usize), color: Color) -> bool {
+        for y in 0..self.board.len() {
+            for x in 0..self.board[0].len() {
+                if let Some(piece) = self.ref_piece((x, y)) {
+                    if piece.color != color
+                        && self.movement_targets((x, y), piece, true).contains(&pos)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn is_checked(&self, color: Color) -> bool {
+        let king_pos = if color == Color::White {
+            self.white_king
+        } else {
+            self.black_king
+        };
+        self.is_threatened(king_pos, color)
+    }
+
+    fn legal_moves(&self, color: Color) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::new();
+        for y in 0..self.board.len() {
+            for x in 0..self.board[0].len() {
+                let piece = match self.ref_piece((x, y)) {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+                let last_rank = if color == Color::White { 7 } else { 0 };
+                for (to, _) in self.get_moves((x, y)) {
+                    if piece.piece_type == PieceType::Pawn && to.1 == last_rank {
+                        for promotion in PROMOTIONS.iter() {
+                            moves.push(Move {
+                                from: (x, y),
+                                to,
+                                promotion: Some(promotion.clone()),
+                            });
+                        }
+                    } else {
+                        moves.push(Move {
+                            from: (x, y),
+                            to,
+                            promotion: None,
+                        });
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    fn make_move(&mut self, mov: &Move) -> Result<String, String> {
+        let result = self.move_piece(mov.from, mov.to)?;
+        match &mov.promotion {
+            Some(piece_type) => self.promote(mov.to, piece_type.clone()),
+            None => Ok(result),
+        }
+    }
+
+    fn ref_piece(&self, position: (usize, usize)) -> Option<&Piece> {
+        self.board[position.1][position.0].as_ref()
+    }
+}
+
+fn step_from(position: (usize, usize), direction: (isize, isize)) -> Option<(usize, usize)> {
+    let new_x = position.0 as isize + direction.0;
+    let new_y = position.1 as isize + direction.1;
+    if (0..8).contains(&new_x) && (0..8).contains(&new_y) {
+        Some((new_x as usize, new_y as usize))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,61 @@
+//! Move generation and search speed, run with `cargo bench`
+
+use maltebl_chess::board_logic::*;
+use maltebl_chess::search::*;
+use std::time::Instant;
+
+mod legacy;
+use legacy::LegacyBoard;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn time<F: FnMut() -> u64>(name: &str, mut run: F) {
+    let start = Instant::now();
+    let nodes = run();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>10} nodes {:>8.1} ms {:>10.0} nodes/s",
+        name,
+        nodes,
+        elapsed.as_secs_f64() * 1000.0,
+        nodes as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    for &(name, fen, depth) in [("start", STARTING_FEN, 4), ("kiwipete", KIWIPETE, 3)].iter() {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        let legacy = LegacyBoard::new(&position.board);
+        let mut old_nodes = 0;
+        time(&format!("perft {} {} (old get_moves)", name, depth), || {
+            old_nodes = legacy.perft(position.turn, depth);
+            old_nodes
+        });
+        let mut nodes = 0;
+        time(&format!("perft {} {} (MoveList)", name, depth), || {
+            nodes = position.board.perft(position.turn, depth);
+            nodes
+        });
+        assert_eq!(old_nodes, nodes, "the generators disagree");
+        let limits = SearchLimits {
+            depth: 4,
+            time: None,
+            threads: 1,
+            stop: None,
+        };
+        time(&format!("search {} 4", name), || {
+            search(&position.board, position.turn, &limits)
+                .unwrap()
+                .nodes
+        });
+        let threads = SearchLimits {
+            threads: 4,
+            ..limits
+        };
+        time(&format!("search {} 4 (4 threads)", name), || {
+            search(&position.board, position.turn, &threads)
+                .unwrap()
+                .nodes
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+/// Piece that can't leave the line between an enemy slider and its own king
+#[derive(PartialEq, Clone, Debug)]
+pub struct Pin {
+    pub pinned: (usize, usize),
+    pub pinner: (usize, usize),
+    /// Spaces the pinned piece may still move to: from the pinner up to the king
+    pub ray: Vec<(usize, usize)>,
+}
+
+impl ChessBoard {
+    /// Pieces of `color` that attack `square`, whether or not it is occupied
+    pub fn attackers_of(&self, square: (usize, usize), color: Color) -> Vec<(usize, usize)> {
+        self.pieces_of(color)
+            .map(|(position, _)| position)
+            .filter(|&position| self.attacks(position, square))
+            .collect()
+    }
+
+    /// Every space attacked by a piece of `color`
+    pub fn attacked_squares(&self, color: Color) -> Vec<(usize, usize)> {
+        let attacked = self
+            .pieces_of(color)
+            .fold(0, |attacked, (position, piece)| {
+                attacked | self.reach(position, piece, true).0
+            });
+        movegen::squares(attacked).collect()
+    }
+
+    /// Pieces giving check to the king of `color`
+    pub fn checkers(&self, color: Color) -> Vec<(usize, usize)> {
+        match self.king_position(color) {
+            Some(king) => self.attackers_of(king, opponent(color)),
+            None => Vec::new(),
+        }
+    }
+
+    /// Pieces of `color` pinned to their king by a rider of the other side
+    pub fn pinned_pieces(&self, color: Color) -> Vec<Pin> {
+        let king = match self.king_position(color) {
+            Some(king) => king,
+            None => return Vec::new(),
+        };
+        let mut pins: Vec<Pin> = Vec::new();
+        for (pinner, piece) in self.pieces_of(opponent(color)) {
+            for rule in piece.movement.rules() {
+                if !rule.captures || rule.range == Some(1) || (rule.initial_only && piece.has_moved)
+                {
+                    continue;
+                }
+                for direction in rule.directions(piece.color) {
+                    let mut ray = vec![pinner];
+                    let mut blocker: Option<(usize, usize)> = None;
+                    let mut current = pinner;
+                    while rule.range != Some(ray.len() - 1) {
+                        current = match step_from(current, direction) {
+                            Some(next) => next,
+                            None => break,
+                        };
+                        if current == king {
+                            if let Some(pinned) = blocker {
+                                ray.retain(|&space| space != pinned);
+                                pins.push(Pin {
+                                    pinned,
+                                    pinner,
+                                    ray,
+                                });
+                            }
+                            break;
+                        }
+                        match self.ref_piece(current) {
+                            Some(other) if other.color == color && blocker.is_none() => {
+                                blocker = Some(current)
+                            }
+                            Some(_) => break,
+                            None => (),
+                        }
+                        ray.push(current);
+                    }
+                }
+            }
+        }
+        pins.sort_by_key(|pin| pin.pinned);
+        pins.dedup_by_key(|pin| pin.pinned);
+        pins
+    }
+
+    /// Whether `mov` checks the enemy king with a piece other than the one that moved
+    pub fn is_discovered_check(&self, mov: &Move) -> bool {
+        let color = match self.ref_piece(mov.from) {
+            Some(piece) => piece.color,
+            None => return false,
+        };
+        let mut after = self.clone();
+        if after.make_move(mov).is_err() {
+            return false;
+        }
+        after.checkers(opponent(color)).into_iter().any(|checker| {
+            checker != mov.to
+                && checker != mov.from
+                && matches!(self.ref_piece(checker), Some(piece) if piece.color == color)
+        })
+    }
+
+    fn pieces_of(&self, color: Color) -> impl Iterator<Item = ((usize, usize), &Piece)> {
+        self.board.iter().enumerate().flat_map(move |(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(x, space)| match space {
+                    Some(piece) if piece.color == color => Some(((x, y), piece)),
+                    _ => None,
+                })
+        })
+    }
+}
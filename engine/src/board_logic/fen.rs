@@ -0,0 +1,165 @@
+use super::*;
+
+/// Starting position of standard chess
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Position read from Forsyth–Edwards Notation together with its counters
+pub struct FenPosition {
+    pub board: ChessBoard,
+    pub turn: Color,
+    /// Half moves since the last capture or pawn move
+    pub halfmove_clock: usize,
+    pub fullmove: usize,
+}
+
+fn piece_type_from_letter(letter: char) -> Option<PieceType> {
+    match letter.to_ascii_uppercase() {
+        'P' => Some(PieceType::Pawn),
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+impl ChessBoard {
+    /// Reads a position in FEN. Only the kings are checked, see `BoardSetup::validate`
+    /// for the rest.
+    pub fn from_fen(fen: &str) -> Result<FenPosition, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(format!(
+                "Expected 4 to 6 fields in FEN, found {}",
+                fields.len()
+            ));
+        }
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("Expected 8 ranks in FEN, found {}", ranks.len()));
+        }
+        let mut setup = BoardSetup::new();
+        setup.castling(CastlingRights::from_fen(fields[2])?);
+        for (i, rank) in ranks.iter().enumerate() {
+            let y = 7 - i;
+            let mut x = 0;
+            for symbol in rank.chars() {
+                if let Some(empty) = symbol.to_digit(10) {
+                    x += empty as usize;
+                    continue;
+                }
+                if x > 7 {
+                    return Err(format!("Rank {} has more than 8 spaces", y + 1));
+                }
+                let piece_type = piece_type_from_letter(symbol)
+                    .ok_or_else(|| format!("Unknown piece {:?} in FEN", symbol))?;
+                let color = if symbol.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                setup.put((x, y), piece_make(color, piece_type));
+                x += 1;
+            }
+            if x != 8 {
+                return Err(format!("Rank {} doesn't have 8 spaces", y + 1));
+            }
+        }
+        for &color in [Color::White, Color::Black].iter() {
+            let kings = setup
+                .pieces()
+                .filter(|(_, piece)| piece.color == color && piece.piece_type == PieceType::King)
+                .count();
+            if kings != 1 {
+                return Err(format!(
+                    "Expected one {:?} king in FEN, found {}",
+                    color, kings
+                ));
+            }
+        }
+        setup.side_to_move(match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(format!("Invalid side to move {:?}", other)),
+        });
+        if fields[3] != "-" {
+            let passant = super::super::to_coords(fields[3].to_string())?;
+            if passant.1 != 2 && passant.1 != 5 {
+                return Err(format!("Invalid en passant square {:?}", fields[3]));
+            }
+            setup.en_passant(Some(passant));
+        }
+        let counter = |index: usize, default: usize| -> Result<usize, String> {
+            match fields.get(index) {
+                Some(text) => text
+                    .parse()
+                    .map_err(|_| format!("Invalid move counter {:?}", text)),
+                None => Ok(default),
+            }
+        };
+        Ok(FenPosition {
+            board: setup.to_board(),
+            turn: setup.turn(),
+            halfmove_clock: counter(4, 0)?,
+            fullmove: counter(5, 1)?.max(1),
+        })
+    }
+
+    /// Castlings still allowed, going by unmoved kings and corner rooks
+    pub fn castling_rights(&self) -> CastlingRights {
+        let unmoved = |position: (usize, usize), color: Color, piece_type: PieceType| {
+            matches!(self.ref_piece(position),
+                Some(piece) if piece.piece_type == piece_type && piece.color == color && !piece.has_moved)
+        };
+        let rights = |color: Color, home: usize, rook_x: usize| {
+            unmoved((4, home), color, PieceType::King)
+                && unmoved((rook_x, home), color, PieceType::Rook)
+        };
+        CastlingRights {
+            white_king_side: rights(Color::White, 0, 7),
+            white_queen_side: rights(Color::White, 0, 0),
+            black_king_side: rights(Color::Black, 7, 7),
+            black_queen_side: rights(Color::Black, 7, 0),
+        }
+    }
+
+    /// Writes the position in FEN
+    pub fn to_fen(&self, turn: Color, halfmove_clock: usize, fullmove: usize) -> String {
+        let mut placement = String::new();
+        for y in (0..8).rev() {
+            let mut empty = 0;
+            for x in 0..8 {
+                match self.ref_piece((x, y)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.letter());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if y > 0 {
+                placement.push('/');
+            }
+        }
+        let passant = match self.passant_connection {
+            Some((square, _)) => super::super::to_notation(square).unwrap(),
+            None => "-".to_string(),
+        };
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            if turn == Color::White { "w" } else { "b" },
+            self.castling_rights().to_fen(),
+            passant,
+            halfmove_clock,
+            fullmove
+        )
+    }
+}
@@ -4,13 +4,36 @@ pub type Board = [[Option<Piece>; 8]; 8]; //Hardcoded size bad? //flatten to 64,
 
 #[cfg(test)]
 mod tests;
+
+mod attacks;
+mod fen;
+mod movegen;
+mod see;
+#[cfg(feature = "serde")]
+mod serialization;
+mod setup;
+mod symmetry;
+mod zobrist;
+
+pub use attacks::Pin;
+pub use fen::{FenPosition, STARTING_FEN};
+pub use movegen::{MoveList, MoveStage, MAX_MOVES};
+pub use setup::{BoardSetup, CastlingRights};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::ChessBoardData"))]
 pub struct ChessBoard {
     board: Board,
     white_king: (usize, usize),
     black_king: (usize, usize),
     passant_connection: Option<((usize, usize), (usize, usize))>,
+    king_capture: bool,
 }
 
+/// Board as seen through the fog of war, `None` marks a hidden space
+pub type RedactedBoard = [[Option<Option<Piece>>; 8]; 8];
+
 impl ChessBoard {
     pub fn get_board(&self) -> Board {
         self.board.clone()
@@ -65,72 +88,90 @@ impl ChessBoard {
         position: (usize, usize),
         mov: (usize, usize),
     ) -> Result<String, String> {
-        if self.ref_piece(position).is_none() {
-            return Err("Tried to move empty space!".to_string());
-        }
-        let mut possible_moves = self.get_moves(position);
-        possible_moves.retain(|(move_, _)| *move_ == mov);
-        if !possible_moves.is_empty() {
-            let (movement, special_move) = possible_moves.pop().unwrap();
-            if let Some(special_move) = special_move {
-                match special_move {
-                    SpecialMove::Pawn2Step => {
-                        let (pos_x, pos_y) = movement;
-                        self.force_move(position, movement)?;
-                        self.passant_connection = Some(((pos_x, pos_y - 1), (pos_x, pos_y)));
-
-                        Ok(format!(
-                            "{} {}",
-                            super::to_notation(position).ok().unwrap(),
-                            super::to_notation(mov).ok().unwrap()
-                        ))
-                    }
-                    SpecialMove::CastlingLeft => {
-                        let color = self.ref_piece(position).unwrap().color;
-                        let pos_y = if color == Color::White { 0 } else { 7 };
-                        self.force_move(position, (2, pos_y))?;
-                        self.force_move((0, pos_y), (2, pos_y))?;
-                        Ok("O-O-O".to_string())
-                    }
-                    SpecialMove::CastlingRight => {
-                        let color = self.ref_piece(position).unwrap().color;
-                        let pos_y = if color == Color::White { 0 } else { 7 };
-                        self.force_move(position, (6, pos_y))?;
-                        self.force_move((0, pos_y), (6, pos_y))?;
-                        Ok("O-O".to_string())
-                    }
+        match self.play(position, mov)? {
+            Some(SpecialMove::Pawn2Step) => Ok(format!(
+                "{} {}",
+                super::to_notation(position).ok().unwrap(),
+                super::to_notation(mov).ok().unwrap()
+            )),
+            Some(SpecialMove::CastlingLeft) => Ok("O-O-O".to_string()),
+            Some(SpecialMove::CastlingRight) => Ok("O-O".to_string()),
+            None => {
+                let piece = self.ref_piece(mov).unwrap();
+                if piece.piece_type == PieceType::Pawn
+                    && mov.1 == if piece.color == Color::White { 7 } else { 0 }
+                {
+                    return Ok(format!(
+                        "{} {} Promotion",
+                        super::to_notation(position).ok().unwrap(),
+                        super::to_notation(mov).ok().unwrap()
+                    ));
                 }
-            } else {
-                self.force_move(position, movement)?;
-                let piece = self.ref_piece(movement).unwrap();
-                let mut result = format!(
+                Ok(format!(
                     "{}{} {}",
                     piece.piece_type,
                     super::to_notation(position).ok().unwrap(),
                     super::to_notation(mov).ok().unwrap()
-                );
-                if piece.piece_type == PieceType::Pawn {
-                    if movement.1 == if piece.color == Color::White { 7 } else { 0 } {
-                        result = format!(
-                            "{} {} Promotion",
-                            super::to_notation(position).ok().unwrap(),
-                            super::to_notation(mov).ok().unwrap()
-                        );
-                    }
-                    if let Some((passant_pos, pawn_pos)) = self.passant_connection {
-                        if movement == passant_pos && piece.piece_type == PieceType::Pawn {
-                            self.board[pawn_pos.1][pawn_pos.0] = None;
-                        }
-                    }
-                }
-                Ok(result)
+                ))
             }
-        } else {
-            Err(format!(
+        }
+    }
+
+    /// Moves the piece at `position` to `mov` if that is legal, returning which special move
+    /// it was
+    fn play(
+        &mut self,
+        position: (usize, usize),
+        mov: (usize, usize),
+    ) -> Result<Option<SpecialMove>, String> {
+        if self.ref_piece(position).is_none() {
+            return Err("Tried to move empty space!".to_string());
+        }
+        let special_move = self.find_move(position, mov).ok_or_else(|| {
+            format!(
                 "Tried to do illegal move! piece at {:?} cannot move to {:?}",
                 position, mov.0,
-            ))
+            )
+        })?;
+        match special_move {
+            Some(SpecialMove::Pawn2Step) => {
+                self.force_move(position, mov)?;
+                self.passant_connection = Some(((mov.0, (position.1 + mov.1) / 2), mov));
+            }
+            Some(SpecialMove::CastlingLeft) => {
+                self.force_move(position, (2, position.1))?;
+                self.force_move((0, position.1), (3, position.1))?;
+                self.passant_connection = None;
+            }
+            Some(SpecialMove::CastlingRight) => {
+                self.force_move(position, (6, position.1))?;
+                self.force_move((7, position.1), (5, position.1))?;
+                self.passant_connection = None;
+            }
+            None => {
+                self.force_move(position, mov)?;
+                if let Some((passant_pos, pawn_pos)) = self.passant_connection {
+                    if mov == passant_pos
+                        && self.ref_piece(mov).unwrap().piece_type == PieceType::Pawn
+                    {
+                        self.board[pawn_pos.1][pawn_pos.0] = None;
+                    }
+                }
+                self.passant_connection = None;
+            }
         }
+        Ok(special_move)
+    }
+
+    /// The special move going from `from` to `to`, `None` when it is illegal
+    fn find_move(&self, from: (usize, usize), to: (usize, usize)) -> Option<Option<SpecialMove>> {
+        let mut found = None;
+        self.pseudo_targets(from, MoveStage::All, &mut |target, special| {
+            if target == to && found.is_none() && self.is_legal_target(from, to, &special) {
+                found = Some(special);
+            }
+        });
+        found
     }
 
     pub fn get_moves(
@@ -138,136 +179,50 @@ impl ChessBoard {
         position: (usize, usize),
     ) -> Vec<((usize, usize), Option<SpecialMove>)> {
         let mut all_moves: Vec<((usize, usize), Option<SpecialMove>)> = Vec::new();
-        for mov in self.regular_moves(position) {
-            if !self.self_check(position, mov) {
-                all_moves.push((mov, None));
-            }
-        }
-        for mov in self.special_moves(position) {
-            if mov.1 == SpecialMove::Pawn2Step {
-                if !self.self_check(position, mov.0) {
-                    all_moves.push((mov.0, Some(mov.1)));
-                }
-            } else {
-                all_moves.push((mov.0, Some(mov.1)));
-            }
-        }
-
+        self.piece_targets(position, MoveStage::All, &mut |target, special| {
+            all_moves.push((target, special))
+        });
         all_moves
     }
 
+    #[cfg(test)]
     fn regular_moves(&self, position: (usize, usize)) -> Vec<(usize, usize)> {
-        let mut results: Vec<(usize, usize)> = Vec::new();
         if let Some(piece) = self.ref_piece(position) {
-            let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
-            match piece.piece_type {
-                PieceType::Pawn => {
-                    let color_modifier = if piece.color == Color::White { 1 } else { -1 };
-                    if let Ok(space) = self.check_move(position, (0, color_modifier)) {
-                        if space.1.is_none() {
-                            legal_spaces.push(space);
-                        }
-                    }
-                    if let Ok(space) = self.check_move(position, (1, color_modifier)) {
-                        if space.1.is_some() && space.1.unwrap().color != piece.color {
-                            legal_spaces.push(space);
-                        }
-                    }
-                    if let Ok(space) = self.check_move(position, (-1, color_modifier)) {
-                        if space.1.is_some() && space.1.unwrap().color != piece.color {
-                            legal_spaces.push(space);
-                        }
-                    }
-                }
-                _ => {
-                    let (movement1, movement2) = piece.movement;
-                    legal_spaces.extend(self.check_around(
-                        position,
-                        movement1,
-                        piece.moves_continous,
-                    ));
-                    if let Some(movement) = movement2 {
-                        legal_spaces.extend(self.check_around(
-                            position,
-                            movement,
-                            piece.moves_continous,
-                        ));
-                    }
-                }
-            }
-            for (move_to, space) in legal_spaces {
-                if let Some(p) = space {
-                    if p.color != piece.color {
-                        results.push(move_to);
-                    }
-                } else {
-                    results.push(move_to);
-                }
+            self.movement_targets(position, piece, false)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Squares reached by the movement of `piece` standing at `position`.
+    /// With `attacks` set, every square the piece could capture on is returned instead,
+    /// whether it is empty or not.
+    fn movement_targets(
+        &self,
+        position: (usize, usize),
+        piece: &Piece,
+        attacks: bool,
+    ) -> Vec<(usize, usize)> {
+        let (captures, quiets) = self.reach(position, piece, attacks);
+        movegen::squares(captures | quiets).collect()
+    }
+
+    fn is_passant_capture(&self, piece: &Piece, target: (usize, usize)) -> bool {
+        match self.passant_connection {
+            Some((passant_pos, pawn_pos)) => {
+                piece.piece_type == PieceType::Pawn
+                    && passant_pos == target
+                    && matches!(self.ref_piece(pawn_pos), Some(pawn) if pawn.color != piece.color)
             }
+            None => false,
         }
-        results.sort();
-        results.dedup();
-        results
     }
 
     fn special_moves(&self, position: (usize, usize)) -> Vec<((usize, usize), SpecialMove)> {
         let mut special_moves: Vec<((usize, usize), SpecialMove)> = Vec::new();
-        if let Some(piece) = self.ref_piece(position) {
-            if !piece.has_moved {
-                if piece.piece_type == PieceType::Pawn {
-                    let color_modifier = if piece.color == Color::White { 2 } else { -2 };
-                    if let Ok(target_space) = self.check_move(position, (0, color_modifier)) {
-                        if target_space.1.is_none() {
-                            special_moves.push((target_space.0, SpecialMove::Pawn2Step));
-                        }
-                    }
-                }
-                if piece.piece_type == PieceType::King && !self.is_threatened(position, piece.color)
-                {
-                    let color_modifier = if piece.color == Color::White { 0 } else { 7 };
-                    let mut spaces = self.check_continous(position, (1, 0));
-                    if let Some(space) = spaces.pop() {
-                        if let Some(rook) = space.1 {
-                            if rook.piece_type == PieceType::Rook
-                                && rook.color == piece.color
-                                && !rook.has_moved
-                            {
-                                let mut can_castle = true;
-                                for spots in spaces {
-                                    if self.is_threatened(spots.0, piece.color) {
-                                        can_castle = false;
-                                    }
-                                }
-                                if can_castle {
-                                    special_moves
-                                        .push(((6, color_modifier), SpecialMove::CastlingRight));
-                                }
-                            }
-                        }
-                    }
-                    let mut spaces = self.check_continous(position, (-1, 0));
-                    if let Some(space) = spaces.pop() {
-                        if let Some(rook) = space.1 {
-                            if rook.piece_type == PieceType::Rook
-                                && rook.color == piece.color
-                                && !rook.has_moved
-                            {
-                                let mut can_castle = true;
-                                for spots in spaces {
-                                    if self.is_threatened(spots.0, piece.color) {
-                                        can_castle = false;
-                                    }
-                                }
-                                if can_castle {
-                                    special_moves
-                                        .push(((2, color_modifier), SpecialMove::CastlingLeft));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        self.special_targets(position, &mut |target, special| {
+            special_moves.push((target, special))
+        });
         special_moves
     }
 
@@ -310,16 +265,14 @@ impl ChessBoard {
         direction: (isize, isize),
     ) -> Vec<((usize, usize), Option<&Piece>)> {
         let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
-        if let Ok(target_space) = self.check_move(position, direction) {
+        let mut current = position;
+        while let Ok(target_space) = self.check_move(current, direction) {
             legal_spaces.push(target_space);
-            if target_space.1.is_none() {
-                legal_spaces.extend(self.check_continous(target_space.0, direction));
-            }
-            if self.passant_connection.is_some()
-                && self.passant_connection.unwrap().0 == target_space.0
-            {
-                legal_spaces.extend(self.check_continous(target_space.0, direction));
+            let passed = matches!(self.passant_connection, Some((passant_pos, _)) if passant_pos == target_space.0);
+            if target_space.1.is_some() && !passed {
+                break;
             }
+            current = target_space.0;
         }
         legal_spaces
     }
@@ -331,7 +284,7 @@ impl ChessBoard {
     ) -> Result<((usize, usize), Option<&Piece>), String> {
         let new_x = position.0 as isize + moves.0;
         let new_y = position.1 as isize + moves.1;
-        if new_x < 0 || new_x >= 8 || new_y < 0 || new_y >= 8 {
+        if !(0..8).contains(&new_x) || !(0..8).contains(&new_y) {
             return Err("not valid movement".to_string());
         }
         let new_pos = (new_x as usize, new_y as usize);
@@ -349,20 +302,11 @@ impl ChessBoard {
         }
     }
 
-    fn self_check(&self, move_from: (usize, usize), move_to: (usize, usize)) -> bool {
-        let mut test = self.clone_chess();
-        let piece = test.ref_piece(move_from).unwrap();
-        let color = piece.color;
-        test.force_move(move_from, move_to)
-            .expect("Error during checking for self-check:");
-        test.is_checked(color)
-    }
-
     fn force_move(
         &mut self,
         piece_pos: (usize, usize),
         new_pos: (usize, usize),
-    ) -> Result<String, String> {
+    ) -> Result<(), String> {
         if let Some(mut piece) = self.board[piece_pos.1][piece_pos.0].take() {
             if piece.piece_type == PieceType::King {
                 if piece.color == Color::White {
@@ -372,33 +316,22 @@ impl ChessBoard {
                 }
             }
             piece.moved();
-            self.board[new_pos.1][new_pos.0] = Some(piece);
-            Ok(format!("Moved from {:?} to {:?}", piece_pos, new_pos))
+            // a king taken without check rules leaves the board
+            if let Some(captured) = self.board[new_pos.1][new_pos.0].replace(piece) {
+                if captured.piece_type == PieceType::King {
+                    if captured.color == Color::White {
+                        self.white_king = (256, 256);
+                    } else {
+                        self.black_king = (256, 256);
+                    }
+                }
+            }
+            Ok(())
         } else {
             Err(format!("Can't force move, no piece at {:?}", piece_pos))
         }
     }
 
-    fn is_threatened(&self, pos: (usize, usize), color: Color) -> bool {
-        let mut direction = (0, 1);
-        if self.is_threatened_by(pos, color, direction, true) {
-            return true;
-        }
-        direction = (1, 1);
-        if self.is_threatened_by(pos, color, direction, true) {
-            return true;
-        }
-        direction = (1, 2);
-        if self.is_threatened_by(pos, color, direction, false) {
-            return true;
-        }
-        direction = (2, 1);
-        if self.is_threatened_by(pos, color, direction, false) {
-            return true;
-        }
-        false
-    }
-
     pub fn is_threatened_by(
         &self,
         pos: (usize, usize),
@@ -408,12 +341,9 @@ impl ChessBoard {
     ) -> bool {
         for spaces in self.check_around(pos, moveset, check_continous) {
             if let (p_pos, Some(piece)) = spaces {
-                if piece.color != color {
-                    for mov in self.regular_moves(p_pos) {
-                        if mov == pos {
-                            return true;
-                        }
-                    }
+                if piece.color != color && self.movement_targets(p_pos, piece, true).contains(&pos)
+                {
+                    return true;
                 }
             }
         }
@@ -430,29 +360,163 @@ impl ChessBoard {
     }
 
     pub fn is_checkmate(&self, color: Color) -> bool {
-        let king_pos = if color == Color::White {
-            self.white_king
-        } else {
-            self.black_king
-        };
-        if self.is_threatened(king_pos, color) {
-            if !self.get_moves(king_pos).is_empty() {
-                return false;
-            }
-            for y in 0..self.ref_board().len() {
-                for x in 0..self.ref_board()[0].len() {
-                    if let Some(piece) = self.ref_piece((x, y)) {
-                        if piece.color == color && !self.get_moves((x, y)).is_empty() {
-                            return false;
+        self.is_checked(color) && !self.has_legal_moves(color)
+    }
+
+    /// Whether `color` has a move that doesn't leave its king in check
+    pub fn has_legal_moves(&self, color: Color) -> bool {
+        for y in 0..self.board.len() {
+            for x in 0..self.board[0].len() {
+                if let Some(piece) = self.ref_piece((x, y)) {
+                    if piece.color == color {
+                        let mut found = false;
+                        self.piece_targets((x, y), MoveStage::All, &mut |_, _| found = true);
+                        if found {
+                            return true;
                         }
                     }
                 }
             }
-            return true;
         }
         false
     }
 
+    pub fn is_stalemate(&self, color: Color) -> bool {
+        !self.is_checked(color) && !self.has_legal_moves(color)
+    }
+
+    /// Every legal move of `color`, pawns reaching the last rank once for each promotion
+    pub fn legal_moves(&self, color: Color) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::new();
+        self.visit_moves(color, MoveStage::All, |mov| moves.push(mov));
+        moves
+    }
+
+    /// Plays `mov` including its promotion
+    pub fn make_move(&mut self, mov: &Move) -> Result<String, String> {
+        let result = self.move_piece(mov.from, mov.to)?;
+        match &mov.promotion {
+            Some(piece_type) => self.promote(mov.to, piece_type.clone()),
+            None => Ok(result),
+        }
+    }
+
+    /// Plays `mov` like `make_move` without describing it, so nothing is allocated
+    pub fn apply_move(&mut self, mov: &Move) -> Result<(), String> {
+        self.play(mov.from, mov.to)?;
+        if let Some(piece_type) = &mov.promotion {
+            let piece = self.ref_piece(mov.to).unwrap();
+            if piece.piece_type != PieceType::Pawn
+                || mov.to.1 != if piece.color == Color::White { 7 } else { 0 }
+                || *piece_type == PieceType::King
+            {
+                return Err("Tried to promote unit at wrong place or of wrong type".to_string());
+            }
+            self.board[mov.to.1][mov.to.0] = Some(piece_make(piece.color, piece_type.clone()));
+        }
+        Ok(())
+    }
+
+    /// Standard algebraic notation for moving the piece at `from` to `to`,
+    /// without promotion or check markers since those depend on what happens after
+    pub fn san(&self, from: (usize, usize), to: (usize, usize)) -> Result<String, String> {
+        let piece = self
+            .ref_piece(from)
+            .ok_or_else(|| format!("There is no piece at {:?}", from))?;
+        let special_move = match self.get_moves(from).into_iter().find(|(mov, _)| *mov == to) {
+            Some((_, special_move)) => special_move,
+            None => return Err(format!("Illegal move from {:?} to {:?}", from, to)),
+        };
+        match special_move {
+            Some(SpecialMove::CastlingRight) => return Ok("O-O".to_string()),
+            Some(SpecialMove::CastlingLeft) => return Ok("O-O-O".to_string()),
+            _ => (),
+        }
+        let target = super::to_notation(to)?;
+        let capture = matches!(self.ref_piece(to), Some(p) if p.color != piece.color)
+            || self.is_passant_capture(piece, to);
+        if piece.piece_type == PieceType::Pawn {
+            return Ok(if capture {
+                format!("{}x{}", &super::to_notation(from)?[..1], target)
+            } else {
+                target
+            });
+        }
+        let mut rivals: Vec<(usize, usize)> = Vec::new();
+        for y in 0..self.board.len() {
+            for x in 0..self.board[0].len() {
+                if let Some(other) = self.ref_piece((x, y)) {
+                    if (x, y) != from
+                        && other.color == piece.color
+                        && other.piece_type == piece.piece_type
+                        && self.get_moves((x, y)).iter().any(|(mov, _)| *mov == to)
+                    {
+                        rivals.push((x, y));
+                    }
+                }
+            }
+        }
+        let origin = super::to_notation(from)?;
+        let disambiguation = if rivals.is_empty() {
+            ""
+        } else if rivals.iter().all(|rival| rival.0 != from.0) {
+            &origin[..1]
+        } else if rivals.iter().all(|rival| rival.1 != from.1) {
+            &origin[1..]
+        } else {
+            &origin[..]
+        };
+        Ok(format!(
+            "{}{}{}{}",
+            piece.piece_type,
+            disambiguation,
+            if capture { "x" } else { "" },
+            target
+        ))
+    }
+
+    /// Whether `color` could still mate by some series of legal moves, the rule for a flag
+    /// fall in FIDE article 6.9. The pieces of the other side count, as they can take the
+    /// squares around their own king: a lone knight mates against anything but queens, and
+    /// bishops of one square color against a pawn, a knight or a bishop of the other color.
+    pub fn has_mating_material(&self, color: Color) -> bool {
+        let mut knights = 0;
+        // by the color of their squares
+        let mut bishops = [false; 2];
+        let mut blockers = Vec::new();
+        for (y, row) in self.board.iter().enumerate() {
+            for (x, piece) in row.iter().enumerate() {
+                let piece = match piece {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+                match &piece.piece_type {
+                    PieceType::King => (),
+                    piece_type if piece.color != color => blockers.push((piece_type, (x + y) % 2)),
+                    PieceType::Knight => knights += 1,
+                    PieceType::Bishop => bishops[(x + y) % 2] = true,
+                    _ => return true,
+                }
+            }
+        }
+        match (knights, bishops) {
+            (0, [false, false]) => false,
+            (1, [false, false]) => blockers
+                .iter()
+                .any(|(piece_type, _)| **piece_type != PieceType::Queen),
+            (0, [true, false]) | (0, [false, true]) => {
+                blockers
+                    .iter()
+                    .any(|&(piece_type, square)| match piece_type {
+                        PieceType::Queen | PieceType::Rook => false,
+                        PieceType::Bishop => !bishops[square],
+                        _ => true,
+                    })
+            }
+            _ => true,
+        }
+    }
+
     pub fn standard_pieces(&mut self, color: Color) {
         let mut y = if color == Color::White { 1 } else { 6 };
         for x in 0..8 {
@@ -474,13 +538,57 @@ impl ChessBoard {
         self.add_piece(piece_make(color, PieceType::Rook), (7, y));
     }
 
-    fn clone_chess(&self) -> ChessBoard {
-        ChessBoard {
-            board: self.board.clone(),
-            white_king: self.white_king,
-            black_king: self.black_king,
-            passant_connection: self.passant_connection,
+    /// Play without check, where leaving the king attacked is allowed and the king can be
+    /// captured like any other piece. Used for dark chess.
+    pub fn set_king_capture(&mut self, king_capture: bool) {
+        self.king_capture = king_capture;
+    }
+
+    pub fn has_king(&self, color: Color) -> bool {
+        self.king_position(color).is_some()
+    }
+
+    pub fn king_position(&self, color: Color) -> Option<(usize, usize)> {
+        let king_pos = if color == Color::White {
+            self.white_king
+        } else {
+            self.black_king
+        };
+        if king_pos != (256, 256)
+            && matches!(self.ref_piece(king_pos), Some(king) if king.piece_type == PieceType::King && king.color == color)
+        {
+            Some(king_pos)
+        } else {
+            None
+        }
+    }
+
+    /// Spaces occupied by pieces of `color` together with every space they can move to or attack
+    pub fn visible_squares(&self, color: Color) -> Vec<(usize, usize)> {
+        let mut visible: Vec<(usize, usize)> = Vec::new();
+        for y in 0..self.board.len() {
+            for x in 0..self.board[0].len() {
+                if let Some(piece) = self.ref_piece((x, y)) {
+                    if piece.color == color {
+                        visible.push((x, y));
+                        visible.extend(self.movement_targets((x, y), piece, false));
+                        visible.extend(self.movement_targets((x, y), piece, true));
+                        visible.extend(self.special_moves((x, y)).into_iter().map(|(mov, _)| mov));
+                    }
+                }
+            }
         }
+        visible.sort();
+        visible.dedup();
+        visible
+    }
+
+    pub fn redacted_board(&self, color: Color) -> RedactedBoard {
+        let mut redacted: RedactedBoard = Default::default();
+        for (x, y) in self.visible_squares(color) {
+            redacted[y][x] = Some(self.board[y][x].clone());
+        }
+        redacted
     }
 
     pub fn ref_board(&self) -> &[[Option<Piece>; 8]; 8] {
@@ -492,18 +600,67 @@ impl ChessBoard {
     }
 }
 
-#[derive(PartialEq)]
+/// Pieces a pawn can promote to, strongest first
+pub const PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub promotion: Option<PieceType>,
+}
+
+impl Move {
+    /// Coordinate notation as used by UCI, e.g. `e2e4` or `e7e8q`
+    pub fn to_coordinates(&self) -> String {
+        let mut text = format!(
+            "{}{}",
+            super::to_notation(self.from).unwrap(),
+            super::to_notation(self.to).unwrap()
+        );
+        if let Some(piece_type) = &self.promotion {
+            text.push_str(&piece_type.to_string().to_lowercase());
+        }
+        text
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub enum SpecialMove {
     Pawn2Step,
     CastlingLeft,
     CastlingRight,
 }
 
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+fn step_from(position: (usize, usize), direction: (isize, isize)) -> Option<(usize, usize)> {
+    let new_x = position.0 as isize + direction.0;
+    let new_y = position.1 as isize + direction.1;
+    if (0..8).contains(&new_x) && (0..8).contains(&new_y) {
+        Some((new_x as usize, new_y as usize))
+    } else {
+        None
+    }
+}
+
 pub fn init_board() -> ChessBoard {
     ChessBoard {
         board: Default::default(),
         white_king: (256, 256),
         black_king: (256, 256),
         passant_connection: None,
+        king_capture: false,
     }
 }
@@ -0,0 +1,426 @@
+use super::*;
+use std::ops::{Deref, DerefMut};
+
+/// Most moves a `MoveList` holds, standard chess never gets past 218
+pub const MAX_MOVES: usize = 256;
+
+const NO_MOVE: Move = Move {
+    from: (0, 0),
+    to: (0, 0),
+    promotion: None,
+};
+
+/// Moves kept on the stack, so generating them never allocates
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList {
+            moves: [NO_MOVE; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Panics when the list is full
+    pub fn push(&mut self, mov: Move) {
+        assert!(self.len < MAX_MOVES, "More than {} moves", MAX_MOVES);
+        self.moves[self.len] = mov;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+/// Part of the legal moves to generate. Searching the captures first often makes the
+/// quiet moves unnecessary.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MoveStage {
+    /// Captures, en passant included, and promotions
+    Captures,
+    /// Every other move, castling included
+    Quiets,
+    All,
+}
+
+/// Set of spaces, bit `x * 8 + y` stands for `(x, y)` so they come out sorted
+type Squares = u64;
+
+fn bit((x, y): (usize, usize)) -> Squares {
+    1 << (x * 8 + y)
+}
+
+pub(super) fn squares(mut set: Squares) -> impl Iterator<Item = (usize, usize)> {
+    std::iter::from_fn(move || {
+        if set == 0 {
+            return None;
+        }
+        let index = set.trailing_zeros() as usize;
+        set &= set - 1;
+        Some((index / 8, index % 8))
+    })
+}
+
+/// How many times `step` fits into `delta`, if it points that way at all
+fn steps_to(step: (isize, isize), delta: (isize, isize)) -> Option<usize> {
+    let times = if step.0 != 0 {
+        delta.0 / step.0
+    } else {
+        delta.1 / step.1
+    };
+    if times > 0 && (step.0 * times, step.1 * times) == delta {
+        Some(times as usize)
+    } else {
+        None
+    }
+}
+
+/// Whether `piece` at `from` could capture on `square`, with `occupant` telling what
+/// stands where
+fn reaches<'a>(
+    piece: &Piece,
+    from: (usize, usize),
+    square: (usize, usize),
+    occupant: &impl Fn((usize, usize)) -> Option<&'a Piece>,
+) -> bool {
+    let delta = (
+        square.0 as isize - from.0 as isize,
+        square.1 as isize - from.1 as isize,
+    );
+    piece.movement.rules().iter().any(|rule| {
+        rule.captures
+            && !(rule.initial_only && piece.has_moved)
+            && rule
+                .steps_for(piece.color)
+                .any(|step| match steps_to(step, delta) {
+                    Some(times) if rule.range.is_none_or(|range| times <= range) => {
+                        (1..times as isize).all(|i| {
+                            let between = (
+                                (from.0 as isize + step.0 * i) as usize,
+                                (from.1 as isize + step.1 * i) as usize,
+                            );
+                            occupant(between).is_none()
+                        })
+                    }
+                    _ => false,
+                })
+    })
+}
+
+impl ChessBoard {
+    /// Adds the legal moves of `color` from `stage` to `moves`, pawns reaching the last rank
+    /// once for each promotion. Together the stages give the moves of `legal_moves`.
+    pub fn generate_moves(&self, color: Color, stage: MoveStage, moves: &mut MoveList) {
+        self.visit_moves(color, stage, |mov| moves.push(mov));
+    }
+
+    /// Number of move sequences `depth` half moves long, for testing and timing the move
+    /// generator
+    pub fn perft(&self, color: Color, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut moves = MoveList::new();
+        self.generate_moves(color, MoveStage::All, &mut moves);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|mov| {
+                let mut next = self.clone();
+                next.apply_move(mov).expect("perft tried an illegal move");
+                next.perft(opponent(color), depth - 1)
+            })
+            .sum()
+    }
+
+    pub(super) fn visit_moves(&self, color: Color, stage: MoveStage, mut visit: impl FnMut(Move)) {
+        let last_rank = if color == Color::White { 7 } else { 0 };
+        for y in 0..8 {
+            for x in 0..8 {
+                let piece = match self.ref_piece((x, y)) {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+                let promotes = piece.piece_type == PieceType::Pawn;
+                // pushes to the last rank belong to the captures
+                let targets = if promotes { MoveStage::All } else { stage };
+                self.pseudo_targets((x, y), targets, &mut |to, special| {
+                    let promotion = promotes && to.1 == last_rank;
+                    let noisy = promotion
+                        || self.ref_piece(to).is_some()
+                        || self.is_passant_capture(piece, to);
+                    if (stage != MoveStage::All && noisy != (stage == MoveStage::Captures))
+                        || !self.is_legal_target((x, y), to, &special)
+                    {
+                        return;
+                    }
+                    if promotion {
+                        for promotion in PROMOTIONS.iter() {
+                            visit(Move {
+                                from: (x, y),
+                                to,
+                                promotion: Some(promotion.clone()),
+                            });
+                        }
+                    } else {
+                        visit(Move {
+                            from: (x, y),
+                            to,
+                            promotion: None,
+                        });
+                    }
+                });
+            }
+        }
+    }
+
+    /// Legal targets of the piece at `position` in sorted order, special moves last
+    pub(super) fn piece_targets(
+        &self,
+        position: (usize, usize),
+        stage: MoveStage,
+        visit: &mut impl FnMut((usize, usize), Option<SpecialMove>),
+    ) {
+        self.pseudo_targets(position, stage, &mut |to, special| {
+            if self.is_legal_target(position, to, &special) {
+                visit(to, special);
+            }
+        });
+    }
+
+    /// Castling is already checked by `special_targets`
+    pub(super) fn is_legal_target(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        special: &Option<SpecialMove>,
+    ) -> bool {
+        let castles = matches!(
+            special,
+            Some(SpecialMove::CastlingLeft) | Some(SpecialMove::CastlingRight)
+        );
+        self.king_capture || castles || !self.self_check(from, to)
+    }
+
+    /// Targets of the piece at `position` whether or not they leave its king in check.
+    /// Castling is only produced when it is legal.
+    pub(super) fn pseudo_targets(
+        &self,
+        position: (usize, usize),
+        stage: MoveStage,
+        visit: &mut impl FnMut((usize, usize), Option<SpecialMove>),
+    ) {
+        let piece = match self.ref_piece(position) {
+            Some(piece) => piece,
+            None => return,
+        };
+        let (captures, quiets) = self.reach(position, piece, false);
+        let set = match stage {
+            MoveStage::Captures => captures,
+            MoveStage::Quiets => quiets,
+            MoveStage::All => captures | quiets,
+        };
+        for target in squares(set) {
+            visit(target, None);
+        }
+        if stage != MoveStage::Captures {
+            self.special_targets(position, &mut |target, special| {
+                visit(target, Some(special))
+            });
+        }
+    }
+
+    /// Spaces reached by the movement of `piece` standing at `position`, split into
+    /// captures and moves to empty spaces. With `attacks` set, every space the piece could
+    /// capture on counts as a capture, whether it is empty or not.
+    pub(super) fn reach(
+        &self,
+        position: (usize, usize),
+        piece: &Piece,
+        attacks: bool,
+    ) -> (Squares, Squares) {
+        let mut captures: Squares = 0;
+        let mut quiets: Squares = 0;
+        for rule in piece.movement.rules() {
+            if (rule.initial_only && piece.has_moved) || (attacks && !rule.captures) {
+                continue;
+            }
+            for direction in rule.steps_for(piece.color) {
+                let mut current = position;
+                let mut distance = 0;
+                while rule.range != Some(distance) {
+                    current = match step_from(current, direction) {
+                        Some(target) => target,
+                        None => break,
+                    };
+                    distance += 1;
+                    match self.ref_piece(current) {
+                        Some(target) => {
+                            if rule.captures && (attacks || target.color != piece.color) {
+                                captures |= bit(current);
+                            }
+                            break;
+                        }
+                        None if attacks => captures |= bit(current),
+                        None if rule.captures && self.is_passant_capture(piece, current) => {
+                            captures |= bit(current)
+                        }
+                        None if rule.moves => quiets |= bit(current),
+                        None => (),
+                    }
+                }
+            }
+        }
+        (captures, quiets & !captures)
+    }
+
+    /// Double steps of unmoved pawns and castling
+    pub(super) fn special_targets(
+        &self,
+        position: (usize, usize),
+        visit: &mut impl FnMut((usize, usize), SpecialMove),
+    ) {
+        let piece = match self.ref_piece(position) {
+            Some(piece) if !piece.has_moved => piece,
+            _ => return,
+        };
+        if piece.piece_type == PieceType::Pawn {
+            let forward = if piece.color == Color::White { 1 } else { -1 };
+            let passing = step_from(position, (0, forward));
+            let target = step_from(position, (0, 2 * forward));
+            if let (Some(passing), Some(target)) = (passing, target) {
+                if self.ref_piece(passing).is_none() && self.occupant(target).is_none() {
+                    visit(target, SpecialMove::Pawn2Step);
+                }
+            }
+        }
+        if piece.piece_type == PieceType::King
+            && (self.king_capture || !self.is_threatened(position, piece.color))
+        {
+            let row = position.1;
+            let home = if piece.color == Color::White { 0 } else { 7 };
+            for &(direction, king_x, special) in [
+                (1, 6, SpecialMove::CastlingRight),
+                (-1, 2, SpecialMove::CastlingLeft),
+            ]
+            .iter()
+            {
+                let mut x = position.0 as isize + direction;
+                while (0..8).contains(&x) && self.occupant((x as usize, row)).is_none() {
+                    x += direction;
+                }
+                if !(0..8).contains(&x) {
+                    continue;
+                }
+                let rook_in_place = matches!(self.ref_piece((x as usize, row)),
+                    Some(rook) if rook.piece_type == PieceType::Rook
+                        && rook.color == piece.color
+                        && !rook.has_moved);
+                // b1 and b8 may be attacked, the king doesn't cross them
+                let crossed_safely = self.king_capture
+                    || (position.0 as isize + direction..x)
+                        .chain(x + 1..position.0 as isize)
+                        .filter(|&x| x >= 2)
+                        .all(|x| !self.is_threatened((x as usize, row), piece.color));
+                if rook_in_place && crossed_safely {
+                    visit((king_x, home), special);
+                }
+            }
+        }
+    }
+
+    /// What stands on `position` for the move generation, where the square passed by
+    /// a double step holds the pawn that made it
+    fn occupant(&self, position: (usize, usize)) -> Option<&Piece> {
+        match self.passant_connection {
+            Some((passant_pos, pawn_pos)) if passant_pos == position => self.ref_piece(pawn_pos),
+            _ => self.ref_piece(position),
+        }
+    }
+
+    /// Whether the piece at `from` could capture on `square`
+    pub(super) fn attacks(&self, from: (usize, usize), square: (usize, usize)) -> bool {
+        match self.ref_piece(from) {
+            Some(piece) => reaches(piece, from, square, &|space| self.ref_piece(space)),
+            None => false,
+        }
+    }
+
+    /// Whether a piece of the other side than `color` could capture on `pos`
+    pub(super) fn is_threatened(&self, pos: (usize, usize), color: Color) -> bool {
+        self.is_attacked(pos, color, &|square| self.ref_piece(square))
+    }
+
+    /// Whether moving the piece at `from` to `to` leaves its king in check, tested without
+    /// playing the move
+    pub(super) fn self_check(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let piece = match self.ref_piece(from) {
+            Some(piece) => piece,
+            None => return false,
+        };
+        let king = if piece.piece_type == PieceType::King {
+            to
+        } else {
+            match self.king_position(piece.color) {
+                Some(king) => king,
+                None => return false,
+            }
+        };
+        let captured = match self.passant_connection {
+            Some((passant_pos, pawn_pos)) if self.is_passant_capture(piece, to) => {
+                debug_assert_eq!(passant_pos, to);
+                Some(pawn_pos)
+            }
+            _ => None,
+        };
+        self.is_attacked(king, piece.color, &|square| {
+            if square == to {
+                Some(piece)
+            } else if square == from || Some(square) == captured {
+                None
+            } else {
+                self.ref_piece(square)
+            }
+        })
+    }
+
+    fn is_attacked<'a>(
+        &'a self,
+        pos: (usize, usize),
+        color: Color,
+        occupant: &impl Fn((usize, usize)) -> Option<&'a Piece>,
+    ) -> bool {
+        (0..8).any(|y| {
+            (0..8).any(|x| match occupant((x, y)) {
+                Some(piece) => piece.color != color && reaches(piece, (x, y), pos, occupant),
+                None => false,
+            })
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+/// Value the king gets when choosing the cheapest attacker, it always goes last
+const KING_VALUE: i32 = 100_000;
+
+impl ChessBoard {
+    /// Static exchange evaluation: material won or lost in centipawns when `mov` starts
+    /// a series of captures on its target and both sides always recapture with their
+    /// cheapest piece, stopping whenever that is better for them. Pieces behind a capturer
+    /// join in once it is gone. Pins are not considered, and the king only captures last.
+    pub fn see(&self, mov: &Move) -> i32 {
+        let mut board = self.clone();
+        let target = mov.to;
+        let mover = match board.board[mov.from.1][mov.from.0].take() {
+            Some(piece) => piece,
+            None => return 0,
+        };
+        let mut first_gain = match board.ref_piece(target) {
+            Some(piece) => piece.value(),
+            None if self.is_passant_capture(&mover, target) => {
+                let (_, pawn) = self.passant_connection.unwrap();
+                board.board[pawn.1][pawn.0] = None;
+                piece_make(opponent(mover.color), PieceType::Pawn).value()
+            }
+            None => 0,
+        };
+        let mut on_target = match &mov.promotion {
+            Some(piece_type) => {
+                let promoted = piece_make(mover.color, piece_type.clone());
+                first_gain += promoted.value() - mover.value();
+                promoted
+            }
+            None => mover,
+        };
+        board.board[target.1][target.0] = Some(on_target.clone());
+        // at most every piece on the board takes part
+        let mut gains = [0; 64];
+        gains[0] = first_gain;
+        let mut count = 1;
+        let mut side = opponent(on_target.color);
+        while let Some(position) = board.cheapest_attacker(target, side) {
+            let mut capturer = board.board[position.1][position.0].take().unwrap();
+            if capturer.piece_type == PieceType::King && board.is_threatened(target, side) {
+                break;
+            }
+            let mut gain = on_target.value();
+            let last_rank = if side == Color::White { 7 } else { 0 };
+            if capturer.piece_type == PieceType::Pawn && target.1 == last_rank {
+                let queen = piece_make(side, PieceType::Queen);
+                gain += queen.value() - capturer.value();
+                capturer = queen;
+            }
+            gains[count] = gain - gains[count - 1];
+            count += 1;
+            board.board[target.1][target.0] = Some(capturer.clone());
+            on_target = capturer;
+            side = opponent(side);
+        }
+        for index in (1..count).rev() {
+            gains[index - 1] = -(-gains[index - 1]).max(gains[index]);
+        }
+        gains[0]
+    }
+
+    /// Piece of `color` attacking `square` that is worth the least, the king counting most
+    fn cheapest_attacker(&self, square: (usize, usize), color: Color) -> Option<(usize, usize)> {
+        let mut cheapest: Option<((usize, usize), i32)> = None;
+        for y in 0..8 {
+            for x in 0..8 {
+                let piece = match self.ref_piece((x, y)) {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+                let value = if piece.piece_type == PieceType::King {
+                    KING_VALUE
+                } else {
+                    piece.value()
+                };
+                if cheapest.is_none_or(|(_, least)| value < least) && self.attacks((x, y), square) {
+                    cheapest = Some(((x, y), value));
+                }
+            }
+        }
+        cheapest.map(|(position, _)| position)
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+use std::convert::TryFrom;
+
+/// Unchecked fields of a `ChessBoard`, validated before a board is built from them
+#[derive(serde::Deserialize)]
+pub struct ChessBoardData {
+    board: Board,
+    white_king: (usize, usize),
+    black_king: (usize, usize),
+    passant_connection: Option<((usize, usize), (usize, usize))>,
+    king_capture: bool,
+}
+
+impl TryFrom<ChessBoardData> for ChessBoard {
+    type Error = String;
+
+    fn try_from(data: ChessBoardData) -> Result<Self, Self::Error> {
+        for &(color, king_pos) in [
+            (Color::White, data.white_king),
+            (Color::Black, data.black_king),
+        ]
+        .iter()
+        {
+            let kings: Vec<(usize, usize)> = (0..8)
+                .flat_map(|y| (0..8).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    matches!(&data.board[y][x], Some(piece) if piece.piece_type == PieceType::King && piece.color == color)
+                })
+                .collect();
+            // without check rules a captured king is gone, and its field says so
+            if kings.is_empty() && data.king_capture && king_pos == (256, 256) {
+                continue;
+            }
+            if kings.len() != 1 {
+                return Err(format!(
+                    "Expected one {} king, found {}",
+                    color_name(color),
+                    kings.len()
+                ));
+            }
+            if kings[0] != king_pos {
+                return Err(format!(
+                    "The {} king is at {:?}, not at {:?}",
+                    color_name(color),
+                    kings[0],
+                    king_pos
+                ));
+            }
+        }
+        if let Some((passant_pos, pawn_pos)) = data.passant_connection {
+            let on_board = |(x, y): (usize, usize)| x < 8 && y < 8;
+            if !on_board(passant_pos)
+                || !on_board(pawn_pos)
+                || data.board[passant_pos.1][passant_pos.0].is_some()
+                || !matches!(&data.board[pawn_pos.1][pawn_pos.0], Some(pawn) if pawn.piece_type == PieceType::Pawn)
+            {
+                return Err(format!(
+                    "Invalid en passant connection {:?}",
+                    (passant_pos, pawn_pos)
+                ));
+            }
+        }
+        Ok(ChessBoard {
+            board: data.board,
+            white_king: data.white_king,
+            black_king: data.black_king,
+            passant_connection: data.passant_connection,
+            king_capture: data.king_capture,
+        })
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    if color == Color::White {
+        "white"
+    } else {
+        "black"
+    }
+}
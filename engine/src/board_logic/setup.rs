@@ -0,0 +1,299 @@
+use super::*;
+
+/// Which castlings are still allowed, independent of whether they are possible right now
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> Self {
+        CastlingRights {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true,
+        }
+    }
+
+    /// Reads the castling field of FEN, e.g. `KQkq`, `Kq` or `-`
+    pub fn from_fen(text: &str) -> Result<Self, String> {
+        let mut rights = CastlingRights::default();
+        if text == "-" {
+            return Ok(rights);
+        }
+        for symbol in text.chars() {
+            let right = match symbol {
+                'K' => &mut rights.white_king_side,
+                'Q' => &mut rights.white_queen_side,
+                'k' => &mut rights.black_king_side,
+                'q' => &mut rights.black_queen_side,
+                _ => return Err(format!("Invalid castling rights {:?}", text)),
+            };
+            *right = true;
+        }
+        Ok(rights)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let text: String = [
+            (self.white_king_side, 'K'),
+            (self.white_queen_side, 'Q'),
+            (self.black_king_side, 'k'),
+            (self.black_queen_side, 'q'),
+        ]
+        .iter()
+        .filter(|(allowed, _)| *allowed)
+        .map(|(_, symbol)| symbol)
+        .collect();
+        if text.is_empty() {
+            "-".to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Rights of `color` as (king side, queen side)
+    pub fn of(&self, color: Color) -> (bool, bool) {
+        match color {
+            Color::White => (self.white_king_side, self.white_queen_side),
+            Color::Black => (self.black_king_side, self.black_queen_side),
+        }
+    }
+}
+
+/// Position put together piece by piece. Nothing is checked until `validate` or `build`,
+/// so pieces can be moved around freely in between.
+///
+/// ```
+/// # use maltebl_chess::{board_logic::*, piece_logic::*};
+/// let (board, turn) = BoardSetup::new()
+///     .put((4, 0), piece_make(Color::White, PieceType::King))
+///     .put((0, 0), piece_make(Color::White, PieceType::Rook))
+///     .put((4, 7), piece_make(Color::Black, PieceType::King))
+///     .side_to_move(Color::Black)
+///     .build()
+///     .unwrap();
+/// assert_eq!(turn, Color::Black);
+/// assert!(!board.is_checked(Color::Black));
+/// ```
+#[derive(Clone)]
+pub struct BoardSetup {
+    board: Board,
+    turn: Color,
+    castling: CastlingRights,
+    en_passant: Option<(usize, usize)>,
+}
+
+impl Default for BoardSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardSetup {
+    /// Empty board with White to move
+    pub fn new() -> Self {
+        BoardSetup {
+            board: Default::default(),
+            turn: Color::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+        }
+    }
+
+    /// The starting position of standard chess
+    pub fn standard() -> Self {
+        let mut board = init_board();
+        board.standard_pieces(Color::White);
+        board.standard_pieces(Color::Black);
+        BoardSetup::from_board(&board, Color::White)
+    }
+
+    /// Setup of an existing position, castling rights follow from unmoved kings and rooks
+    pub fn from_board(board: &ChessBoard, turn: Color) -> Self {
+        BoardSetup {
+            board: board.board.clone(),
+            turn,
+            castling: board.castling_rights(),
+            en_passant: board.passant_connection.map(|(square, _)| square),
+        }
+    }
+
+    /// Places `piece` at `position`, replacing whatever stood there.
+    /// Positions are (file, rank) from 0 to 7, anything else panics.
+    pub fn put(&mut self, position: (usize, usize), piece: Piece) -> &mut Self {
+        self.board[position.1][position.0] = Some(piece);
+        self
+    }
+
+    pub fn remove(&mut self, position: (usize, usize)) -> &mut Self {
+        self.board[position.1][position.0] = None;
+        self
+    }
+
+    pub fn piece_at(&self, position: (usize, usize)) -> Option<&Piece> {
+        self.board[position.1][position.0].as_ref()
+    }
+
+    pub fn side_to_move(&mut self, color: Color) -> &mut Self {
+        self.turn = color;
+        self
+    }
+
+    pub fn castling(&mut self, rights: CastlingRights) -> &mut Self {
+        self.castling = rights;
+        self
+    }
+
+    /// Square a pawn skipped with its double step on the last move
+    pub fn en_passant(&mut self, square: Option<(usize, usize)>) -> &mut Self {
+        self.en_passant = square;
+        self
+    }
+
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn en_passant_square(&self) -> Option<(usize, usize)> {
+        self.en_passant
+    }
+
+    pub(super) fn pieces(&self) -> impl Iterator<Item = ((usize, usize), &Piece)> {
+        self.board.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(x, space)| space.as_ref().map(|piece| ((x, y), piece)))
+        })
+    }
+
+    /// Checks that the position can come up in a game
+    pub fn validate(&self) -> Result<(), String> {
+        for &color in [Color::White, Color::Black].iter() {
+            let own = || self.pieces().filter(|(_, piece)| piece.color == color);
+            let kings = own()
+                .filter(|(_, piece)| piece.piece_type == PieceType::King)
+                .count();
+            if kings != 1 {
+                return Err(format!("Expected one {:?} king, found {}", color, kings));
+            }
+            let count = own().count();
+            if count > 16 {
+                return Err(format!("{:?} has {} pieces, at most 16 fit", color, count));
+            }
+            let pawns = own()
+                .filter(|(_, piece)| piece.piece_type == PieceType::Pawn)
+                .count();
+            if pawns > 8 {
+                return Err(format!("{:?} has {} pawns, at most 8 fit", color, pawns));
+            }
+            let (king_side, queen_side) = self.castling.of(color);
+            for &(allowed, rook_x, side) in
+                [(king_side, 7, "king"), (queen_side, 0, "queen")].iter()
+            {
+                let home = if color == Color::White { 0 } else { 7 };
+                let in_place = |x: usize, piece_type: PieceType| {
+                    matches!(self.piece_at((x, home)),
+                        Some(piece) if piece.color == color && piece.piece_type == piece_type)
+                };
+                if allowed && !(in_place(4, PieceType::King) && in_place(rook_x, PieceType::Rook)) {
+                    return Err(format!(
+                        "{:?} can't castle {} side without king and rook in place",
+                        color, side
+                    ));
+                }
+            }
+        }
+        if let Some((position, _)) = self
+            .pieces()
+            .find(|((_, y), piece)| piece.piece_type == PieceType::Pawn && (*y == 0 || *y == 7))
+        {
+            return Err(format!(
+                "There is a pawn on the back rank at {}",
+                super::super::to_notation(position)?
+            ));
+        }
+        if let Some(square) = self.en_passant {
+            let (rank, pawn_rank, start_rank) = if self.turn == Color::White {
+                (5, 4, 6)
+            } else {
+                (2, 3, 1)
+            };
+            let pawn_in_place = matches!(self.piece_at((square.0, pawn_rank)),
+                Some(pawn) if pawn.piece_type == PieceType::Pawn && pawn.color != self.turn);
+            if square.1 != rank
+                || !pawn_in_place
+                || self.piece_at(square).is_some()
+                || self.piece_at((square.0, start_rank)).is_some()
+            {
+                return Err(format!(
+                    "No pawn can have just passed {}",
+                    super::super::to_notation(square)?
+                ));
+            }
+        }
+        let board = self.to_board();
+        let waiting = match self.turn {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        if board.is_checked(waiting) {
+            return Err(format!(
+                "{:?} is in check but it's {:?}'s move",
+                waiting, self.turn
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates the setup and returns the board with the side to move
+    pub fn build(&self) -> Result<(ChessBoard, Color), String> {
+        self.validate()?;
+        Ok((self.to_board(), self.turn))
+    }
+
+    /// Board without validation, pawns off their starting rank and kings or rooks
+    /// without castling rights count as moved. With several kings of a color the first one
+    /// found is tracked.
+    pub(super) fn to_board(&self) -> ChessBoard {
+        let mut board = init_board();
+        for ((x, y), piece) in self.pieces() {
+            let mut piece = piece.clone();
+            let color = piece.color;
+            let home = if color == Color::White { 0 } else { 7 };
+            let (king_side, queen_side) = self.castling.of(color);
+            piece.has_moved = match piece.piece_type {
+                PieceType::Pawn => y != if color == Color::White { 1 } else { 6 },
+                PieceType::King => (x, y) != (4, home) || !(king_side || queen_side),
+                PieceType::Rook => match (x, y) {
+                    (7, y) if y == home => !king_side,
+                    (0, y) if y == home => !queen_side,
+                    _ => true,
+                },
+                _ => false,
+            };
+            if piece.piece_type == PieceType::King && !board.has_king(color) {
+                if color == Color::White {
+                    board.white_king = (x, y);
+                } else {
+                    board.black_king = (x, y);
+                }
+            }
+            board.board[y][x] = Some(piece);
+        }
+        if let Some(square) = self.en_passant {
+            let pawn_rank = if square.1 == 2 { 3 } else { 4 };
+            board.passant_connection = Some((square, (square.0, pawn_rank)));
+        }
+        board
+    }
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+impl ChessBoard {
+    /// The position seen from the other side: ranks are reversed and colours swapped.
+    /// Returns it with the side to move after `turn`, the other colour, so it is the same
+    /// position. Castling rights and the en passant square go along to the other side.
+    pub fn mirror_vertical(&self, turn: Color) -> (ChessBoard, Color) {
+        (self.transform(|(x, y)| (x, 7 - y), true), opponent(turn))
+    }
+
+    /// Files reversed, a to h, with `turn` still to move. Kings and rooks lose their
+    /// castling rights, as castling only works with the king starting on the e-file.
+    pub fn mirror_horizontal(&self, turn: Color) -> (ChessBoard, Color) {
+        (self.transform(|(x, y)| (7 - x, y), false), turn)
+    }
+
+    /// Board turned half a circle with colours swapped, like both mirrors together.
+    /// Returns it with the other colour than `turn` to move, castling rights are lost as
+    /// in `mirror_horizontal`.
+    pub fn rotate(&self, turn: Color) -> (ChessBoard, Color) {
+        (
+            self.transform(|(x, y)| (7 - x, 7 - y), true),
+            opponent(turn),
+        )
+    }
+
+    fn transform(
+        &self,
+        square: impl Fn((usize, usize)) -> (usize, usize),
+        swap_colors: bool,
+    ) -> ChessBoard {
+        let keeps_files = square((0, 0)).0 == 0;
+        let mut board = init_board();
+        board.king_capture = self.king_capture;
+        for (y, row) in self.board.iter().enumerate() {
+            for (x, space) in row.iter().enumerate() {
+                if let Some(piece) = space {
+                    let mut piece = piece.clone();
+                    if swap_colors {
+                        piece.color = opponent(piece.color);
+                    }
+                    if !keeps_files && matches!(piece.piece_type, PieceType::King | PieceType::Rook)
+                    {
+                        piece.has_moved = true;
+                    }
+                    let (x, y) = square((x, y));
+                    board.board[y][x] = Some(piece);
+                }
+            }
+        }
+        // missing kings keep their placeholder outside the board
+        let king = |position: (usize, usize)| {
+            if position.0 < 8 {
+                square(position)
+            } else {
+                position
+            }
+        };
+        let (white_king, black_king) = (king(self.white_king), king(self.black_king));
+        if swap_colors {
+            board.white_king = black_king;
+            board.black_king = white_king;
+        } else {
+            board.white_king = white_king;
+            board.black_king = black_king;
+        }
+        board.passant_connection = self
+            .passant_connection
+            .map(|(passed, pawn)| (square(passed), square(pawn)));
+        board
+    }
+}
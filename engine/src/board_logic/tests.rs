@@ -1,8 +1,10 @@
+#![allow(clippy::bool_assert_comparison)]
 use crate::{board_logic::*, console_display::*, *};
 #[test]
 fn it_translates() {
     assert_eq!(to_coords("a5".to_string()).unwrap(), (0, 4));
     assert_eq!(to_notation((0, 4)).unwrap(), ("a5"));
+    assert!(to_coords("é".to_string()).is_err());
 }
 #[test]
 #[should_panic(expected = "Tried to add piece at non-empty space at (0, 0)")]
@@ -167,6 +169,487 @@ fn finds_mov() {
     let mut movs = board.get_moves((3, 1));
     movs.retain(|(mov, _)| *mov == (1, 3));
     for (mov, special) in movs {
-        println!("{:?} {}", mov, { special == None });
+        println!("{:?} {}", mov, special.is_none());
     }
 }
+
+#[test]
+fn parses_betza() {
+    let wazir = Movement::parse("W").unwrap();
+    assert_eq!(wazir.rules()[0].steps.len(), 4);
+    assert_eq!(wazir.rules()[0].range, Some(1));
+    let nightrider = Movement::parse("NN").unwrap();
+    assert_eq!(nightrider.rules()[0].steps.len(), 8);
+    assert_eq!(nightrider.rules()[0].range, None);
+    let pawn = Movement::parse("mfWcfF").unwrap();
+    assert_eq!(pawn.rules()[0].steps, vec![(0, 1)]);
+    assert_eq!(
+        (pawn.rules()[0].moves, pawn.rules()[0].captures),
+        (true, false)
+    );
+    assert_eq!(pawn.rules()[1].steps, vec![(1, 1), (-1, 1)]);
+    assert_eq!(
+        (pawn.rules()[1].moves, pawn.rules()[1].captures),
+        (false, true)
+    );
+    assert_eq!(Movement::parse("ffN").unwrap().rules()[0].steps.len(), 2);
+    assert_eq!(Movement::parse("fsW").unwrap().rules()[0].steps.len(), 3);
+    assert_eq!(Movement::parse("R4").unwrap().rules()[0].range, Some(4));
+    assert!(Movement::parse("X").is_err());
+    assert!(Movement::parse("RR").is_err());
+    assert!(Movement::parse("mf").is_err());
+}
+
+#[test]
+fn fairy_moves() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), (0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::King), (7, 7));
+    let camel = custom_piece(Color::White, 'C', "C").unwrap();
+    board.add_piece(camel, (3, 3));
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), (4, 6));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), (2, 6));
+    print_board(board.ref_board());
+    assert_eq!(
+        board.regular_moves((3, 3)),
+        vec![(0, 2), (0, 4), (2, 0), (4, 0), (4, 6), (6, 2), (6, 4)]
+    );
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), (0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::King), (7, 7));
+    let berolina = custom_piece(Color::Black, 'P', "mfFcfW").unwrap();
+    board.add_piece(berolina, (3, 3));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), (3, 2));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), (2, 2));
+    assert_eq!(board.regular_moves((3, 3)), vec![(3, 2), (4, 2)]);
+}
+
+#[test]
+fn fairy_attacks() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), (0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::King), (7, 7));
+    let nightrider = custom_piece(Color::Black, 'M', "NN").unwrap();
+    board.add_piece(nightrider, (4, 2));
+    print_board(board.ref_board());
+    assert_eq!(true, board.is_checked(Color::White));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), (2, 1));
+    assert_eq!(false, board.is_checked(Color::White));
+    let archbishop = custom_piece(Color::Black, 'A', "BN").unwrap();
+    board.add_piece(archbishop, (1, 2));
+    assert_eq!(true, board.is_checked(Color::White));
+    assert_eq!(
+        "A",
+        format!("{}", piece_make(Color::White, archbishop_type()))
+    );
+}
+
+fn archbishop_type() -> PieceType {
+    PieceType::Custom('A', "BN".to_string())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_board() {
+    let mut board: ChessBoard = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    board.move_piece((3, 1), (3, 3)).unwrap();
+    let json = serde_json::to_string(&board).unwrap();
+    let loaded: ChessBoard = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.passant_connection, Some(((3, 2), (3, 3))));
+    assert_eq!(loaded.white_king, (4, 0));
+    assert!(loaded.ref_piece((3, 3)).unwrap().has_moved);
+    print_board(loaded.ref_board());
+
+    let moved_king = json.replace("\"white_king\":[4,0]", "\"white_king\":[4,1]");
+    assert!(serde_json::from_str::<ChessBoard>(&moved_king)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("The white king is at (4, 0)"));
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), (4, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Queen), (4, 1));
+    board.black_king = (4, 1);
+    let json = serde_json::to_string(&board).unwrap();
+    assert!(serde_json::from_str::<ChessBoard>(&json)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("Expected one black king, found 0"));
+}
+
+#[test]
+fn castling_moves_the_rook() {
+    let mut board = ChessBoard::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1")
+        .unwrap()
+        .board;
+    board.move_piece((4, 0), (6, 0)).unwrap();
+    assert_eq!(board.ref_piece((5, 0)).unwrap().piece_type, PieceType::Rook);
+    assert!(board.ref_piece((7, 0)).is_none());
+    board.move_piece((4, 7), (2, 7)).unwrap();
+    assert_eq!(board.ref_piece((3, 7)).unwrap().piece_type, PieceType::Rook);
+    assert!(board.ref_piece((0, 7)).is_none());
+    assert_eq!(
+        board.to_fen(Color::White, 2, 2),
+        "2kr3r/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 w - - 2 2"
+    );
+    // an attacked b-file doesn't stop castling long, an attacked d-file does
+    let board = ChessBoard::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1")
+        .unwrap()
+        .board;
+    assert!(board
+        .get_moves((4, 0))
+        .iter()
+        .any(|(mov, _)| *mov == (2, 0)));
+    let board = ChessBoard::from_fen("3rk3/8/8/8/8/8/8/R3K3 w Q - 0 1")
+        .unwrap()
+        .board;
+    assert!(!board
+        .get_moves((4, 0))
+        .iter()
+        .any(|(mov, _)| *mov == (2, 0)));
+}
+
+#[test]
+fn en_passant() {
+    let mut board = ChessBoard::from_fen(STARTING_FEN).unwrap().board;
+    board.move_piece((4, 1), (4, 3)).unwrap();
+    board.move_piece((0, 6), (0, 5)).unwrap();
+    board.move_piece((4, 3), (4, 4)).unwrap();
+    board.move_piece((3, 6), (3, 4)).unwrap();
+    assert_eq!(
+        board.to_fen(Color::White, 0, 3),
+        "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+    );
+    board.move_piece((4, 4), (3, 5)).unwrap();
+    assert!(board.ref_piece((3, 4)).is_none());
+    // the chance is gone after one move
+    let mut board = ChessBoard::from_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1")
+        .unwrap()
+        .board;
+    board.move_piece((4, 1), (4, 3)).unwrap();
+    assert!(board
+        .get_moves((3, 3))
+        .iter()
+        .any(|(mov, _)| *mov == (4, 2)));
+    board.move_piece((4, 7), (3, 7)).unwrap();
+    board.move_piece((4, 0), (3, 0)).unwrap();
+    assert!(!board
+        .get_moves((3, 3))
+        .iter()
+        .any(|(mov, _)| *mov == (4, 2)));
+}
+
+#[test]
+fn pawn_needs_a_free_path() {
+    let board = ChessBoard::from_fen("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1")
+        .unwrap()
+        .board;
+    assert!(board.get_moves((4, 1)).is_empty());
+    // castling also ends the chance to take en passant
+    let mut board = ChessBoard::from_fen("4k3/3p4/8/4P3/8/8/8/4K2R b K - 0 1")
+        .unwrap()
+        .board;
+    board.move_piece((3, 6), (3, 4)).unwrap();
+    board.move_piece((4, 0), (6, 0)).unwrap();
+    assert_eq!(
+        board.to_fen(Color::Black, 0, 2),
+        "4k3/8/8/3pP3/8/8/8/5RK1 b - - 0 2"
+    );
+}
+
+#[test]
+fn reads_fen() {
+    let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+    let position = ChessBoard::from_fen(fen).unwrap();
+    assert_eq!(position.turn, Color::White);
+    assert_eq!(position.halfmove_clock, 4);
+    assert_eq!(position.fullmove, 4);
+    assert_eq!(position.board.to_fen(Color::White, 4, 4), fen);
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let board = ChessBoard::from_fen(kiwipete).unwrap().board;
+    assert_eq!(board.legal_moves(Color::White).len(), 48);
+    assert!(ChessBoard::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+    assert!(
+        ChessBoard::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err()
+    );
+    assert!(ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1").is_err());
+    assert!(ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - é 0 1").is_err());
+}
+
+#[test]
+fn validates_setups() {
+    let king = |color| piece_make(color, PieceType::King);
+    let mut setup = BoardSetup::new();
+    setup.put((4, 0), king(Color::White));
+    assert!(setup.validate().unwrap_err().contains("Black king"));
+    setup.put((4, 7), king(Color::Black));
+    assert!(setup.validate().is_ok());
+    setup.put((3, 0), king(Color::White));
+    assert!(setup.validate().is_err());
+    setup.remove((3, 0));
+
+    setup.put((0, 7), piece_make(Color::White, PieceType::Pawn));
+    assert!(setup.validate().unwrap_err().contains("back rank"));
+    setup.put((0, 7), piece_make(Color::White, PieceType::Rook));
+    // Black can't be in check with White to move
+    assert!(setup.validate().unwrap_err().contains("in check"));
+    setup.side_to_move(Color::Black);
+    assert!(setup.validate().is_ok());
+    setup.remove((0, 7));
+
+    setup.castling(CastlingRights::from_fen("K").unwrap());
+    assert!(setup.validate().unwrap_err().contains("castle"));
+    setup.put((7, 0), piece_make(Color::White, PieceType::Rook));
+    let (board, turn) = setup.build().unwrap();
+    assert_eq!(board.to_fen(turn, 0, 1), "4k3/8/8/8/8/8/8/4K2R b K - 0 1");
+
+    for x in 0..8 {
+        for y in 1..3 {
+            setup.put((x, y), piece_make(Color::White, PieceType::Knight));
+        }
+    }
+    assert!(setup.validate().unwrap_err().contains("at most 16"));
+
+    let mut setup = BoardSetup::from_board(
+        &ChessBoard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1")
+            .unwrap()
+            .board,
+        Color::White,
+    );
+    setup.en_passant(Some((3, 5)));
+    assert!(setup.validate().is_ok());
+    setup.en_passant(Some((4, 5)));
+    assert!(setup.validate().is_err());
+    setup.en_passant(Some((3, 5))).side_to_move(Color::Black);
+    assert!(setup.validate().is_err());
+}
+
+#[test]
+fn attack_queries() {
+    let board = ChessBoard::from_fen("7k/8/8/8/1b6/8/3NR3/r3K3 w - - 0 1")
+        .unwrap()
+        .board;
+    assert_eq!(board.checkers(Color::White), vec![(0, 0)]);
+    assert!(board.checkers(Color::Black).is_empty());
+    assert_eq!(board.attackers_of((2, 3), Color::White), vec![(3, 1)]);
+    assert_eq!(
+        board.attackers_of((4, 3), Color::White),
+        vec![(3, 1), (4, 1)]
+    );
+    let attacked = board.attacked_squares(Color::Black);
+    assert!(attacked.contains(&(3, 0)) && attacked.contains(&(3, 1)));
+    assert!(!attacked.contains(&(4, 1)));
+
+    // the knight is pinned by the bishop, the rook isn't since it shields nothing
+    let pins = board.pinned_pieces(Color::White);
+    assert_eq!(
+        pins,
+        vec![Pin {
+            pinned: (3, 1),
+            pinner: (1, 3),
+            ray: vec![(1, 3), (2, 2)],
+        }]
+    );
+
+    let board = ChessBoard::from_fen("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1")
+        .unwrap()
+        .board;
+    let discovered = |to| Move {
+        from: (4, 3),
+        to,
+        promotion: None,
+    };
+    assert!(board.is_discovered_check(&discovered((3, 5))));
+    assert!(!board.is_discovered_check(&Move {
+        from: (4, 0),
+        to: (3, 0),
+        promotion: None,
+    }));
+}
+
+#[test]
+fn static_exchange() {
+    let see = |fen: &str, mov: &str| {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        let mov = position
+            .board
+            .legal_moves(position.turn)
+            .into_iter()
+            .find(|m| m.to_coordinates() == mov)
+            .unwrap();
+        position.board.see(&mov)
+    };
+    // free pawn
+    assert_eq!(
+        see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+        100
+    );
+    // the queen behind the rook and the queen behind the bishop join in
+    assert_eq!(
+        see(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        ),
+        -220
+    );
+    // the king can't recapture against the doubled rooks
+    assert_eq!(see("4k3/4r3/8/8/8/3p4/4R3/4R1K1 w - - 0 1", "e2e7"), 500);
+    assert_eq!(see("4k3/8/3r4/8/8/3p4/8/3RK3 w - - 0 1", "d1d3"), -400);
+    // a quiet move onto an attacked square
+    assert_eq!(see("4k3/8/3p4/8/4N3/8/8/4K3 w - - 0 1", "e4c5"), -320);
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see("1r5k/P7/8/8/8/8/8/K7 w - - 0 1", "a7b8q"), 1300);
+    assert_eq!(see("1rk5/P7/8/8/8/8/8/K7 w - - 0 1", "a7b8q"), 400);
+    assert_eq!(see("8/8/8/8/8/2kq4/3R4/3RK3 b - - 0 1", "d3d2"), -400);
+}
+
+#[test]
+fn hashes_positions() {
+    let hash = |fen: &str| {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        position.board.zobrist_hash(position.turn)
+    };
+    let start = ChessBoard::from_fen(STARTING_FEN).unwrap().board;
+    let mut board = start.clone();
+    for mov in ["g1f3", "g8f6", "f3g1", "f6g8"].iter() {
+        let from = to_coords(mov[..2].to_string()).unwrap();
+        let to = to_coords(mov[2..].to_string()).unwrap();
+        board
+            .make_move(&Move {
+                from,
+                to,
+                promotion: None,
+            })
+            .unwrap();
+    }
+    assert_eq!(board.zobrist_hash(Color::White), hash(STARTING_FEN));
+    assert_ne!(start.zobrist_hash(Color::Black), hash(STARTING_FEN));
+    // an en passant square nobody can take on doesn't matter
+    assert_eq!(
+        hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+        hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+    );
+    assert_ne!(
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"),
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")
+    );
+    assert_ne!(
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1")
+    );
+}
+
+#[test]
+fn perft() {
+    let count = |fen: &str, depth: usize| {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        position.board.perft(position.turn, depth)
+    };
+    assert_eq!(count(STARTING_FEN, 3), 8902);
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(count(kiwipete, 2), 2039);
+    // en passant that would expose the king along the rank
+    assert_eq!(count("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3), 2812);
+}
+
+#[test]
+fn generates_captures_first() {
+    let position = ChessBoard::from_fen("4k3/1P6/8/3pP3/8/2n5/8/R3K2R w KQ d6 0 1").unwrap();
+    let board = &position.board;
+    let mut captures = MoveList::new();
+    board.generate_moves(Color::White, MoveStage::Captures, &mut captures);
+    let coordinates: Vec<String> = captures.iter().map(Move::to_coordinates).collect();
+    assert_eq!(
+        coordinates,
+        vec!["e5d6", "b7b8q", "b7b8r", "b7b8b", "b7b8n"]
+    );
+    let mut moves = MoveList::new();
+    board.generate_moves(Color::White, MoveStage::Quiets, &mut moves);
+    assert!(moves.iter().all(|mov| !captures.contains(mov)));
+    assert!(moves.iter().any(|mov| mov.to_coordinates() == "e1g1"));
+    board.generate_moves(Color::White, MoveStage::Captures, &mut moves);
+    assert_eq!(moves.len(), board.legal_moves(Color::White).len());
+}
+
+#[test]
+fn mirrors_positions() {
+    let position =
+        ChessBoard::from_fen("r3k2r/p1p2ppp/8/1pPp4/8/8/PP1PPPPP/R3K1R1 w Qkq b6 0 1").unwrap();
+    let (board, turn) = (&position.board, position.turn);
+    let fen = |(board, turn): (ChessBoard, Color)| board.to_fen(turn, 0, 1);
+    assert_eq!(
+        fen(board.mirror_vertical(turn)),
+        "r3k1r1/pp1ppppp/8/8/1PpP4/8/P1P2PPP/R3K2R b KQq b3 0 1"
+    );
+    assert_eq!(
+        fen(board.mirror_horizontal(turn)),
+        "r2k3r/ppp2p1p/8/4pPp1/8/8/PPPPP1PP/1R1K3R w - g6 0 1"
+    );
+    assert_eq!(
+        fen(board.rotate(turn)),
+        "1r1k3r/ppppp1pp/8/8/4PpP1/8/PPP2P1P/R2K3R b - g3 0 1"
+    );
+    let (mirrored, mirrored_turn) = board.mirror_vertical(turn);
+    assert_eq!(
+        fen(mirrored.mirror_vertical(mirrored_turn)),
+        board.to_fen(turn, 0, 1)
+    );
+    let (rotated, rotated_turn) = board.rotate(turn);
+    assert_eq!(
+        fen(rotated.rotate(rotated_turn)),
+        "r3k2r/p1p2ppp/8/1pPp4/8/8/PP1PPPPP/R3K1R1 w - b6 0 1"
+    );
+}
+
+#[test]
+fn perft_is_symmetric() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let passant = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    for &(fen, depth) in [(kiwipete, 2), (passant, 3)].iter() {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        let (board, turn) = (&position.board, position.turn);
+        let count = board.perft(turn, depth);
+        let (mirrored, turn) = board.mirror_vertical(turn);
+        assert_eq!(mirrored.perft(turn, depth), count);
+    }
+    // without castling rights, as those are lost when the files are mirrored
+    let no_castling = [
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w - f6 0 3",
+    ];
+    for fen in no_castling.iter() {
+        let position = ChessBoard::from_fen(fen).unwrap();
+        let (board, turn) = (&position.board, position.turn);
+        let count = board.perft(turn, 3);
+        for (transformed, turn) in [board.mirror_horizontal(turn), board.rotate(turn)].iter() {
+            assert_eq!(transformed.perft(*turn, 3), count);
+        }
+    }
+}
+
+#[test]
+fn finds_mating_material() {
+    let can_mate = |fen: &str| {
+        ChessBoard::from_fen(fen)
+            .unwrap()
+            .board
+            .has_mating_material(Color::White)
+    };
+    assert!(!can_mate("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+    assert!(can_mate("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+    // a lone knight needs something to block the king, which a queen can't be
+    assert!(!can_mate("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+    assert!(!can_mate("3qk3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+    assert!(can_mate("3rk3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+    assert!(can_mate("4k3/8/8/8/8/8/8/4KNN1 w - - 0 1"));
+    // bishops on one color, against a bishop on the same or the other color
+    assert!(!can_mate("4k3/8/8/8/8/4B3/8/2B1K3 w - - 0 1"));
+    assert!(!can_mate("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(can_mate("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(can_mate("4k3/4p3/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(!can_mate("r3k3/8/8/8/8/8/8/4KB2 w - - 0 1"));
+    assert!(can_mate("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1"));
+}
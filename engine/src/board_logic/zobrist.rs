@@ -0,0 +1,57 @@
+use super::*;
+
+/// Pseudo-random key number `index`, the same on every run (SplitMix64)
+fn key(index: u64) -> u64 {
+    let mut z = index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const SIDE_KEY: u64 = 1 << 20;
+const CASTLING_KEY: u64 = SIDE_KEY + 1;
+const PASSANT_KEY: u64 = CASTLING_KEY + 4;
+
+impl ChessBoard {
+    /// Zobrist hash of the position with `turn` to move. Pieces are told apart by their
+    /// letter, so fairy pieces hash too. The en passant file only counts when a pawn can
+    /// take there, like in Polyglot books.
+    pub fn zobrist_hash(&self, turn: Color) -> u64 {
+        let mut hash = 0;
+        for (y, row) in self.board.iter().enumerate() {
+            for (x, space) in row.iter().enumerate() {
+                if let Some(piece) = space {
+                    hash ^= key(piece.letter() as u64 * 64 + (y * 8 + x) as u64);
+                }
+            }
+        }
+        if turn == Color::Black {
+            hash ^= key(SIDE_KEY);
+        }
+        let rights = self.castling_rights();
+        for (i, &allowed) in [
+            rights.white_king_side,
+            rights.white_queen_side,
+            rights.black_king_side,
+            rights.black_queen_side,
+        ]
+        .iter()
+        .enumerate()
+        {
+            if allowed {
+                hash ^= key(CASTLING_KEY + i as u64);
+            }
+        }
+        if let Some((square, (pawn_x, pawn_y))) = self.passant_connection {
+            let can_take = [pawn_x.wrapping_sub(1), pawn_x + 1].iter().any(|&x| {
+                x < 8
+                    && matches!(self.ref_piece((x, pawn_y)),
+                        Some(piece) if piece.piece_type == PieceType::Pawn && piece.color == turn)
+            });
+            if can_take {
+                hash ^= key(PASSANT_KEY + square.0 as u64);
+            }
+        }
+        hash
+    }
+}
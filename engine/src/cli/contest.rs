@@ -0,0 +1,40 @@
+use super::*;
+use maltebl_chess::board_logic::STARTING_FEN;
+
+impl Session {
+    pub(super) fn run_match(&self) -> Result<(), String> {
+        let contest = &self.contest;
+        let mut settings = MatchSettings::new(
+            [contest.engines[0].clone(), contest.engines[1].clone()],
+            match &contest.openings {
+                Some(path) => read_openings(&read_file(path)?)?,
+                None => vec![STARTING_FEN.to_string()],
+            },
+        );
+        settings.rounds = contest.rounds;
+        settings.time_control = contest.time_control.clone();
+        settings.concurrency = contest.concurrency;
+        settings.sprt = contest.sprt;
+        let names = [&contest.engines[0].name, &contest.engines[1].name];
+        let report = run_match(&settings, |game, record| {
+            let (white, black) = if game.first_white {
+                (names[0], names[1])
+            } else {
+                (names[1], names[0])
+            };
+            println!(
+                "Game {}: {} - {} {} ({}), {}",
+                game.number + 1,
+                white,
+                black,
+                game.result,
+                game.termination,
+                record
+            );
+        })?;
+        fs::write(&contest.out, report.to_pgn())
+            .map_err(|error| format!("Can't write {}: {}", contest.out, error))?;
+        println!("{}", report);
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+use maltebl_chess::explorer::explore;
+
+impl Session {
+    pub(super) fn import(&mut self, path: &str) -> Result<(), String> {
+        let database = self
+            .database
+            .as_mut()
+            .ok_or("Give the database with --db")?;
+        let report = database.import_pgn(&read_file(path)?)?;
+        for error in &report.errors {
+            println!("Skipped {}", error);
+        }
+        println!(
+            "Imported {} games, the database has {}",
+            report.imported,
+            database.games().len()
+        );
+        Ok(())
+    }
+
+    /// Shows the opening tree after `position`, given as FEN or as moves from the start
+    pub(super) fn explore_from(&mut self, position: &str) -> Result<(), String> {
+        self.game = match ChessGame::from_fen(position) {
+            Ok(game) => game,
+            Err(_) => {
+                let mut game = init_standard_chess();
+                for mov in position.split_whitespace() {
+                    game.play(mov)?;
+                }
+                game
+            }
+        };
+        self.explore()
+    }
+
+    pub(super) fn explore(&self) -> Result<(), String> {
+        let database = self
+            .database
+            .as_ref()
+            .ok_or("Start with --db to explore games")?;
+        let tree = explore(
+            database,
+            self.game.chess_board(),
+            self.game.current_player(),
+        );
+        if tree.total.games == 0 {
+            println!("No games reached this position");
+        } else {
+            print!("{}", tree);
+        }
+        Ok(())
+    }
+}
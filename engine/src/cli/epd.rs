@@ -0,0 +1,22 @@
+use super::*;
+use maltebl_chess::epd::*;
+
+impl Session {
+    pub(super) fn run_epd(&self, path: &str) -> Result<(), String> {
+        let records = read_epd(&read_file(path)?)?;
+        let report = run_suite(&records, &self.limits, |result| {
+            println!(
+                "{} {}: {} {}{}",
+                result.record.id.as_deref().unwrap_or("-"),
+                if result.solved { "solved" } else { "failed" },
+                result.found.as_deref().unwrap_or("-"),
+                score_text(result.score),
+                result
+                    .score_error()
+                    .map_or(String::new(), |error| format!(", ce {:+}", error))
+            )
+        })?;
+        println!("Solved {} of {}", report.solved(), report.results.len());
+        Ok(())
+    }
+}
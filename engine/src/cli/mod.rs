@@ -0,0 +1,241 @@
+use maltebl_chess::{
+    chess_game::*,
+    clock::TimeControl,
+    console_display::{AnsiColors, RenderOptions},
+    database::GameDatabase,
+    engine_match::*,
+    piece_logic::Color,
+    search::*,
+};
+use std::{fs, process, time::Duration};
+
+mod contest;
+mod database;
+mod epd;
+mod play;
+mod puzzles;
+mod uci;
+
+pub const USAGE: &str = "\
+Usage: maltebl-chess [options]
+  --ai <white|black>   let the computer play a side
+  --level <1-6>        strength of the computer, the depth it searches (default 3)
+  --time <seconds>     most time the computer takes for a move (default 10)
+  --threads <n>        threads the computer searches with (default 1)
+  --fen <fen>          start from a position
+  --pgn <file>         continue a saved game
+  --puzzles <file>     solve puzzles from a Lichess puzzle CSV
+  --progress <file>    where puzzle results are kept (default puzzle-progress.txt)
+  --epd <file>         run the computer on a test suite, with --level and --time per position
+  --db <directory>     game database for import and explore
+  --import <file>      add the games of a PGN file to the database
+  --explore <moves|fen> moves played from a position in the database, e.g. \"e4 e5 Nf3\"
+  --engine <spec>      side of an engine match, given twice: name=new,depth=4 or uci=<command>
+  --openings <file>    FEN or EPD starting positions of the match (default the start position)
+  --rounds <n>         times each opening is played with both colours (default 1)
+  --tc <control>       time control of the match games, e.g. 60+1
+  --concurrency <n>    match games played at the same time (default 1)
+  --out <file>         where the match games are written (default match.pgn)
+  --sprt <elo0,elo1>   stop the match once a test between the Elo differences decides
+  --uci                talk UCI on standard input and output
+  --black              show the board from Black's side
+  --ascii              letters instead of chess symbols
+  --no-color           no terminal colours";
+
+pub struct Session {
+    game: ChessGame,
+    options: RenderOptions,
+    ai: Option<Color>,
+    limits: SearchLimits,
+    /// Keeps the transposition table between the moves of a game
+    engine: Engine,
+    progress: String,
+    database: Option<GameDatabase>,
+    contest: MatchOptions,
+    task: Task,
+}
+
+/// Settings of an engine match from the command line
+struct MatchOptions {
+    engines: Vec<EngineSpec>,
+    openings: Option<String>,
+    rounds: usize,
+    time_control: Option<TimeControl>,
+    concurrency: usize,
+    out: String,
+    sprt: Option<Sprt>,
+}
+
+/// What the program was started to do
+#[derive(Clone)]
+enum Task {
+    Play,
+    Puzzles(String),
+    Epd(String),
+    Import(String),
+    Explore(String),
+    Match,
+    Uci,
+}
+
+impl Session {
+    pub fn from_args(args: Vec<String>) -> Result<Session, String> {
+        let mut session = Session {
+            game: init_standard_chess(),
+            options: RenderOptions {
+                ansi: Some(AnsiColors::default()),
+                ..Default::default()
+            },
+            ai: None,
+            limits: SearchLimits {
+                depth: 3,
+                time: Some(Duration::from_secs(10)),
+                threads: 1,
+                stop: None,
+            },
+            engine: Engine::new(),
+            progress: "puzzle-progress.txt".to_string(),
+            database: None,
+            contest: MatchOptions {
+                engines: Vec::new(),
+                openings: None,
+                rounds: 1,
+                time_control: None,
+                concurrency: 1,
+                out: "match.pgn".to_string(),
+                sprt: None,
+            },
+            task: Task::Play,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--ai" => session.ai = parse_side(&value()?)?,
+                "--level" => session.limits.depth = parse_level(&value()?)?,
+                "--time" => {
+                    let seconds = value()?;
+                    let seconds: f64 = seconds
+                        .parse()
+                        .map_err(|_| format!("Invalid time {:?}", seconds))?;
+                    session.limits.time = Some(Duration::from_secs_f64(seconds.max(0.1)));
+                }
+                "--threads" => session.limits.threads = parse_count(&value()?)?,
+                "--fen" => session.game = ChessGame::from_fen(&value()?)?,
+                "--pgn" => session.game = ChessGame::from_pgn(&read_file(&value()?)?)?,
+                "--puzzles" => session.task = Task::Puzzles(value()?),
+                "--progress" => session.progress = value()?,
+                "--epd" => session.task = Task::Epd(value()?),
+                "--db" => session.database = Some(GameDatabase::open(value()?)?),
+                "--import" => session.task = Task::Import(value()?),
+                "--explore" => session.task = Task::Explore(value()?),
+                "--engine" => {
+                    session.contest.engines.push(EngineSpec::parse(&value()?)?);
+                    session.task = Task::Match;
+                }
+                "--openings" => session.contest.openings = Some(value()?),
+                "--rounds" => session.contest.rounds = parse_count(&value()?)?,
+                "--tc" => session.contest.time_control = Some(TimeControl::parse(&value()?)?),
+                "--concurrency" => session.contest.concurrency = parse_count(&value()?)?,
+                "--out" => session.contest.out = value()?,
+                "--sprt" => {
+                    let bounds = value()?;
+                    let elo: Vec<f64> = bounds
+                        .split(',')
+                        .map(|elo| elo.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("Invalid SPRT bounds {:?}", bounds))?;
+                    match elo[..] {
+                        [elo0, elo1] => session.contest.sprt = Some(Sprt::new(elo0, elo1)),
+                        _ => return Err(format!("Expected elo0,elo1, not {:?}", bounds)),
+                    }
+                }
+                "--uci" => session.task = Task::Uci,
+                "--black" => session.options.perspective = Color::Black,
+                "--ascii" => session.options.unicode = false,
+                "--no-color" => session.options.ansi = None,
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                other => return Err(format!("Unknown option {}", other)),
+            }
+        }
+        if matches!(session.task, Task::Import(_) | Task::Explore(_)) && session.database.is_none()
+        {
+            return Err("Give the database with --db".to_string());
+        }
+        if matches!(session.task, Task::Match) && session.contest.engines.len() != 2 {
+            return Err("A match needs --engine twice".to_string());
+        }
+        Ok(session)
+    }
+
+    /// Does what the session was started for
+    pub fn start(&mut self) -> Result<(), String> {
+        match self.task.clone() {
+            Task::Play => {
+                println!("Type help for the commands.");
+                self.run();
+                Ok(())
+            }
+            Task::Puzzles(path) => self.run_puzzles(&path),
+            Task::Epd(path) => self.run_epd(&path),
+            Task::Import(path) => self.import(&path),
+            Task::Explore(position) => self.explore_from(&position),
+            Task::Match => self.run_match(),
+            Task::Uci => self.run_uci(),
+        }
+    }
+}
+
+fn score_text(score: i32) -> String {
+    if score.abs() > MATE - 1000 {
+        let moves = (MATE - score.abs() + 1) / 2;
+        format!("{} in {}", if score > 0 { "mate" } else { "mated" }, moves)
+    } else {
+        format!("{:+.2}", score as f64 / 100.0)
+    }
+}
+
+fn side_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+fn parse_side(side: &str) -> Result<Option<Color>, String> {
+    match side {
+        "white" => Ok(Some(Color::White)),
+        "black" => Ok(Some(Color::Black)),
+        "off" | "none" => Ok(None),
+        other => Err(format!("Expected white, black or off, not {:?}", other)),
+    }
+}
+
+fn parse_level(level: &str) -> Result<usize, String> {
+    match level.parse() {
+        Ok(level) if (1..=6).contains(&level) => Ok(level),
+        _ => Err(format!("The level goes from 1 to 6, not {:?}", level)),
+    }
+}
+
+fn parse_count(count: &str) -> Result<usize, String> {
+    count
+        .parse::<usize>()
+        .ok()
+        .filter(|&count| count > 0)
+        .ok_or_else(|| format!("Expected a positive number, not {:?}", count))
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))
+}
@@ -0,0 +1,243 @@
+use super::*;
+use maltebl_chess::to_coords;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+Moves can be given in coordinates (e2e4, e2 e4, e7e8q) or in SAN (e4, Nf3, O-O, e8=Q).
+Commands:
+  moves <square>       legal moves of the piece on a square
+  hint                 ask the computer for a move
+  undo                 take back your last move
+  flip                 turn the board around
+  draw                 offer a draw
+  resign               give up the game
+  ai <white|black|off> change who the computer plays
+  level <1-6>          change the strength of the computer
+  fen | pgn            show the game as FEN or PGN
+  load fen <fen|file>  set up a position
+  load pgn <file>      load a game
+  save fen|pgn <file>  write the game to a file
+  explore              moves played from here in the games of --db
+  new                  start over
+  quit";
+
+impl Session {
+    pub(super) fn run(&mut self) {
+        self.show();
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        loop {
+            if self.ai == Some(self.game.current_player())
+                && self.game.status() == GameStatus::Ongoing
+            {
+                self.ai_move();
+                self.show();
+                continue;
+            }
+            print!("{}> ", side_name(self.game.current_player()));
+            io::stdout().flush().ok();
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break,
+            };
+            match self.command(line.trim(), &mut lines) {
+                Ok(Action::Quit) => break,
+                Ok(Action::Show) => self.show(),
+                Ok(Action::Nothing) => (),
+                Err(error) => println!("{}", error),
+            }
+        }
+    }
+
+    fn show(&self) {
+        let options = RenderOptions {
+            highlights: self.game.highlights(),
+            ..self.options.clone()
+        };
+        print!("\n{}", self.game.render(&options));
+        match self.game.status() {
+            GameStatus::Ongoing => {
+                if let Some(played) = self.game.history().last() {
+                    println!("Last move: {}", played.san);
+                }
+                if let Some(opening) = self.game.opening() {
+                    println!("Opening: {} {}", opening.eco, opening.name);
+                }
+            }
+            status => println!("{} {}", describe(status), status.result()),
+        }
+    }
+
+    fn command(
+        &mut self,
+        line: &str,
+        lines: &mut impl Iterator<Item = io::Result<String>>,
+    ) -> Result<Action, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let rest: Vec<&str> = words.collect();
+        match (command, &rest[..]) {
+            ("", _) => Ok(Action::Nothing),
+            ("help", _) => {
+                println!("{}", HELP);
+                Ok(Action::Nothing)
+            }
+            ("quit", _) | ("exit", _) => Ok(Action::Quit),
+            ("moves", [square]) => {
+                to_coords(square.to_string())?;
+                let moves = self.game.pick_piece(square.to_string())?;
+                if moves.is_empty() {
+                    println!("The piece on {} can't move", square);
+                } else {
+                    println!("{}", moves.join(" "));
+                }
+                Ok(Action::Nothing)
+            }
+            ("hint", []) => {
+                let result = self.think().ok_or("There are no moves to make")?;
+                let mov = result.best_move;
+                let mut san = self.game.chess_board().san(mov.from, mov.to)?;
+                if let Some(piece_type) = mov.promotion {
+                    san = format!("{}={}", san, piece_type);
+                }
+                println!("Try {} ({})", san, score_text(result.score));
+                Ok(Action::Nothing)
+            }
+            ("undo", []) => {
+                self.game.undo()?;
+                if self.ai == Some(self.game.current_player()) {
+                    self.game.undo()?;
+                }
+                Ok(Action::Show)
+            }
+            ("flip", []) => {
+                self.options.perspective = match self.options.perspective {
+                    Color::White => Color::Black,
+                    Color::Black => Color::White,
+                };
+                Ok(Action::Show)
+            }
+            ("draw", []) => {
+                let player = self.game.current_player();
+                self.game.offer_draw(player)?;
+                let opponent = opponent(player);
+                let accepted = if self.ai == Some(opponent) {
+                    // the computer takes a draw unless it thinks it is better
+                    let result = self.think();
+                    result.is_none_or(|result| result.score >= 0)
+                } else {
+                    print!("{}, do you accept a draw? [y/n] ", side_name(opponent));
+                    io::stdout().flush().ok();
+                    matches!(lines.next(), Some(Ok(answer)) if answer.trim().starts_with('y'))
+                };
+                if accepted {
+                    self.game.accept_draw(opponent)?;
+                    Ok(Action::Show)
+                } else {
+                    println!("The draw offer was declined");
+                    Ok(Action::Nothing)
+                }
+            }
+            ("resign", []) => {
+                self.game.resign(self.game.current_player())?;
+                Ok(Action::Show)
+            }
+            ("ai", [side]) => {
+                self.ai = parse_side(side)?;
+                Ok(Action::Nothing)
+            }
+            ("level", [level]) => {
+                self.limits.depth = parse_level(level)?;
+                Ok(Action::Nothing)
+            }
+            ("fen", []) => {
+                println!("{}", self.game.to_fen());
+                Ok(Action::Nothing)
+            }
+            ("pgn", []) => {
+                println!("{}", self.game.to_pgn());
+                Ok(Action::Nothing)
+            }
+            ("load", ["fen", ..]) => {
+                let fen = rest[1..].join(" ");
+                self.game = match fs::metadata(&fen) {
+                    Ok(_) => ChessGame::from_fen(read_file(&fen)?.trim())?,
+                    Err(_) => ChessGame::from_fen(&fen)?,
+                };
+                Ok(Action::Show)
+            }
+            ("load", ["pgn", file]) => {
+                self.game = ChessGame::from_pgn(&read_file(file)?)?;
+                Ok(Action::Show)
+            }
+            ("save", [format, file]) => {
+                let text = match *format {
+                    "fen" => self.game.to_fen() + "\n",
+                    "pgn" => self.game.to_pgn(),
+                    _ => return Err("Save as fen or pgn".to_string()),
+                };
+                fs::write(file, text)
+                    .map_err(|error| format!("Can't write {}: {}", file, error))?;
+                println!("Saved to {}", file);
+                Ok(Action::Nothing)
+            }
+            ("explore", []) => {
+                self.explore()?;
+                Ok(Action::Nothing)
+            }
+            ("new", []) => {
+                self.game = init_standard_chess();
+                self.engine.clear();
+                Ok(Action::Show)
+            }
+            _ => {
+                self.game.play(line)?;
+                Ok(Action::Show)
+            }
+        }
+    }
+
+    fn think(&self) -> Option<SearchResult> {
+        self.engine.search(
+            self.game.chess_board(),
+            self.game.current_player(),
+            &self.limits,
+        )
+    }
+
+    fn ai_move(&mut self) {
+        println!("{} is thinking...", side_name(self.game.current_player()));
+        match self.think() {
+            Some(result) => {
+                if let Err(error) = self.game.play(&result.best_move.to_coordinates()) {
+                    println!("The computer tried an illegal move: {}", error);
+                    self.ai = None;
+                }
+            }
+            None => self.ai = None,
+        }
+    }
+}
+
+enum Action {
+    Nothing,
+    Show,
+    Quit,
+}
+
+fn describe(status: GameStatus) -> String {
+    match status {
+        GameStatus::Ongoing => "The game goes on.".to_string(),
+        GameStatus::Checkmate(winner) => format!("Checkmate, {} wins.", side_name(winner)),
+        GameStatus::KingCaptured(winner) => {
+            format!("The king was captured, {} wins.", side_name(winner))
+        }
+        GameStatus::OutOfTime(winner) => format!("Out of time, {} wins.", side_name(winner)),
+        GameStatus::Resigned(winner) => format!("Resigned, {} wins.", side_name(winner)),
+        GameStatus::Draw(DrawReason::Stalemate) => "Draw by stalemate.".to_string(),
+        GameStatus::Draw(DrawReason::Agreement) => "Draw by agreement.".to_string(),
+        GameStatus::Draw(DrawReason::InsufficientMaterial) => {
+            "Draw, there is no mating material left.".to_string()
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+use maltebl_chess::puzzle::*;
+use std::io::{self, BufRead, Write};
+
+impl Session {
+    /// Goes through the unsolved puzzles until the input ends or `quit`
+    pub(super) fn run_puzzles(&mut self, path: &str) -> Result<(), String> {
+        let puzzles = read_puzzles(&read_file(path)?)?;
+        let mut progress = PuzzleProgress::load(&self.progress)?;
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        let unsolved: Vec<&Puzzle> = puzzles
+            .iter()
+            .filter(|puzzle| !progress.is_solved(&puzzle.id))
+            .collect();
+        println!(
+            "{} of {} puzzles left. Type hint for the next move or quit to stop.",
+            unsolved.len(),
+            puzzles.len()
+        );
+        for puzzle in unsolved {
+            let mut session = puzzle.start()?;
+            self.options.perspective = session.player();
+            println!("\nPuzzle {} (rating {})", puzzle.id, puzzle.rating);
+            self.show_puzzle(&session);
+            while !session.is_finished() {
+                print!("{} to play> ", side_name(session.player()));
+                io::stdout().flush().ok();
+                let line = match lines.next() {
+                    Some(Ok(line)) => line,
+                    _ => return Ok(()),
+                };
+                match line.trim() {
+                    "" => continue,
+                    "quit" | "exit" => return Ok(()),
+                    "hint" => println!("Try {}", session.hint().unwrap_or("-")),
+                    input => match session.try_move(input) {
+                        Ok(Attempt::Wrong) => println!("That's not it, try again"),
+                        Ok(Attempt::Correct(reply)) => {
+                            println!(
+                                "Correct! {} answers {}",
+                                side_name(opponent(session.player())),
+                                reply
+                            );
+                            self.show_puzzle(&session);
+                        }
+                        Ok(Attempt::Solved) => self.show_puzzle(&session),
+                        Err(error) => println!("{}", error),
+                    },
+                }
+            }
+            progress.record(&puzzle.id, session.is_solved());
+            progress.save(&self.progress)?;
+            println!(
+                "{} {} of {} tries solved ({:.0}%)",
+                if session.is_solved() {
+                    "Solved!"
+                } else {
+                    "Done."
+                },
+                progress.solved(),
+                progress.attempts(),
+                progress.success_rate() * 100.0
+            );
+        }
+        Ok(())
+    }
+
+    fn show_puzzle(&self, session: &PuzzleSession) {
+        let options = RenderOptions {
+            highlights: session.game().highlights(),
+            ..self.options.clone()
+        };
+        print!("\n{}", session.game().render(&options));
+        if let Some(played) = session.game().history().last() {
+            println!("Last move: {}", played.san);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::*;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// A search started by `go`, running on its own thread so `stop` can be read meanwhile
+struct Thinking {
+    stop: Arc<AtomicBool>,
+    /// `go infinite` waits for `stop` before giving the best move
+    infinite: bool,
+    worker: JoinHandle<(Engine, Option<SearchResult>)>,
+}
+
+impl Session {
+    /// Answers a UCI graphical interface or match runner until `quit`
+    pub(super) fn run_uci(&mut self) -> Result<(), String> {
+        let stdin = io::stdin();
+        let mut thinking = None;
+        for line in stdin.lock().lines() {
+            let line = line.map_err(|error| error.to_string())?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.first().copied() {
+                Some("uci") => {
+                    println!("id name maltebl-chess");
+                    println!("option name Threads type spin default 1 min 1 max 64");
+                    println!("uciok");
+                }
+                Some("isready") => println!("readyok"),
+                Some("ucinewgame") => {
+                    self.stop_thinking(thinking.take());
+                    self.game = init_standard_chess();
+                    self.engine.clear();
+                }
+                Some("setoption") => {
+                    if let ["setoption", "name", "Threads", "value", threads] = words[..] {
+                        match parse_count(threads) {
+                            Ok(threads) => self.limits.threads = threads,
+                            Err(error) => println!("info string {}", error),
+                        }
+                    }
+                }
+                Some("position") => {
+                    if let Err(error) = self.set_position(&words[1..]) {
+                        println!("info string {}", error);
+                    }
+                }
+                Some("go") => {
+                    self.stop_thinking(thinking.take());
+                    thinking = Some(self.think_on(&words[1..]));
+                }
+                Some("stop") => self.stop_thinking(thinking.take()),
+                Some("quit") => break,
+                _ => (),
+            }
+            io::stdout().flush().ok();
+        }
+        self.stop_thinking(thinking);
+        Ok(())
+    }
+
+    /// Searches the current position on another thread, which takes the engine along and
+    /// gives the best move when it is done
+    fn think_on(&mut self, words: &[&str]) -> Thinking {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut limits = self.go_limits(words);
+        limits.stop = Some(stop.clone());
+        let infinite = words.contains(&"infinite");
+        let engine = mem::take(&mut self.engine);
+        let board = self.game.chess_board().clone();
+        let color = self.game.current_player();
+        let worker = thread::spawn(move || {
+            let result = engine.search(&board, color, &limits);
+            if !infinite {
+                report(&result);
+            }
+            (engine, result)
+        });
+        Thinking {
+            stop,
+            infinite,
+            worker,
+        }
+    }
+
+    /// Ends the search, if there is one, and takes the engine back
+    fn stop_thinking(&mut self, thinking: Option<Thinking>) {
+        let thinking = match thinking {
+            Some(thinking) => thinking,
+            None => return,
+        };
+        thinking.stop.store(true, Ordering::Relaxed);
+        match thinking.worker.join() {
+            Ok((engine, result)) => {
+                self.engine = engine;
+                if thinking.infinite {
+                    report(&result);
+                }
+            }
+            Err(_) => println!("info string The search failed"),
+        }
+    }
+
+    /// `startpos` or `fen <fen>`, followed by `moves` and the moves played since
+    fn set_position(&mut self, words: &[&str]) -> Result<(), String> {
+        let moves = words.iter().position(|&word| word == "moves");
+        let (setup, moves) = words.split_at(moves.unwrap_or(words.len()));
+        self.game = match setup {
+            ["startpos"] => init_standard_chess(),
+            ["fen", fen @ ..] => ChessGame::from_fen(&fen.join(" "))?,
+            _ => return Err("Expected startpos or fen".to_string()),
+        };
+        for mov in moves.iter().skip(1) {
+            self.game.play(mov)?;
+        }
+        Ok(())
+    }
+
+    /// Search limits of a `go` command, without any the settings of the session are used.
+    /// `infinite` searches until `stop`.
+    fn go_limits(&self, words: &[&str]) -> SearchLimits {
+        let mut limits = self.limits.clone();
+        let mut clock = [None, None];
+        let mut increment = [Duration::default(); 2];
+        let mut moves_to_go = None;
+        for pair in words.windows(2) {
+            let number = pair[1].parse::<u64>().ok();
+            let millis = number.map(Duration::from_millis);
+            match (pair[0], number) {
+                ("depth", Some(depth)) => {
+                    limits.depth = depth as usize;
+                    limits.time = None;
+                }
+                ("movetime", Some(_)) => limits.time = millis,
+                ("wtime", Some(_)) => clock[0] = millis,
+                ("btime", Some(_)) => clock[1] = millis,
+                ("winc", Some(_)) => increment[0] = millis.unwrap(),
+                ("binc", Some(_)) => increment[1] = millis.unwrap(),
+                ("movestogo", Some(moves)) => moves_to_go = Some(moves as u32),
+                _ => (),
+            }
+        }
+        let side = if self.game.current_player() == Color::White {
+            0
+        } else {
+            1
+        };
+        if let Some(remaining) = clock[side] {
+            limits.depth = 64;
+            limits.time = Some(time_for_move(remaining, increment[side], moves_to_go));
+        }
+        if words.contains(&"infinite") {
+            limits.depth = 64;
+            limits.time = None;
+        }
+        limits
+    }
+}
+
+/// The last line of the search and the move it chose
+fn report(result: &Option<SearchResult>) {
+    match result {
+        Some(result) => {
+            let pv: Vec<String> = result.pv.iter().map(|mov| mov.to_coordinates()).collect();
+            println!(
+                "info depth {} score cp {} nodes {} pv {}",
+                result.depth,
+                result.score,
+                result.nodes,
+                pv.join(" ")
+            );
+            println!("bestmove {}", result.best_move.to_coordinates());
+        }
+        None => println!("bestmove 0000"),
+    }
+    io::stdout().flush().ok();
+}
@@ -0,0 +1,324 @@
+use super::piece_logic::Color;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+/// Source of the current time, so clocks can be driven by hand in tests
+pub trait TimeSource: Send {
+    /// Time passed since some fixed point, must never go backwards
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock time source
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> Self {
+        SystemTimeSource {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time source that only moves when told to, clones share the same time
+#[derive(Clone, Default)]
+pub struct ManualTimeSource {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn advance(&self, time: Duration) {
+        *self.now.lock().unwrap() += time;
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Time given back to a player for each move
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bonus {
+    None,
+    /// Added after every move
+    Fischer(Duration),
+    /// Time used is given back after the move, up to the given amount
+    Bronstein(Duration),
+    /// The clock waits this long before it starts counting down (simple or US delay)
+    Delay(Duration),
+}
+
+/// Part of a time control, `moves` is how many moves have to be made before the next stage
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stage {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+/// Stages of a time control, a last stage with a move count is repeated
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::with_bonus(time, Bonus::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::with_bonus(time, Bonus::Fischer(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        Self::with_bonus(time, Bonus::Bronstein(delay))
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        Self::with_bonus(time, Bonus::Delay(delay))
+    }
+
+    fn with_bonus(time: Duration, bonus: Bonus) -> Self {
+        TimeControl {
+            stages: vec![Stage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+
+    /// Parses the PGN `TimeControl` format, e.g. `300+2` or `40/5400+30:1800+30`.
+    /// Besides `+` for Fischer increments, `d` marks a simple delay and `b` a Bronstein delay.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let error = || format!("Invalid time control {:?}", input);
+        let seconds = |text: &str| {
+            text.parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| error())
+        };
+        let mut stages: Vec<Stage> = Vec::new();
+        for stage in input.split(':') {
+            let (moves, rest) = match stage.find('/') {
+                Some(i) => match stage[..i].parse::<u32>() {
+                    Ok(moves) if moves > 0 => (Some(moves), &stage[i + 1..]),
+                    _ => return Err(error()),
+                },
+                None => (None, stage),
+            };
+            let (time, bonus) = match rest.find(['+', 'd', 'b']) {
+                Some(i) => {
+                    let amount = seconds(&rest[i + 1..])?;
+                    let bonus = match &rest[i..=i] {
+                        "+" => Bonus::Fischer(amount),
+                        "d" => Bonus::Delay(amount),
+                        _ => Bonus::Bronstein(amount),
+                    };
+                    (seconds(&rest[..i])?, bonus)
+                }
+                None => (seconds(rest)?, Bonus::None),
+            };
+            stages.push(Stage { moves, time, bonus });
+        }
+        Ok(TimeControl { stages })
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = stage.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write!(f, "{}", stage.time.as_secs())?;
+            match stage.bonus {
+                Bonus::None => (),
+                Bonus::Fischer(time) => write!(f, "+{}", time.as_secs())?,
+                Bonus::Delay(time) => write!(f, "d{}", time.as_secs())?,
+                Bonus::Bronstein(time) => write!(f, "b{}", time.as_secs())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Chess clock for both players, only one side runs at a time
+pub struct Clock {
+    control: TimeControl,
+    source: Box<dyn TimeSource>,
+    remaining: [Duration; 2],
+    moves: [u32; 2],
+    stage: [usize; 2],
+    running: Option<(Color, Duration)>,
+}
+
+fn index(color: Color) -> usize {
+    if color == Color::White {
+        0
+    } else {
+        1
+    }
+}
+
+impl Clock {
+    /// A control without stages gives no time at all, and a stage of zero moves lasts the
+    /// rest of the game
+    pub fn new(mut control: TimeControl, source: Box<dyn TimeSource>) -> Self {
+        if control.stages.is_empty() {
+            control = TimeControl::sudden_death(Duration::default());
+        }
+        for stage in control.stages.iter_mut() {
+            if stage.moves == Some(0) {
+                stage.moves = None;
+            }
+        }
+        let time = control.stages[0].time;
+        Clock {
+            control,
+            source,
+            remaining: [time, time],
+            moves: [0, 0],
+            stage: [0, 0],
+            running: None,
+        }
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// Starts the clock of `color`, stopping the other one without counting it as a move
+    pub fn start(&mut self, color: Color) {
+        self.stop();
+        self.running = Some((color, self.source.now()));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((color, _)) = self.running {
+            self.remaining[index(color)] = self.remaining(color);
+            self.running = None;
+        }
+    }
+
+    /// Gives `color` extra time, e.g. as odds against a stronger player
+    pub fn add_time(&mut self, color: Color, time: Duration) {
+        self.remaining[index(color)] += time;
+    }
+
+    /// Side whose clock is running
+    pub fn active(&self) -> Option<Color> {
+        self.running.map(|(color, _)| color)
+    }
+
+    /// Ends the turn of the running side: adds its bonus, moves it along the time
+    /// control and starts the opponent's clock
+    pub fn press(&mut self) {
+        let (color, started) = match self.running {
+            Some(running) => running,
+            None => return,
+        };
+        let used = self.source.now() - started;
+        let i = index(color);
+        let bonus = self.current_stage(color).bonus;
+        self.remaining[i] = self.remaining(color);
+        if self.remaining[i] > Duration::default() {
+            match bonus {
+                Bonus::Fischer(time) => self.remaining[i] += time,
+                Bonus::Bronstein(time) => self.remaining[i] += used.min(time),
+                Bonus::None | Bonus::Delay(_) => (),
+            }
+        }
+        self.moves[i] += 1;
+        if let Some(moves) = self.current_stage(color).moves {
+            if self.moves[i] == moves {
+                self.moves[i] = 0;
+                if self.stage[i] + 1 < self.control.stages.len() {
+                    self.stage[i] += 1;
+                }
+                self.remaining[i] += self.current_stage(color).time;
+            }
+        }
+        self.running = Some((
+            match color {
+                Color::White => Color::Black,
+                Color::Black => Color::White,
+            },
+            self.source.now(),
+        ));
+    }
+
+    fn current_stage(&self, color: Color) -> &Stage {
+        &self.control.stages[self.stage[index(color)]]
+    }
+
+    /// Time left for `color`, counting the turn in progress
+    pub fn remaining(&self, color: Color) -> Duration {
+        let remaining = self.remaining[index(color)];
+        match self.running {
+            Some((running, started)) if running == color => {
+                let mut used = self.source.now() - started;
+                if let Bonus::Delay(delay) = self.current_stage(color).bonus {
+                    used = used.checked_sub(delay).unwrap_or_default();
+                }
+                remaining.checked_sub(used).unwrap_or_default()
+            }
+            _ => remaining,
+        }
+    }
+
+    /// Moves left until the next stage of the time control
+    pub fn moves_to_go(&self, color: Color) -> Option<u32> {
+        self.current_stage(color)
+            .moves
+            .map(|moves| moves - self.moves[index(color)])
+    }
+
+    /// Side that has run out of time
+    pub fn flagged(&self) -> Option<Color> {
+        [Color::White, Color::Black]
+            .iter()
+            .copied()
+            .find(|&color| self.remaining(color) == Duration::default())
+    }
+}
+
+/// Formats a duration as `h:mm:ss` like PGN `%clk` comments
+pub fn format_clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
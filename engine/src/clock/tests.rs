@@ -0,0 +1,102 @@
+use super::*;
+
+fn secs(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+fn started_clock(control: &str) -> (Clock, ManualTimeSource) {
+    let source = ManualTimeSource::new();
+    let mut clock = Clock::new(
+        TimeControl::parse(control).unwrap(),
+        Box::new(source.clone()),
+    );
+    clock.start(Color::White);
+    (clock, source)
+}
+
+#[test]
+fn parses_time_control() {
+    let control = TimeControl::parse("40/5400+30:1800+30").unwrap();
+    assert_eq!(control.stages.len(), 2);
+    assert_eq!(control.stages[0].moves, Some(40));
+    assert_eq!(control.stages[0].time, secs(5400));
+    assert_eq!(control.stages[1].bonus, Bonus::Fischer(secs(30)));
+    assert_eq!(format!("{}", control), "40/5400+30:1800+30");
+    assert_eq!(
+        TimeControl::parse("300d5").unwrap(),
+        TimeControl::simple_delay(secs(300), secs(5))
+    );
+    assert!(TimeControl::parse("5 minutes").is_err());
+    assert!(TimeControl::parse("0/300").is_err());
+}
+
+#[test]
+fn survives_odd_controls() {
+    let source = ManualTimeSource::new();
+    let mut clock = Clock::new(TimeControl { stages: Vec::new() }, Box::new(source.clone()));
+    clock.start(Color::White);
+    clock.press();
+    assert_eq!(clock.flagged(), Some(Color::White));
+    let zero_moves = TimeControl {
+        stages: vec![Stage {
+            moves: Some(0),
+            time: secs(60),
+            bonus: Bonus::None,
+        }],
+    };
+    let mut clock = Clock::new(zero_moves, Box::new(source));
+    clock.start(Color::White);
+    clock.press();
+    assert_eq!(clock.moves_to_go(Color::White), None);
+    assert_eq!(clock.remaining(Color::White), secs(60));
+}
+
+#[test]
+fn sudden_death() {
+    let (mut clock, time) = started_clock("60");
+    time.advance(secs(10));
+    assert_eq!(clock.remaining(Color::White), secs(50));
+    clock.press();
+    assert_eq!(clock.active(), Some(Color::Black));
+    time.advance(secs(61));
+    assert_eq!(clock.remaining(Color::White), secs(50));
+    assert_eq!(clock.remaining(Color::Black), secs(0));
+    assert_eq!(clock.flagged(), Some(Color::Black));
+    assert_eq!(format_clock(clock.remaining(Color::White)), "0:00:50");
+}
+
+#[test]
+fn increments_and_delays() {
+    let (mut clock, time) = started_clock("60+5");
+    time.advance(secs(10));
+    clock.press();
+    assert_eq!(clock.remaining(Color::White), secs(55));
+
+    let (mut clock, time) = started_clock("60b5");
+    time.advance(secs(3));
+    clock.press();
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    time.advance(secs(10));
+    clock.press();
+    assert_eq!(clock.remaining(Color::Black), secs(55));
+
+    let (mut clock, time) = started_clock("60d5");
+    time.advance(secs(4));
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    time.advance(secs(4));
+    clock.press();
+    assert_eq!(clock.remaining(Color::White), secs(57));
+}
+
+#[test]
+fn multiple_stages() {
+    let (mut clock, time) = started_clock("2/60:30");
+    for _ in 0..3 {
+        time.advance(secs(10));
+        clock.press();
+    }
+    assert_eq!(clock.remaining(Color::White), secs(70));
+    assert_eq!(clock.moves_to_go(Color::White), None);
+    assert_eq!(clock.remaining(Color::Black), secs(50));
+    assert_eq!(clock.moves_to_go(Color::Black), Some(1));
+}
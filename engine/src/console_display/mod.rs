@@ -1,10 +1,114 @@
-use super::piece_logic::*;
+use super::{
+    board_logic::{Board, RedactedBoard},
+    piece_logic::*,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How `render_board` draws a board
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Side shown at the bottom
+    pub perspective: Color,
+    /// File letters below and rank numbers left of the board
+    pub coordinates: bool,
+    /// Chess glyphs instead of letters (`KQRBNP` for White, lower case for Black)
+    pub unicode: bool,
+    /// Background colours for the squares, without them spaces are plain text
+    pub ansi: Option<AnsiColors>,
+    /// Spaces to mark, e.g. the last move or a king in check
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// Codes from the 256 colour ANSI palette
+#[derive(Clone, Debug)]
+pub struct AnsiColors {
+    pub light: u8,
+    pub dark: u8,
+    pub highlight: u8,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            perspective: Color::White,
+            coordinates: true,
+            unicode: true,
+            ansi: None,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl Default for AnsiColors {
+    fn default() -> Self {
+        AnsiColors {
+            light: 180,
+            dark: 94,
+            highlight: 142,
+        }
+    }
+}
+
+/// Draws the board as text, one line per rank. Every space is three characters wide,
+/// without colours highlighted spaces are put in brackets and empty spaces shown as `.`
+pub fn render_board(board: &Board, options: &RenderOptions) -> String {
+    let files: Vec<usize> = if options.perspective == Color::White {
+        (0..8).collect()
+    } else {
+        (0..8).rev().collect()
+    };
+    let ranks: Vec<usize> = if options.perspective == Color::White {
+        (0..8).rev().collect()
+    } else {
+        (0..8).collect()
+    };
+    let mut text = String::new();
+    for &y in &ranks {
+        if options.coordinates {
+            text.push_str(&format!("{} ", y + 1));
+        }
+        for &x in &files {
+            let highlighted = options.highlights.contains(&(x, y));
+            let symbol = match &board[y][x] {
+                Some(piece) if options.unicode => format!("{}", piece),
+                Some(piece) => piece.letter().to_string(),
+                None if options.ansi.is_some() => " ".to_string(),
+                None => ".".to_string(),
+            };
+            match &options.ansi {
+                Some(colors) => {
+                    let background = if highlighted {
+                        colors.highlight
+                    } else if x % 2 == y % 2 {
+                        colors.dark
+                    } else {
+                        colors.light
+                    };
+                    text.push_str(&format!("\x1b[48;5;{}m {} \x1b[0m", background, symbol));
+                }
+                None if highlighted => text.push_str(&format!("[{}]", symbol)),
+                None => text.push_str(&format!(" {} ", symbol)),
+            }
+        }
+        text.push('\n');
+    }
+    if options.coordinates {
+        text.push_str("  ");
+        for &x in &files {
+            text.push_str(&format!(" {} ", (x as u8 + b'a') as char));
+        }
+        text.push('\n');
+    }
+    text
+}
 
 pub fn print_board(board: &[[Option<Piece>; 8]; 8]) {
-    for y in (0..board.len()).rev() {
-        for x in 0..board[0].len() {
+    for row in board.iter().rev() {
+        for space in row.iter() {
             print!("|");
-            if let Some(piece) = &board[y][x] {
+            if let Some(piece) = space {
                 print!("{}", piece);
             } else {
                 print!(" ")
@@ -15,3 +119,19 @@ pub fn print_board(board: &[[Option<Piece>; 8]; 8]) {
         println!("------------------------");
     }
 }
+
+pub fn print_redacted_board(board: &RedactedBoard) {
+    for row in board.iter().rev() {
+        for space in row.iter() {
+            print!("|");
+            match space {
+                Some(Some(piece)) => print!("{}", piece),
+                Some(None) => print!(" "),
+                None => print!("\u{2592}"),
+            }
+            print!("|");
+        }
+        println!();
+        println!("------------------------");
+    }
+}
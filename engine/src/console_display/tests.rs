@@ -0,0 +1,53 @@
+use super::*;
+use crate::board_logic::init_board;
+
+fn standard_board() -> Board {
+    let mut board = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    board.get_board()
+}
+
+#[test]
+fn renders_ascii() {
+    let options = RenderOptions {
+        unicode: false,
+        highlights: vec![(4, 1)],
+        ..Default::default()
+    };
+    let text = render_board(&standard_board(), &options);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "8  r  n  b  q  k  b  n  r ");
+    assert_eq!(lines[6], "2  P  P  P  P [P] P  P  P ");
+    assert_eq!(lines[7], "1  R  N  B  Q  K  B  N  R ");
+    assert_eq!(lines[8], "   a  b  c  d  e  f  g  h ");
+}
+
+#[test]
+fn renders_from_black() {
+    let options = RenderOptions {
+        perspective: Color::Black,
+        coordinates: false,
+        ..Default::default()
+    };
+    let text = render_board(&standard_board(), &options);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(
+        lines[0],
+        " \u{2656}  \u{2658}  \u{2657}  \u{2654}  \u{2655}  \u{2657}  \u{2658}  \u{2656} "
+    );
+    assert_eq!(lines[4], " .  .  .  .  .  .  .  . ");
+}
+
+#[test]
+fn renders_ansi() {
+    let options = RenderOptions {
+        ansi: Some(AnsiColors::default()),
+        highlights: vec![(0, 0)],
+        ..Default::default()
+    };
+    let text = render_board(&standard_board(), &options);
+    assert!(text.contains("1 \x1b[48;5;142m \u{2656} \x1b[0m\x1b[48;5;180m \u{2658} \x1b[0m"));
+    assert!(text.contains("\x1b[48;5;94m   \x1b[0m"));
+}
@@ -0,0 +1,387 @@
+use crate::board_logic::*;
+use crate::chess_game::*;
+use crate::pgn::split_games;
+use crate::piece_logic::*;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// One game per line: the metadata and the moves separated by tabs
+const GAMES_FILE: &str = "games.tsv";
+/// Fixed size records of hash (8 bytes), game (4 bytes) and ply (2 bytes), little endian
+const POSITIONS_FILE: &str = "positions.idx";
+/// Lines of `<signature> <game> <ply>` for every change of material in a game
+const MATERIAL_FILE: &str = "material.idx";
+const POSITION_RECORD: usize = 14;
+
+/// Metadata and main line of a stored game
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct GameRecord {
+    pub id: usize,
+    pub white: String,
+    pub black: String,
+    pub event: String,
+    pub date: String,
+    pub result: String,
+    pub white_elo: Option<u32>,
+    pub black_elo: Option<u32>,
+    /// First position when the game didn't start from the standard one
+    pub start_fen: Option<String>,
+    /// Main line in SAN
+    pub moves: Vec<String>,
+}
+
+impl GameRecord {
+    fn from_game(id: usize, game: &ChessGame) -> GameRecord {
+        let tag = |name: &str| game.tag(name).unwrap_or("?").to_string();
+        let elo = |name: &str| game.tag(name).and_then(|elo| elo.parse().ok());
+        let record = game.record();
+        GameRecord {
+            id,
+            white: tag("White"),
+            black: tag("Black"),
+            event: tag("Event"),
+            date: tag("Date"),
+            result: game
+                .tag("Result")
+                .unwrap_or_else(|| game.status().result())
+                .to_string(),
+            white_elo: elo("WhiteElo"),
+            black_elo: elo("BlackElo"),
+            start_fen: game.tag("FEN").map(String::from),
+            moves: record
+                .mainline()
+                .iter()
+                .filter_map(|&node| record.node(node)?.played.as_ref())
+                .map(|played| played.san.clone())
+                .collect(),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        let clean = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+        let elo = |elo: Option<u32>| elo.map(|elo| elo.to_string()).unwrap_or_default();
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            self.id,
+            clean(&self.white),
+            clean(&self.black),
+            clean(&self.event),
+            clean(&self.date),
+            clean(&self.result),
+            elo(self.white_elo),
+            elo(self.black_elo),
+            self.start_fen.as_deref().unwrap_or(""),
+            self.moves.join(" ")
+        )
+    }
+
+    fn from_line(line: &str) -> Result<GameRecord, String> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 10 {
+            return Err(format!("Invalid game line {:?}", line));
+        }
+        let elo = |text: &str| text.parse().ok();
+        Ok(GameRecord {
+            id: fields[0]
+                .parse()
+                .map_err(|_| format!("Invalid game id {:?}", fields[0]))?,
+            white: fields[1].to_string(),
+            black: fields[2].to_string(),
+            event: fields[3].to_string(),
+            date: fields[4].to_string(),
+            result: fields[5].to_string(),
+            white_elo: elo(fields[6]),
+            black_elo: elo(fields[7]),
+            start_fen: Some(fields[8].to_string()).filter(|fen| !fen.is_empty()),
+            moves: fields[9].split_whitespace().map(String::from).collect(),
+        })
+    }
+
+    /// Plays the game again, with the stored tags
+    pub fn to_game(&self) -> Result<ChessGame, String> {
+        let mut game = match &self.start_fen {
+            Some(fen) => ChessGame::from_fen(fen)?,
+            None => init_standard_chess(),
+        };
+        for san in &self.moves {
+            game.play(san)?;
+        }
+        for (name, value) in [
+            ("Event", &self.event),
+            ("Date", &self.date),
+            ("White", &self.white),
+            ("Black", &self.black),
+            ("Result", &self.result),
+        ]
+        .iter()
+        {
+            game.set_tag(name, value);
+        }
+        for (name, elo) in [("WhiteElo", self.white_elo), ("BlackElo", self.black_elo)].iter() {
+            if let Some(elo) = elo {
+                game.set_tag(name, &elo.to_string());
+            }
+        }
+        if let Some(fen) = &self.start_fen {
+            game.set_tag("FEN", fen);
+        }
+        Ok(game)
+    }
+}
+
+/// Place in a stored game where a searched position came up
+#[derive(Clone, Debug)]
+pub struct PositionMatch<'a> {
+    pub game: &'a GameRecord,
+    /// Half moves played before the position came up, 0 for the first position
+    pub ply: usize,
+}
+
+impl PositionMatch<'_> {
+    /// The move that led to the position in SAN
+    pub fn last_move(&self) -> Option<&str> {
+        self.game
+            .moves
+            .get(self.ply.checked_sub(1)?)
+            .map(String::as_str)
+    }
+
+    /// The move played in the position in SAN, `None` when the game ended there
+    pub fn next_move(&self) -> Option<&str> {
+        self.game.moves.get(self.ply).map(String::as_str)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Games that couldn't be read, these are skipped
+    pub errors: Vec<String>,
+}
+
+/// Places in games as (game id, ply)
+type Occurrences = Vec<(usize, usize)>;
+
+/// Games stored in a directory, indexed by the Zobrist hash of every position
+/// and by material signature
+pub struct GameDatabase {
+    directory: PathBuf,
+    games: Vec<GameRecord>,
+    positions: HashMap<u64, Occurrences>,
+    material: HashMap<String, Occurrences>,
+}
+
+impl GameDatabase {
+    /// Opens the database in `directory`, creating it if needed
+    pub fn open(directory: impl AsRef<Path>) -> Result<GameDatabase, String> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)
+            .map_err(|error| format!("Can't create {}: {}", directory.display(), error))?;
+        let read = |name: &str| -> Result<Vec<u8>, String> {
+            let path = directory.join(name);
+            match fs::read(&path) {
+                Ok(bytes) => Ok(bytes),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+                Err(error) => Err(format!("Can't read {}: {}", path.display(), error)),
+            }
+        };
+        let mut database = GameDatabase {
+            directory: directory.clone(),
+            games: Vec::new(),
+            positions: HashMap::new(),
+            material: HashMap::new(),
+        };
+        let games = String::from_utf8(read(GAMES_FILE)?).map_err(|error| error.to_string())?;
+        for line in games.lines().filter(|line| !line.is_empty()) {
+            database.games.push(GameRecord::from_line(line)?);
+        }
+        for record in read(POSITIONS_FILE)?.chunks_exact(POSITION_RECORD) {
+            let number = |range: std::ops::Range<usize>| {
+                record[range]
+                    .iter()
+                    .rev()
+                    .fold(0u64, |value, &byte| value << 8 | byte as u64)
+            };
+            database
+                .positions
+                .entry(number(0..8))
+                .or_default()
+                .push((number(8..12) as usize, number(12..14) as usize));
+        }
+        let material =
+            String::from_utf8(read(MATERIAL_FILE)?).map_err(|error| error.to_string())?;
+        for line in material.lines() {
+            match line.split(' ').collect::<Vec<&str>>()[..] {
+                [signature, game, ply] => database
+                    .material
+                    .entry(signature.to_string())
+                    .or_default()
+                    .push((
+                        game.parse()
+                            .map_err(|_| format!("Invalid material line {:?}", line))?,
+                        ply.parse()
+                            .map_err(|_| format!("Invalid material line {:?}", line))?,
+                    )),
+                _ => return Err(format!("Invalid material line {:?}", line)),
+            }
+        }
+        Ok(database)
+    }
+
+    pub fn games(&self) -> &[GameRecord] {
+        &self.games
+    }
+
+    pub fn game(&self, id: usize) -> Option<&GameRecord> {
+        self.games.get(id)
+    }
+
+    /// Adds every game of a PGN text, games that can't be read are skipped and reported
+    pub fn import_pgn(&mut self, text: &str) -> Result<ImportReport, String> {
+        let mut report = ImportReport::default();
+        for (i, pgn) in split_games(text).into_iter().enumerate() {
+            match ChessGame::from_pgn(pgn) {
+                Ok(game) => {
+                    self.add_game(&game)?;
+                    report.imported += 1;
+                }
+                Err(error) => report.errors.push(format!("Game {}: {}", i + 1, error)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Stores the main line of `game` and returns its id
+    pub fn add_game(&mut self, game: &ChessGame) -> Result<usize, String> {
+        let id = self.games.len();
+        let record = GameRecord::from_game(id, game);
+        let (mut board, mut turn) = match &record.start_fen {
+            Some(fen) => {
+                let position = ChessBoard::from_fen(fen)?;
+                (position.board, position.turn)
+            }
+            None => {
+                let position = ChessBoard::from_fen(STARTING_FEN)?;
+                (position.board, position.turn)
+            }
+        };
+        let tree = game.record();
+        let mut positions: Vec<u8> = Vec::new();
+        let mut material = String::new();
+        let mut last_signature = String::new();
+        for (ply, node) in std::iter::once(None)
+            .chain(tree.mainline().into_iter().map(Some))
+            .enumerate()
+        {
+            if let Some(played) = node.and_then(|node| tree.node(node)?.played.as_ref()) {
+                board.make_move(&Move {
+                    from: played.from,
+                    to: played.to,
+                    promotion: played.promotion.clone(),
+                })?;
+                turn = opponent(turn);
+            }
+            positions.extend_from_slice(&board.zobrist_hash(turn).to_le_bytes());
+            positions.extend_from_slice(&(id as u32).to_le_bytes());
+            positions.extend_from_slice(&(ply.min(u16::MAX as usize) as u16).to_le_bytes());
+            let signature = material_signature(&board);
+            if signature != last_signature {
+                material.push_str(&format!("{} {} {}\n", signature, id, ply));
+                last_signature = signature;
+            }
+        }
+        self.append(GAMES_FILE, record.to_line().as_bytes())?;
+        self.append(POSITIONS_FILE, &positions)?;
+        self.append(MATERIAL_FILE, material.as_bytes())?;
+        for record in positions.chunks_exact(POSITION_RECORD) {
+            let mut hash = [0; 8];
+            hash.copy_from_slice(&record[..8]);
+            let ply = u16::from_le_bytes([record[12], record[13]]) as usize;
+            self.positions
+                .entry(u64::from_le_bytes(hash))
+                .or_default()
+                .push((id, ply));
+        }
+        for line in material.lines() {
+            let mut fields = line.split(' ');
+            let signature = fields.next().unwrap().to_string();
+            let ply = fields.nth(1).unwrap().parse().unwrap();
+            self.material.entry(signature).or_default().push((id, ply));
+        }
+        self.games.push(record);
+        Ok(id)
+    }
+
+    fn append(&self, name: &str, bytes: &[u8]) -> Result<(), String> {
+        let path = self.directory.join(name);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(bytes))
+            .map_err(|error| format!("Can't write {}: {}", path.display(), error))
+    }
+
+    /// Every game that reached the position, at the first time it did
+    pub fn find_position(&self, board: &ChessBoard, turn: Color) -> Vec<PositionMatch<'_>> {
+        self.matches(self.positions.get(&board.zobrist_hash(turn)))
+    }
+
+    /// Every game that reached the material signature, like `KRPvKR`, at the first time it did
+    pub fn find_material(&self, signature: &str) -> Vec<PositionMatch<'_>> {
+        self.matches(self.material.get(signature))
+    }
+
+    /// Games where a player's name contains `name`, ignoring case
+    pub fn games_by_player(&self, name: &str) -> Vec<&GameRecord> {
+        let name = name.to_lowercase();
+        self.games
+            .iter()
+            .filter(|game| {
+                game.white.to_lowercase().contains(&name)
+                    || game.black.to_lowercase().contains(&name)
+            })
+            .collect()
+    }
+
+    fn matches(&self, occurrences: Option<&Occurrences>) -> Vec<PositionMatch<'_>> {
+        let mut matches: Vec<PositionMatch> = Vec::new();
+        for &(game, ply) in occurrences.into_iter().flatten() {
+            if matches.last().is_some_and(|last| last.game.id == game) {
+                continue;
+            }
+            if let Some(game) = self.games.get(game) {
+                matches.push(PositionMatch { game, ply });
+            }
+        }
+        matches
+    }
+}
+
+/// The pieces of both sides like `KQRRBNPPPPvKQRBBPPPPP`, White first
+pub fn material_signature(board: &ChessBoard) -> String {
+    let mut sides = [String::new(), String::new()];
+    for piece in board.ref_board().iter().flatten().flatten() {
+        let side = if piece.color == Color::White { 0 } else { 1 };
+        sides[side].push(piece.letter().to_ascii_uppercase());
+    }
+    for side in sides.iter_mut() {
+        let order = |letter: char| "KQRBNP".find(letter).unwrap_or(6);
+        let mut letters: Vec<char> = side.chars().collect();
+        letters.sort_by_key(|&letter| (order(letter), letter));
+        *side = letters.into_iter().collect();
+    }
+    format!("{}v{}", sides[0], sides[1])
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
@@ -0,0 +1,94 @@
+use super::*;
+
+const PGN: &str = r#"[Event "Club"]
+[Date "2024.01.05"]
+[White "Anna Berg"]
+[Black "Carl Dahl"]
+[Result "1-0"]
+[WhiteElo "1850"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Bxc6 dxc6 1-0
+
+[Event "Club"]
+[White "Carl Dahl"]
+[Black "Eva Fors"]
+[Result "1/2-1/2"]
+
+1. Nf3 Nc6 2. e4 e5 3. d4 1/2-1/2
+
+[Event "Broken"]
+[White "Anna Berg"]
+[Black "Eva Fors"]
+[Result "*"]
+
+1. e4 e5 2. Ke3 *
+"#;
+
+fn database(name: &str) -> GameDatabase {
+    let directory = std::env::temp_dir().join(format!("maltebl_chess_database_{}", name));
+    let _ = fs::remove_dir_all(&directory);
+    GameDatabase::open(directory).unwrap()
+}
+
+fn position(moves: &[&str]) -> (ChessBoard, Color) {
+    let mut game = init_standard_chess();
+    for mov in moves {
+        game.play(mov).unwrap();
+    }
+    (game.chess_board().clone(), game.current_player())
+}
+
+#[test]
+fn imports_pgn() {
+    let mut database = database("import");
+    let report = database.import_pgn(PGN).unwrap();
+    assert_eq!(report.imported, 2);
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.errors[0].starts_with("Game 3"));
+    let game = database.game(0).unwrap();
+    assert_eq!(game.white, "Anna Berg");
+    assert_eq!(game.white_elo, Some(1850));
+    assert_eq!(game.black_elo, None);
+    assert_eq!(game.moves.len(), 8);
+    assert_eq!(
+        game.to_game().unwrap().to_pgn(),
+        ChessGame::from_pgn(PGN).unwrap().to_pgn()
+    );
+}
+
+#[test]
+fn finds_positions() {
+    let mut database = database("positions");
+    database.import_pgn(PGN).unwrap();
+    let (board, turn) = position(&["e4", "e5", "Nf3", "Nc6"]);
+    let matches = database.find_position(&board, turn);
+    assert_eq!(matches.len(), 2);
+    assert_eq!((matches[0].game.id, matches[0].ply), (0, 4));
+    assert_eq!(matches[0].next_move(), Some("Bb5"));
+    assert_eq!(matches[1].last_move(), Some("e5"));
+    assert_eq!(matches[1].next_move(), Some("d4"));
+    let (board, turn) = position(&["d4"]);
+    assert!(database.find_position(&board, turn).is_empty());
+    // the index is read back from disk
+    let reopened = GameDatabase::open(&database.directory).unwrap();
+    let (board, turn) = position(&[]);
+    assert_eq!(reopened.find_position(&board, turn).len(), 2);
+    assert_eq!(reopened.games(), database.games());
+}
+
+#[test]
+fn finds_players_and_material() {
+    let mut database = database("material");
+    database.import_pgn(PGN).unwrap();
+    assert_eq!(database.games_by_player("carl").len(), 2);
+    assert_eq!(database.games_by_player("Anna")[0].id, 0);
+    let (board, _) = position(&["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6"]);
+    assert_eq!(
+        material_signature(&board),
+        "KQRRBBNNPPPPPPPPvKQRRBBNPPPPPPPP"
+    );
+    let matches = database.find_material("KQRRBNNPPPPPPPPvKQRRBBNPPPPPPPP");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].ply, 8);
+    assert_eq!(matches[0].last_move(), Some("dxc6"));
+}
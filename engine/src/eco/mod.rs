@@ -0,0 +1,104 @@
+use crate::board_logic::*;
+use crate::piece_logic::Color;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[cfg(test)]
+mod tests;
+
+/// Lines of `code`, `name` and the moves in SAN, separated by tabs, in the
+/// order of the codes and with at least one line for every code
+const TABLE: &str = include_str!("openings.tsv");
+
+/// Named opening of the Encyclopaedia of Chess Openings
+#[derive(PartialEq, Clone, Debug)]
+pub struct Opening {
+    /// ECO code from A00 to E99
+    pub eco: &'static str,
+    /// Opening and variation, like `Sicilian Defense: Najdorf Variation`
+    pub name: &'static str,
+    /// Moves in SAN from the starting position
+    pub moves: &'static str,
+}
+
+struct Index {
+    openings: Vec<Opening>,
+    positions: HashMap<u64, usize>,
+}
+
+/// Reads the table and hashes the position at the end of every line,
+/// the first line reaching a position names it
+fn index() -> &'static Index {
+    static INDEX: OnceLock<Index> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut index = Index {
+            openings: Vec::new(),
+            positions: HashMap::new(),
+        };
+        // positions at the end of earlier lines, most lines continue one of them
+        let mut reached: HashMap<&str, (ChessBoard, Color)> = HashMap::new();
+        let start = ChessBoard::from_fen(STARTING_FEN).unwrap();
+        reached.insert("", (start.board, start.turn));
+        for line in TABLE.lines().filter(|line| !line.is_empty()) {
+            let mut fields = line.split('\t');
+            let (eco, name, moves) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(eco), Some(name), Some(moves)) => (eco, name, moves),
+                _ => panic!("Invalid line in the opening table: {:?}", line),
+            };
+            let known = moves
+                .rmatch_indices(' ')
+                .map(|(end, _)| &moves[..end])
+                .chain(std::iter::once(""))
+                .find(|prefix| reached.contains_key(prefix))
+                .unwrap();
+            let (mut board, mut turn) = reached[known].clone();
+            for san in moves[known.len()..].split_whitespace() {
+                if let Err(error) = play_san(&mut board, turn, san) {
+                    panic!("Can't play {} in {}: {}", san, name, error);
+                }
+                turn = match turn {
+                    Color::White => Color::Black,
+                    Color::Black => Color::White,
+                };
+            }
+            index
+                .positions
+                .entry(board.zobrist_hash(turn))
+                .or_insert(index.openings.len());
+            reached.insert(moves, (board, turn));
+            index.openings.push(Opening { eco, name, moves });
+        }
+        index
+    })
+}
+
+/// Every opening of the table
+pub fn openings() -> &'static [Opening] {
+    &index().openings
+}
+
+/// The opening named after this exact position, whatever move order led to it
+pub fn classify(board: &ChessBoard, turn: Color) -> Option<&'static Opening> {
+    let index = index();
+    index
+        .positions
+        .get(&board.zobrist_hash(turn))
+        .map(|&i| &index.openings[i])
+}
+
+fn play_san(board: &mut ChessBoard, turn: Color, san: &str) -> Result<(), String> {
+    let san = san.trim_end_matches(['+', '#']);
+    let mov = board
+        .legal_moves(turn)
+        .into_iter()
+        .find(|mov| {
+            let promotion = mov.promotion.as_ref().map(|p| format!("={}", p));
+            board
+                .san(mov.from, mov.to)
+                .ok()
+                .map(|text| text + &promotion.unwrap_or_default())
+                == Some(san.to_string())
+        })
+        .ok_or_else(|| format!("{} is not a legal move", san))?;
+    board.make_move(&mov).map(|_| ())
+}
@@ -0,0 +1,45 @@
+use super::*;
+use crate::chess_game::*;
+
+fn opening_after(moves: &str) -> Option<&'static Opening> {
+    let mut game = init_standard_chess();
+    for san in moves.split_whitespace() {
+        game.play(san).unwrap();
+    }
+    classify(game.chess_board(), game.current_player())
+}
+
+#[test]
+fn reads_the_table() {
+    let openings = openings();
+    assert!(openings.len() > 200);
+    assert_eq!(openings.first().unwrap().eco, "A00");
+    assert_eq!(openings.last().unwrap().eco, "E99");
+    // every line ends in its own position
+    assert_eq!(index().positions.len(), openings.len());
+}
+
+#[test]
+fn covers_every_code() {
+    for volume in "ABCDE".chars() {
+        for number in 0..100 {
+            let eco = format!("{}{:02}", volume, number);
+            assert!(
+                openings().iter().any(|opening| opening.eco == eco),
+                "{} is missing",
+                eco
+            );
+        }
+    }
+}
+
+#[test]
+fn recognizes_transpositions() {
+    let najdorf = opening_after("e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6").unwrap();
+    assert_eq!(najdorf.eco, "B90");
+    assert_eq!(najdorf.name, "Sicilian Defense: Najdorf Variation");
+    let semi_slav = opening_after("d4 d5 c4 c6 Nf3 Nf6 Nc3 e6").unwrap();
+    assert_eq!(semi_slav.eco, "D43");
+    assert_eq!(opening_after("Nf3 d5 d4").unwrap().eco, "D02");
+    assert!(opening_after("e4 e5 Qh5").is_none());
+}
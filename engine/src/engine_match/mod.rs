@@ -0,0 +1,685 @@
+use crate::board_logic::Move;
+use crate::chess_game::*;
+use crate::clock::*;
+use crate::piece_logic::Color;
+use crate::search::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests;
+
+/// One side of a match, either the search of this crate or an external UCI engine
+#[derive(PartialEq, Clone, Debug)]
+pub struct EngineSpec {
+    pub name: String,
+    /// Command line of a UCI engine, `None` for the built-in search
+    pub command: Option<String>,
+    /// Most half moves searched, needed when there is no time control
+    pub depth: Option<usize>,
+    pub threads: usize,
+}
+
+impl EngineSpec {
+    pub fn builtin(name: &str, depth: usize) -> Self {
+        EngineSpec {
+            name: name.to_string(),
+            command: None,
+            depth: Some(depth),
+            threads: 1,
+        }
+    }
+
+    /// Reads comma separated settings, e.g. `name=new,depth=4,threads=2` for the built-in
+    /// search or `uci=/usr/bin/stockfish,name=sf` for a UCI engine
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut spec = EngineSpec {
+            name: String::new(),
+            command: None,
+            depth: None,
+            threads: 1,
+        };
+        for setting in text.split(',') {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value in {:?}", setting))?;
+            let number = || {
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&number| number > 0)
+                    .ok_or_else(|| format!("Invalid {} {:?}", key, value))
+            };
+            match key.trim() {
+                "name" => spec.name = value.to_string(),
+                "uci" => spec.command = Some(value.to_string()),
+                "depth" => spec.depth = Some(number()?),
+                "threads" => spec.threads = number()?,
+                _ => return Err(format!("Unknown engine setting {:?}", key)),
+            }
+        }
+        if spec.name.is_empty() {
+            spec.name = match (&spec.command, spec.depth) {
+                (Some(command), _) => command.clone(),
+                (None, Some(depth)) => format!("depth {}", depth),
+                (None, None) => "maltebl-chess".to_string(),
+            };
+        }
+        Ok(spec)
+    }
+
+    fn start(&self) -> Result<Box<dyn MatchEngine>, String> {
+        match &self.command {
+            Some(command) => Ok(Box::new(UciEngine::start(command, self)?)),
+            None => Ok(Box::new(BuiltinEngine {
+                depth: self.depth.unwrap_or(64),
+                threads: self.threads,
+                engine: Engine::new(),
+            })),
+        }
+    }
+}
+
+/// Sequential probability ratio test of the hypotheses that the first engine is `elo0`
+/// or `elo1` stronger, with `alpha` and `beta` as the chances of wrongly accepting either
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SprtVerdict {
+    /// Not stronger by `elo1`
+    AcceptH0,
+    /// Stronger by at least `elo0`
+    AcceptH1,
+    Continue,
+}
+
+impl Sprt {
+    /// Test with 5% error rates
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// Log-likelihood ratio of the results, using a normal approximation of the scores
+    pub fn llr(&self, record: &Record) -> f64 {
+        let games = record.games() as f64;
+        if record.games() == 0 {
+            return 0.0;
+        }
+        let score = record.score();
+        let squares = (record.wins as f64 + record.draws as f64 / 4.0) / games;
+        let variance = (squares - score * score) / games;
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// LLR below the first bound accepts H0, above the second H1
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn verdict(&self, record: &Record) -> SprtVerdict {
+        let llr = self.llr(record);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtVerdict::AcceptH1
+        } else if llr <= lower {
+            SprtVerdict::AcceptH0
+        } else {
+            SprtVerdict::Continue
+        }
+    }
+}
+
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+/// Results of the first engine
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Record {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Points per game, from 0 to 1
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+
+    /// Elo difference that gives the score, infinite when every game was won or lost
+    pub fn elo(&self) -> f64 {
+        elo(self.score())
+    }
+
+    /// Half the width of the 95% confidence interval of `elo`, infinite with only wins or losses
+    pub fn elo_error(&self) -> f64 {
+        let games = self.games() as f64;
+        if self.wins == self.games() || self.losses == self.games() {
+            return f64::INFINITY;
+        }
+        let score = self.score();
+        let deviation = |points: f64| (points - score).powi(2);
+        let variance = (self.wins as f64 * deviation(1.0)
+            + self.draws as f64 * deviation(0.5)
+            + self.losses as f64 * deviation(0.0))
+            / games;
+        let margin = 1.96 * (variance / games).sqrt();
+        let bounded = |score: f64| score.clamp(1e-9, 1.0 - 1e-9);
+        (elo(bounded(score + margin)) - elo(bounded(score - margin))) / 2.0
+    }
+
+    fn add(&mut self, points: f64) {
+        if points > 0.75 {
+            self.wins += 1;
+        } else if points < 0.25 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "+{} ={} -{} ({:.1}%), Elo {:+.1} ± {:.1}",
+            self.wins,
+            self.draws,
+            self.losses,
+            self.score() * 100.0,
+            self.elo(),
+            self.elo_error()
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchSettings {
+    pub engines: [EngineSpec; 2],
+    /// Starting positions in FEN, each is played once with either engine as White
+    pub openings: Vec<String>,
+    /// Times to go through the openings
+    pub rounds: usize,
+    pub time_control: Option<TimeControl>,
+    /// Games played at the same time
+    pub concurrency: usize,
+    /// Half moves after which a game is adjudicated a draw
+    pub max_plies: usize,
+    /// Stops starting new games once the test decides
+    pub sprt: Option<Sprt>,
+}
+
+impl MatchSettings {
+    pub fn new(engines: [EngineSpec; 2], openings: Vec<String>) -> Self {
+        MatchSettings {
+            engines,
+            openings,
+            rounds: 1,
+            time_control: None,
+            concurrency: 1,
+            max_plies: 400,
+            sprt: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchGame {
+    /// Place in the schedule, starting at 0
+    pub number: usize,
+    pub opening: usize,
+    /// Whether the first engine had White
+    pub first_white: bool,
+    /// As written in PGN, `1-0`, `0-1` or `1/2-1/2`
+    pub result: &'static str,
+    /// Why the game ended, e.g. `checkmate` or `threefold repetition`
+    pub termination: String,
+    pub pgn: String,
+}
+
+impl MatchGame {
+    /// Points of the first engine
+    pub fn points(&self) -> f64 {
+        match (self.result, self.first_white) {
+            ("1-0", true) | ("0-1", false) => 1.0,
+            ("1-0", false) | ("0-1", true) => 0.0,
+            _ => 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchReport {
+    pub names: [String; 2],
+    /// Games in the order they were scheduled
+    pub games: Vec<MatchGame>,
+    pub record: Record,
+    pub sprt: Option<(Sprt, SprtVerdict)>,
+}
+
+impl MatchReport {
+    /// Every game, separated by blank lines
+    pub fn to_pgn(&self) -> String {
+        self.games
+            .iter()
+            .map(|game| game.pgn.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} vs {}: {}", self.names[0], self.names[1], self.record)?;
+        if let Some((sprt, verdict)) = &self.sprt {
+            let (lower, upper) = sprt.bounds();
+            let verdict = match verdict {
+                SprtVerdict::AcceptH0 => "H0 accepted",
+                SprtVerdict::AcceptH1 => "H1 accepted",
+                SprtVerdict::Continue => "inconclusive",
+            };
+            write!(
+                f,
+                "\nSPRT [{}, {}]: LLR {:.2} ({:.2}, {:.2}), {}",
+                sprt.elo0,
+                sprt.elo1,
+                sprt.llr(&self.record),
+                lower,
+                upper,
+                verdict
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads one FEN or EPD position per line, skipping empty lines and `#` comments
+pub fn read_openings(text: &str) -> Result<Vec<String>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 {
+                return Err(format!("Invalid opening {:?}", line));
+            }
+            let counters = fields.len() >= 6
+                && fields[4..6]
+                    .iter()
+                    .all(|field| field.parse::<usize>().is_ok());
+            let fen = if counters {
+                fields[..6].join(" ")
+            } else {
+                format!("{} 0 1", fields[..4].join(" "))
+            };
+            ChessGame::from_fen(&fen)?;
+            Ok(fen)
+        })
+        .collect()
+}
+
+/// Plays every opening with both colours, `progress` is called with each finished game.
+/// Games end by their `GameStatus`, or as a draw after a threefold repetition, fifty
+/// moves without capture or pawn move, or `max_plies`. An illegal move or a crashed engine
+/// loses the game.
+pub fn run_match(
+    settings: &MatchSettings,
+    mut progress: impl FnMut(&MatchGame, &Record),
+) -> Result<MatchReport, String> {
+    if settings.openings.is_empty() {
+        return Err("The match needs at least one opening".to_string());
+    }
+    if settings.rounds == 0 {
+        return Err("The match needs at least one round".to_string());
+    }
+    for engine in &settings.engines {
+        if engine.depth.is_none() && settings.time_control.is_none() {
+            return Err(format!(
+                "Give {} a depth or the match a time control",
+                engine.name
+            ));
+        }
+    }
+    let total = settings.openings.len() * 2 * settings.rounds;
+    let next = AtomicUsize::new(0);
+    let decided = AtomicBool::new(false);
+    let mut report = MatchReport {
+        names: [
+            settings.engines[0].name.clone(),
+            settings.engines[1].name.clone(),
+        ],
+        games: Vec::new(),
+        record: Record::default(),
+        sprt: settings.sprt.map(|sprt| (sprt, SprtVerdict::Continue)),
+    };
+    let (sender, receiver) = mpsc::channel::<Result<MatchGame, String>>();
+    thread::scope(|scope| {
+        for _ in 0..settings.concurrency.clamp(1, total) {
+            let sender = sender.clone();
+            let (next, decided) = (&next, &decided);
+            scope.spawn(move || {
+                let mut engines = match start_engines(settings) {
+                    Ok(engines) => engines,
+                    Err(error) => {
+                        let _ = sender.send(Err(error));
+                        return;
+                    }
+                };
+                while !decided.load(Ordering::Relaxed) {
+                    let number = next.fetch_add(1, Ordering::Relaxed);
+                    if number >= total {
+                        break;
+                    }
+                    let result = play_game(settings, &mut engines, number);
+                    if sender.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        let mut error = None;
+        for result in receiver {
+            match result {
+                Ok(game) => {
+                    report.record.add(game.points());
+                    if let Some((sprt, verdict)) = report.sprt.as_mut() {
+                        *verdict = sprt.verdict(&report.record);
+                        if *verdict != SprtVerdict::Continue {
+                            decided.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    progress(&game, &report.record);
+                    report.games.push(game);
+                }
+                Err(message) => {
+                    decided.store(true, Ordering::Relaxed);
+                    error.get_or_insert(message);
+                }
+            }
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    })?;
+    report.games.sort_by_key(|game| game.number);
+    Ok(report)
+}
+
+fn start_engines(settings: &MatchSettings) -> Result<[Box<dyn MatchEngine>; 2], String> {
+    Ok([settings.engines[0].start()?, settings.engines[1].start()?])
+}
+
+fn play_game(
+    settings: &MatchSettings,
+    engines: &mut [Box<dyn MatchEngine>; 2],
+    number: usize,
+) -> Result<MatchGame, String> {
+    let opening = number / 2 % settings.openings.len();
+    let first_white = number.is_multiple_of(2);
+    let fen = &settings.openings[opening];
+    let names = [&settings.engines[0].name, &settings.engines[1].name];
+    let (white, black) = if first_white { (0, 1) } else { (1, 0) };
+    let mut game = ChessGame::from_fen(fen)?;
+    game.set_tag("Event", "Engine match");
+    game.set_tag("Round", &(number + 1).to_string());
+    game.set_tag("White", names[white]);
+    game.set_tag("Black", names[black]);
+    for engine in engines.iter_mut() {
+        engine.new_game()?;
+    }
+    if let Some(control) = &settings.time_control {
+        game.set_clock(Clock::new(
+            control.clone(),
+            Box::new(SystemTimeSource::new()),
+        ));
+    }
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    let mut plies = 0;
+    let (result, termination) = loop {
+        let turn = game.current_player();
+        let status = game.status();
+        if status != GameStatus::Ongoing {
+            break (status.result(), termination(status));
+        }
+        let repetitions = seen
+            .entry(game.chess_board().zobrist_hash(turn))
+            .or_insert(0);
+        *repetitions += 1;
+        if *repetitions >= 3 {
+            break ("1/2-1/2", "threefold repetition".to_string());
+        }
+        if game.halfmove_clock() >= 100 {
+            break ("1/2-1/2", "fifty move rule".to_string());
+        }
+        if plies >= settings.max_plies {
+            break ("1/2-1/2", "adjudicated after the move limit".to_string());
+        }
+        let mover = if turn == Color::White { white } else { black };
+        let played = engines[mover]
+            .choose(&game, fen)
+            .and_then(|mov| game.play(&mov.to_coordinates()));
+        if let Err(error) = played {
+            if game.status() != GameStatus::Ongoing {
+                continue;
+            }
+            let loss = if turn == Color::White { "0-1" } else { "1-0" };
+            break (loss, format!("{} forfeits: {}", names[mover], error));
+        }
+        plies += 1;
+    };
+    game.set_tag("Result", result);
+    game.set_tag("Termination", &termination);
+    Ok(MatchGame {
+        number,
+        opening,
+        first_white,
+        result,
+        termination,
+        pgn: game.to_pgn(),
+    })
+}
+
+fn termination(status: GameStatus) -> String {
+    match status {
+        GameStatus::Checkmate(_) => "checkmate".to_string(),
+        GameStatus::KingCaptured(_) => "king captured".to_string(),
+        GameStatus::OutOfTime(_) => "time forfeit".to_string(),
+        GameStatus::Resigned(_) => "resignation".to_string(),
+        GameStatus::Draw(reason) => format!("{:?}", reason).to_lowercase(),
+        GameStatus::Ongoing => "unterminated".to_string(),
+    }
+}
+
+fn move_time(game: &ChessGame) -> Option<Duration> {
+    let clock = game.clock()?;
+    let color = game.current_player();
+    Some(time_for_move(
+        clock.remaining(color),
+        increment(clock),
+        clock.moves_to_go(color),
+    ))
+}
+
+fn increment(clock: &Clock) -> Duration {
+    match clock.control().stages.first().map(|stage| stage.bonus) {
+        Some(Bonus::Fischer(time)) | Some(Bonus::Bronstein(time)) => time,
+        _ => Duration::default(),
+    }
+}
+
+/// A running engine of a match
+trait MatchEngine: Send {
+    fn new_game(&mut self) -> Result<(), String>;
+
+    /// Move for the side to move, `start` is the FEN the game began from
+    fn choose(&mut self, game: &ChessGame, start: &str) -> Result<Move, String>;
+}
+
+struct BuiltinEngine {
+    depth: usize,
+    threads: usize,
+    engine: Engine,
+}
+
+impl MatchEngine for BuiltinEngine {
+    fn new_game(&mut self) -> Result<(), String> {
+        self.engine.clear();
+        Ok(())
+    }
+
+    fn choose(&mut self, game: &ChessGame, _: &str) -> Result<Move, String> {
+        let limits = SearchLimits {
+            depth: self.depth,
+            time: move_time(game),
+            threads: self.threads,
+            stop: None,
+        };
+        self.engine
+            .search(game.chess_board(), game.current_player(), &limits)
+            .map(|result| result.best_move)
+            .ok_or_else(|| "No move found".to_string())
+    }
+}
+
+/// Engine in another process, talking UCI over its standard input and output
+struct UciEngine {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    depth: Option<usize>,
+}
+
+impl UciEngine {
+    fn start(command: &str, spec: &EngineSpec) -> Result<Self, String> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or("Empty engine command")?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Can't start {}: {}", command, error))?;
+        let mut engine = UciEngine {
+            input: child.stdin.take().unwrap(),
+            output: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            depth: spec.depth,
+        };
+        engine.send("uci")?;
+        engine.wait_for("uciok")?;
+        if spec.threads > 1 {
+            engine.send(&format!("setoption name Threads value {}", spec.threads))?;
+        }
+        Ok(engine)
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.input, "{}", line)
+            .and_then(|_| self.input.flush())
+            .map_err(|error| format!("Engine stopped listening: {}", error))
+    }
+
+    /// Reads lines up to one starting with `word`, which is returned
+    fn wait_for(&mut self, word: &str) -> Result<String, String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.output.read_line(&mut line) {
+                Ok(0) => return Err("Engine quit".to_string()),
+                Ok(_) if line.split_whitespace().next() == Some(word) => {
+                    return Ok(line.trim().to_string())
+                }
+                Ok(_) => (),
+                Err(error) => return Err(format!("Can't read from engine: {}", error)),
+            }
+        }
+    }
+}
+
+impl MatchEngine for UciEngine {
+    fn new_game(&mut self) -> Result<(), String> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok").map(|_| ())
+    }
+
+    fn choose(&mut self, game: &ChessGame, start: &str) -> Result<Move, String> {
+        let moves: Vec<String> = game
+            .history()
+            .iter()
+            .map(|played| played.to_move().to_coordinates())
+            .collect();
+        let mut position = format!("position fen {}", start);
+        if !moves.is_empty() {
+            position = format!("{} moves {}", position, moves.join(" "));
+        }
+        self.send(&position)?;
+        let go = match game.clock() {
+            Some(clock) => {
+                let millis = |time: Duration| time.as_millis();
+                let increment = millis(increment(clock));
+                let mut go = format!(
+                    "go wtime {} btime {} winc {} binc {}",
+                    millis(clock.remaining(Color::White)),
+                    millis(clock.remaining(Color::Black)),
+                    increment,
+                    increment
+                );
+                if let Some(moves) = clock.moves_to_go(game.current_player()) {
+                    go = format!("{} movestogo {}", go, moves);
+                }
+                go
+            }
+            None => format!("go depth {}", self.depth.unwrap_or(1)),
+        };
+        self.send(&go)?;
+        let line = self.wait_for("bestmove")?;
+        let coordinates = line.split_whitespace().nth(1).unwrap_or("(none)");
+        game.parse_move(coordinates)
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,97 @@
+use super::*;
+
+#[test]
+fn estimates_elo() {
+    let record = Record {
+        wins: 60,
+        draws: 20,
+        losses: 20,
+    };
+    assert!((record.score() - 0.7).abs() < 1e-9);
+    assert!((record.elo() - 147.19).abs() < 0.01);
+    assert!(record.elo_error() > 20.0 && record.elo_error() < 100.0);
+    let even = Record {
+        wins: 10,
+        draws: 0,
+        losses: 10,
+    };
+    assert!(even.elo().abs() < 1e-9);
+}
+
+#[test]
+fn decides_sprt() {
+    let sprt = Sprt::new(0.0, 10.0);
+    let strong = Record {
+        wins: 600,
+        draws: 200,
+        losses: 200,
+    };
+    let weak = Record {
+        wins: 200,
+        draws: 200,
+        losses: 600,
+    };
+    let close = Record {
+        wins: 10,
+        draws: 10,
+        losses: 9,
+    };
+    assert_eq!(sprt.verdict(&strong), SprtVerdict::AcceptH1);
+    assert_eq!(sprt.verdict(&weak), SprtVerdict::AcceptH0);
+    assert_eq!(sprt.verdict(&close), SprtVerdict::Continue);
+}
+
+#[test]
+fn parses_engines() {
+    assert_eq!(
+        EngineSpec::parse("name=new,depth=4,threads=2"),
+        Ok(EngineSpec {
+            name: "new".to_string(),
+            command: None,
+            depth: Some(4),
+            threads: 2,
+        })
+    );
+    let uci = EngineSpec::parse("uci=/usr/bin/stockfish").unwrap();
+    assert_eq!(uci.name, "/usr/bin/stockfish");
+    assert_eq!(uci.command.as_deref(), Some("/usr/bin/stockfish"));
+    assert!(EngineSpec::parse("depth=0").is_err());
+    assert!(EngineSpec::parse("speed=3").is_err());
+}
+
+#[test]
+fn reads_openings() {
+    let openings = read_openings(
+        "# two openings\n\
+         rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\n\n\
+         4k3/8/8/8/8/8/8/4K2R w K - bm Rh8+;\n",
+    )
+    .unwrap();
+    assert_eq!(openings.len(), 2);
+    assert_eq!(openings[1], "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert!(read_openings("not a position").is_err());
+}
+
+#[test]
+fn plays_match() {
+    let mut settings = MatchSettings::new(
+        [EngineSpec::builtin("a", 1), EngineSpec::builtin("b", 1)],
+        vec!["4k3/8/8/8/8/8/8/R3K3 w Q - 0 1".to_string()],
+    );
+    settings.concurrency = 2;
+    settings.max_plies = 20;
+    let mut finished = 0;
+    let report = run_match(&settings, |_, _| finished += 1).unwrap();
+    assert_eq!(finished, 2);
+    assert_eq!(report.games.len(), 2);
+    assert!(report.games[0].first_white && !report.games[1].first_white);
+    assert_eq!(report.record.games(), 2);
+    assert_eq!(report.record.wins, report.record.losses);
+    let pgn = report.to_pgn();
+    assert_eq!(pgn.matches("[White \"a\"]").count(), 1);
+    assert!(pgn.contains("[Termination "));
+    assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1\"]"));
+
+    settings.rounds = 0;
+    assert!(run_match(&settings, |_, _| ()).is_err());
+}
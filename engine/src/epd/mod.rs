@@ -0,0 +1,300 @@
+use crate::chess_game::*;
+use crate::search::*;
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// Position of an Extended Position Description test suite, like
+/// `r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - bm Nxc6; id "test";`
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct EpdRecord {
+    /// The four position fields of FEN, without the move counters
+    pub position: String,
+    pub id: Option<String>,
+    /// `bm`: moves in SAN of which one has to be found
+    pub best_moves: Vec<String>,
+    /// `am`: moves in SAN that must not be played
+    pub avoid_moves: Vec<String>,
+    /// `acd`: depth of the analysis
+    pub depth: Option<usize>,
+    /// `ce`: evaluation in centipawns for the side to move
+    pub score: Option<i32>,
+    /// `pv`: expected line of play in SAN
+    pub pv: Vec<String>,
+    /// Any other operations as opcode and operands
+    pub other: Vec<(String, String)>,
+}
+
+impl EpdRecord {
+    pub fn parse(line: &str) -> Result<EpdRecord, String> {
+        let mut rest = line.trim();
+        let mut position: Vec<&str> = Vec::new();
+        while position.len() < 4 {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("Expected 4 position fields in EPD {:?}", line));
+            }
+            position.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+        let mut record = EpdRecord {
+            position: position.join(" "),
+            ..Default::default()
+        };
+        for operation in split_operations(rest) {
+            let (opcode, operands) = match operation.split_once(char::is_whitespace) {
+                Some((opcode, operands)) => (opcode, operands.trim()),
+                None => (operation.as_str(), ""),
+            };
+            let moves = || operands.split_whitespace().map(String::from).collect();
+            match opcode {
+                "id" => record.id = Some(operands.trim_matches('"').to_string()),
+                "bm" => record.best_moves = moves(),
+                "am" => record.avoid_moves = moves(),
+                "pv" => record.pv = moves(),
+                "acd" => {
+                    record.depth = Some(
+                        operands
+                            .parse()
+                            .map_err(|_| format!("Invalid acd {:?}", operands))?,
+                    )
+                }
+                "ce" => {
+                    record.score = Some(
+                        operands
+                            .parse()
+                            .map_err(|_| format!("Invalid ce {:?}", operands))?,
+                    )
+                }
+                _ => record
+                    .other
+                    .push((opcode.to_string(), operands.to_string())),
+            }
+        }
+        let game = ChessGame::from_fen(&record.fen())?;
+        for (opcode, moves) in [("bm", &record.best_moves), ("am", &record.avoid_moves)].iter() {
+            for san in moves.iter() {
+                game.parse_move(san)
+                    .map_err(|error| format!("Invalid {} move {:?}: {}", opcode, san, error))?;
+            }
+        }
+        Ok(record)
+    }
+
+    /// The position as full FEN
+    pub fn fen(&self) -> String {
+        format!("{} 0 1", self.position)
+    }
+
+    /// Writes the record back as one EPD line
+    pub fn to_epd(&self) -> String {
+        let mut operations: Vec<String> = Vec::new();
+        if !self.best_moves.is_empty() {
+            operations.push(format!("bm {}", self.best_moves.join(" ")));
+        }
+        if !self.avoid_moves.is_empty() {
+            operations.push(format!("am {}", self.avoid_moves.join(" ")));
+        }
+        if let Some(id) = &self.id {
+            operations.push(format!("id \"{}\"", id));
+        }
+        if let Some(depth) = self.depth {
+            operations.push(format!("acd {}", depth));
+        }
+        if let Some(score) = self.score {
+            operations.push(format!("ce {}", score));
+        }
+        if !self.pv.is_empty() {
+            operations.push(format!("pv {}", self.pv.join(" ")));
+        }
+        for (opcode, operands) in &self.other {
+            operations.push(format!("{} {}", opcode, operands).trim_end().to_string());
+        }
+        let mut line = self.position.clone();
+        for operation in operations {
+            line.push_str(&format!(" {};", operation));
+        }
+        line
+    }
+
+    /// Whether playing `input` (coordinates or SAN) passes the `bm` and `am` tests, never
+    /// for a record without either
+    pub fn accepts(&self, input: &str) -> Result<bool, String> {
+        if self.best_moves.is_empty() && self.avoid_moves.is_empty() {
+            return Ok(false);
+        }
+        let game = ChessGame::from_fen(&self.fen())?;
+        let mov = game.parse_move(input)?;
+        let matches = |moves: &[String]| -> Result<bool, String> {
+            for san in moves {
+                if game.parse_move(san)? == mov {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+        Ok((self.best_moves.is_empty() || matches(&self.best_moves)?)
+            && !matches(&self.avoid_moves)?)
+    }
+}
+
+/// Splits operations at semicolons that aren't inside a quoted string
+fn split_operations(text: &str) -> Vec<String> {
+    let mut operations: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for symbol in text.chars() {
+        match symbol {
+            '"' => {
+                quoted = !quoted;
+                current.push(symbol);
+            }
+            ';' if !quoted => operations.push(std::mem::take(&mut current)),
+            _ => current.push(symbol),
+        }
+    }
+    operations.push(current);
+    operations
+        .into_iter()
+        .map(|operation| operation.trim().to_string())
+        .filter(|operation| !operation.is_empty())
+        .collect()
+}
+
+/// Reads a suite with one record per line, skipping empty lines and `#` comments
+pub fn read_epd(text: &str) -> Result<Vec<EpdRecord>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            EpdRecord::parse(line).map_err(|error| format!("Line {}: {}", i + 1, error))
+        })
+        .collect()
+}
+
+/// How the engine did on one record
+#[derive(Clone, Debug)]
+pub struct EpdResult {
+    pub record: EpdRecord,
+    /// Move the engine chose in SAN, `None` without legal moves
+    pub found: Option<String>,
+    pub solved: bool,
+    pub score: i32,
+    pub depth: usize,
+    pub nodes: u64,
+}
+
+impl EpdResult {
+    /// How far the score is off the `ce` of the record, with mates counted as in EPD
+    pub fn score_error(&self) -> Option<i32> {
+        let score = if self.score > MATE - 1000 {
+            32767 - (MATE - self.score)
+        } else if self.score < -MATE + 1000 {
+            -32767 + (MATE + self.score)
+        } else {
+            self.score
+        };
+        self.record.score.map(|expected| score - expected)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SuiteReport {
+    pub results: Vec<EpdResult>,
+}
+
+impl SuiteReport {
+    pub fn solved(&self) -> usize {
+        self.results.iter().filter(|result| result.solved).count()
+    }
+
+    pub fn failed(&self) -> Vec<&EpdResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.solved)
+            .collect()
+    }
+}
+
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, result) in self.results.iter().enumerate() {
+            write!(
+                f,
+                "{:<12} {:<6} found {:<8} expected {:<16} depth {:>2} score {:>6}",
+                result
+                    .record
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| (i + 1).to_string()),
+                if result.solved { "solved" } else { "FAILED" },
+                result.found.as_deref().unwrap_or("-"),
+                expectation(&result.record),
+                result.depth,
+                result.score
+            )?;
+            if let Some(error) = result.score_error() {
+                write!(f, " ce {:+}", error)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "Score: {}/{}", self.solved(), self.results.len())
+    }
+}
+
+fn expectation(record: &EpdRecord) -> String {
+    let mut parts = record.best_moves.clone();
+    parts.extend(record.avoid_moves.iter().map(|san| format!("!{}", san)));
+    parts.join(" ")
+}
+
+/// Searches every record with `limits`, calling `progress` after each one. Without a time
+/// limit a record is searched to its `acd` depth, if it has one.
+/// A record counts as solved when the chosen move is one of `bm` and none of `am`, one with
+/// neither or with moves that don't parse counts as failed.
+/// The records share one transposition table, cleared before each of them.
+pub fn run_suite(
+    records: &[EpdRecord],
+    limits: &SearchLimits,
+    mut progress: impl FnMut(&EpdResult),
+) -> Result<SuiteReport, String> {
+    let mut report = SuiteReport::default();
+    let mut engine = Engine::new();
+    for record in records {
+        let game = ChessGame::from_fen(&record.fen())?;
+        engine.clear();
+        let mut limits = limits.clone();
+        if let (Some(depth), None) = (record.depth, limits.time) {
+            limits.depth = depth;
+        }
+        let result = match engine.search(game.chess_board(), game.current_player(), &limits) {
+            Some(found) => {
+                let mov = found.best_move;
+                let mut san = game.chess_board().san(mov.from, mov.to)?;
+                if let Some(piece_type) = &mov.promotion {
+                    san = format!("{}={}", san, piece_type);
+                }
+                EpdResult {
+                    record: record.clone(),
+                    solved: record.accepts(&mov.to_coordinates()).unwrap_or(false),
+                    found: Some(san),
+                    score: found.score,
+                    depth: found.depth,
+                    nodes: found.nodes,
+                }
+            }
+            None => EpdResult {
+                record: record.clone(),
+                found: None,
+                solved: false,
+                score: 0,
+                depth: 0,
+                nodes: 0,
+            },
+        };
+        progress(&result);
+        report.results.push(result);
+    }
+    Ok(report)
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+const SUITE: &str = "\
+# mates and a piece up
+6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"back rank\"; ce 32766; pv Ra8#;
+r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - am Qe2; acd 4; id \"no queen\";
+4k3/8/8/3q4/8/8/3R4/3K4 w - - bm Rxd5; c0 \"free; queen\"; id \"queen\";
+";
+
+#[test]
+fn reads_records() {
+    let records = read_epd(SUITE).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].id.as_deref(), Some("back rank"));
+    assert_eq!(records[0].best_moves, vec!["Ra8#"]);
+    assert_eq!(records[0].score, Some(32766));
+    assert_eq!(records[0].pv, vec!["Ra8#"]);
+    assert_eq!(records[1].avoid_moves, vec!["Qe2"]);
+    assert_eq!(records[1].depth, Some(4));
+    assert_eq!(
+        records[2].other,
+        vec![("c0".to_string(), "\"free; queen\"".to_string())]
+    );
+    assert_eq!(EpdRecord::parse(&records[2].to_epd()).unwrap(), records[2]);
+    assert!(EpdRecord::parse("8/8/8/8 w - -").is_err());
+    // the moves to find have to be legal in the position
+    let error = EpdRecord::parse("4k3/8/8/8/8/8/8/4K3 w - - bm Qd8;").unwrap_err();
+    assert!(error.contains("Invalid bm move \"Qd8\""));
+}
+
+#[test]
+fn checks_moves() {
+    let records = read_epd(SUITE).unwrap();
+    assert!(records[0].accepts("a1a8").unwrap());
+    assert!(!records[0].accepts("Kf1").unwrap());
+    assert!(!records[1].accepts("d1e2").unwrap());
+    assert!(records[1].accepts("Bc4").unwrap());
+    let untested = EpdRecord::parse("4k3/8/8/8/8/8/8/4K3 w - - id \"kings\";").unwrap();
+    assert!(!untested.accepts("Kd2").unwrap());
+}
+
+#[test]
+fn runs_suite() {
+    let records = read_epd(SUITE).unwrap();
+    let limits = SearchLimits {
+        depth: 2,
+        time: None,
+        threads: 1,
+        stop: None,
+    };
+    let mut seen = 0;
+    let report = run_suite(&records, &limits, |_| seen += 1).unwrap();
+    assert_eq!(seen, 3);
+    assert_eq!(report.solved(), 3);
+    assert!(report.failed().is_empty());
+    assert!(report.to_string().ends_with("Score: 3/3"));
+    // mate in one is ce 32766, and acd gives the depth without a time limit
+    assert_eq!(report.results[0].score_error(), Some(0));
+    assert!(report
+        .to_string()
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("ce +0"));
+    assert_eq!(report.results[1].depth, 4);
+    assert_eq!(report.results[2].score_error(), None);
+
+    // a record that was built with a move that doesn't parse fails instead of ending the run
+    let mut broken = records[2].clone();
+    broken.best_moves = vec!["Qd8".to_string()];
+    let report = run_suite(&[broken], &limits, |_| ()).unwrap();
+    assert_eq!(report.solved(), 0);
+}
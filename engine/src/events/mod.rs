@@ -0,0 +1,88 @@
+use crate::chess_game::{GameStatus, PlayedMove};
+use crate::piece_logic::{Color, PieceType};
+use std::sync::mpsc::{self, Receiver};
+
+#[cfg(test)]
+mod tests;
+
+/// How a piece got to its square
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MoveKind {
+    Normal,
+    PawnDoubleStep,
+    EnPassant,
+    CastleKingSide,
+    CastleQueenSide,
+}
+
+/// Something that happened in a `ChessGame`, see `ChessGame::subscribe`
+#[derive(PartialEq, Clone, Debug)]
+pub enum GameEvent {
+    MoveMade {
+        color: Color,
+        from: (usize, usize),
+        to: (usize, usize),
+        /// Piece taken, a pawn for en passant
+        captured: Option<PieceType>,
+        kind: MoveKind,
+    },
+    /// A pawn became `piece_type`, after the move that brought it there
+    Promotion {
+        square: (usize, usize),
+        piece_type: PieceType,
+    },
+    /// The king of the given colour is in check
+    Check(Color),
+    GameEnded(GameStatus),
+    /// The last move was taken back
+    Undo(PlayedMove),
+    /// The board was set to a position not reached by a move, e.g. another game or node
+    PositionLoaded {
+        fen: String,
+    },
+}
+
+pub type ObserverId = usize;
+
+type Callback = Box<dyn FnMut(&GameEvent) + Send>;
+
+/// Callbacks registered on a game
+#[derive(Default)]
+pub struct Observers {
+    next_id: ObserverId,
+    callbacks: Vec<(ObserverId, Callback)>,
+}
+
+impl Observers {
+    pub(crate) fn add(&mut self, callback: Callback) -> ObserverId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.callbacks.push((id, callback));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: ObserverId) -> bool {
+        let count = self.callbacks.len();
+        self.callbacks.retain(|(observer, _)| *observer != id);
+        self.callbacks.len() < count
+    }
+
+    /// Sends every event to a channel, the receiver can go to another thread
+    pub(crate) fn channel(&mut self) -> Receiver<GameEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.add(Box::new(move |event| {
+            let _ = sender.send(event.clone());
+        }));
+        receiver
+    }
+
+    pub(crate) fn emit(&mut self, event: GameEvent) {
+        for (_, callback) in self.callbacks.iter_mut() {
+            callback(&event);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+}
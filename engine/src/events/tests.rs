@@ -0,0 +1,105 @@
+use super::*;
+use crate::board_logic::STARTING_FEN;
+use crate::chess_game::*;
+use std::sync::{Arc, Mutex};
+
+fn recorded(game: &mut ChessGame) -> Arc<Mutex<Vec<GameEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    game.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+    events
+}
+
+#[test]
+fn reports_moves_and_mate() {
+    let mut game = init_standard_chess();
+    let events = recorded(&mut game);
+    for san in ["f3", "e5", "g4", "Qh4#"].iter() {
+        game.play(san).unwrap();
+    }
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 6);
+    assert_eq!(
+        events[1],
+        GameEvent::MoveMade {
+            color: Color::Black,
+            from: (4, 6),
+            to: (4, 4),
+            captured: None,
+            kind: MoveKind::PawnDoubleStep,
+        }
+    );
+    assert_eq!(events[4], GameEvent::Check(Color::White));
+    assert_eq!(
+        events[5],
+        GameEvent::GameEnded(GameStatus::Checkmate(Color::Black))
+    );
+}
+
+#[test]
+fn tells_special_moves() {
+    let mut game = ChessGame::from_fen("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq e3 0 1").unwrap();
+    let events = game.events();
+    game.play("dxe3").unwrap();
+    game.play("O-O").unwrap();
+    game.play("O-O-O").unwrap();
+    let kinds: Vec<_> = events
+        .try_iter()
+        .filter_map(|event| match event {
+            GameEvent::MoveMade { captured, kind, .. } => Some((captured, kind)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (Some(PieceType::Pawn), MoveKind::EnPassant),
+            (None, MoveKind::CastleKingSide),
+            (None, MoveKind::CastleQueenSide),
+        ]
+    );
+}
+
+#[test]
+fn announces_promotion_after_the_move() {
+    let mut game = ChessGame::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let events = recorded(&mut game);
+    game.play("b8=Q").unwrap();
+    let events = events.lock().unwrap();
+    assert!(matches!(events[0], GameEvent::MoveMade { .. }));
+    assert_eq!(
+        events[1..],
+        [
+            GameEvent::Promotion {
+                square: (1, 7),
+                piece_type: PieceType::Queen,
+            },
+            GameEvent::Check(Color::Black),
+        ]
+    );
+}
+
+#[test]
+fn reports_undo_and_loading() {
+    let mut game = init_standard_chess();
+    let events = recorded(&mut game);
+    game.play("e4").unwrap();
+    game.undo().unwrap();
+    game.load(init_standard_chess());
+    let id = game.subscribe(|_| ());
+    assert!(game.unsubscribe(id));
+    assert!(!game.unsubscribe(id));
+    game.resign(Color::White).unwrap();
+    let events = events.lock().unwrap();
+    assert!(matches!(&events[1], GameEvent::Undo(played) if played.san == "e4"));
+    assert_eq!(
+        events[2],
+        GameEvent::PositionLoaded {
+            fen: STARTING_FEN.to_string()
+        }
+    );
+    assert_eq!(
+        events[3],
+        GameEvent::GameEnded(GameStatus::Resigned(Color::Black))
+    );
+}
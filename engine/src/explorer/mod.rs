@@ -0,0 +1,124 @@
+use crate::board_logic::ChessBoard;
+use crate::database::*;
+use crate::piece_logic::Color;
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// Results of the games that went on with one move, or of all games in a position
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct MoveStats {
+    /// Move in SAN, empty for the totals
+    pub san: String,
+    pub games: usize,
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+    rating_sum: u64,
+    ratings: u64,
+}
+
+impl MoveStats {
+    fn add(&mut self, game: &GameRecord) {
+        self.games += 1;
+        match game.result.as_str() {
+            "1-0" => self.white_wins += 1,
+            "0-1" => self.black_wins += 1,
+            "1/2-1/2" => self.draws += 1,
+            _ => (),
+        }
+        for elo in game.white_elo.iter().chain(game.black_elo.iter()) {
+            self.rating_sum += *elo as u64;
+            self.ratings += 1;
+        }
+    }
+
+    fn percent(&self, count: usize) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.games as f64
+        }
+    }
+
+    pub fn white_percent(&self) -> f64 {
+        self.percent(self.white_wins)
+    }
+
+    pub fn draw_percent(&self) -> f64 {
+        self.percent(self.draws)
+    }
+
+    pub fn black_percent(&self) -> f64 {
+        self.percent(self.black_wins)
+    }
+
+    /// Average rating of the players with a known rating
+    pub fn average_rating(&self) -> Option<u32> {
+        (self.ratings > 0).then(|| (self.rating_sum / self.ratings) as u32)
+    }
+}
+
+/// Every continuation played from a position, the most popular first
+#[derive(Clone, Debug, Default)]
+pub struct OpeningTree {
+    pub total: MoveStats,
+    pub moves: Vec<MoveStats>,
+}
+
+/// Collects the moves played after the position in the games of `database`
+pub fn explore(database: &GameDatabase, board: &ChessBoard, turn: Color) -> OpeningTree {
+    let mut tree = OpeningTree::default();
+    for found in database.find_position(board, turn) {
+        tree.total.add(found.game);
+        let san = match found.next_move() {
+            Some(san) => san,
+            None => continue,
+        };
+        let index = match tree.moves.iter().position(|stats| stats.san == san) {
+            Some(index) => index,
+            None => {
+                tree.moves.push(MoveStats {
+                    san: san.to_string(),
+                    ..Default::default()
+                });
+                tree.moves.len() - 1
+            }
+        };
+        tree.moves[index].add(found.game);
+    }
+    tree.moves
+        .sort_by(|a, b| b.games.cmp(&a.games).then_with(|| a.san.cmp(&b.san)));
+    tree
+}
+
+impl fmt::Display for OpeningTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<8} {:>6}  {:>5}  {:>5}  {:>5}  {:>6}",
+            "Move", "Games", "White", "Draw", "Black", "Rating"
+        )?;
+        for stats in self.moves.iter().chain(std::iter::once(&self.total)) {
+            writeln!(
+                f,
+                "{:<8} {:>6}  {:>4.0}%  {:>4.0}%  {:>4.0}%  {:>6}",
+                if stats.san.is_empty() {
+                    "Total"
+                } else {
+                    &stats.san
+                },
+                stats.games,
+                stats.white_percent(),
+                stats.draw_percent(),
+                stats.black_percent(),
+                stats
+                    .average_rating()
+                    .map(|rating| rating.to_string())
+                    .unwrap_or_else(|| "-".to_string())
+            )?;
+        }
+        Ok(())
+    }
+}
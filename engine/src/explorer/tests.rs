@@ -0,0 +1,58 @@
+use super::*;
+use crate::chess_game::*;
+
+const PGN: &str = r#"[White "A"]
+[Black "B"]
+[Result "1-0"]
+[WhiteElo "2000"]
+[BlackElo "1800"]
+
+1. e4 e5 2. Nf3 1-0
+
+[White "C"]
+[Black "D"]
+[Result "0-1"]
+[WhiteElo "1600"]
+
+1. e4 c5 0-1
+
+[White "E"]
+[Black "F"]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Bc4 1/2-1/2
+
+[White "G"]
+[Black "H"]
+[Result "1-0"]
+
+1. d4 1-0
+"#;
+
+#[test]
+fn counts_continuations() {
+    let directory = std::env::temp_dir().join("maltebl_chess_explorer");
+    let _ = std::fs::remove_dir_all(&directory);
+    let mut database = GameDatabase::open(&directory).unwrap();
+    database.import_pgn(PGN).unwrap();
+    let mut game = init_standard_chess();
+    let tree = explore(&database, game.chess_board(), game.current_player());
+    assert_eq!(tree.total.games, 4);
+    assert_eq!(tree.moves.len(), 2);
+    let e4 = &tree.moves[0];
+    assert_eq!((e4.san.as_str(), e4.games), ("e4", 3));
+    assert_eq!((e4.white_wins, e4.draws, e4.black_wins), (1, 1, 1));
+    assert!((e4.white_percent() - 100.0 / 3.0).abs() < 1e-9);
+    assert_eq!(e4.average_rating(), Some(1800));
+    assert_eq!(tree.moves[1].average_rating(), None);
+
+    game.play("e4").unwrap();
+    game.play("e5").unwrap();
+    let tree = explore(&database, game.chess_board(), game.current_player());
+    let moves: Vec<&str> = tree.moves.iter().map(|stats| stats.san.as_str()).collect();
+    assert_eq!(moves, vec!["Bc4", "Nf3"]);
+    assert_eq!(tree.total.draw_percent(), 50.0);
+    let table = tree.to_string();
+    assert!(table.lines().nth(1).unwrap().starts_with("Bc4"));
+    assert!(table.lines().last().unwrap().starts_with("Total"));
+}
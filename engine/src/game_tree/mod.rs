@@ -0,0 +1,209 @@
+use crate::chess_game::PlayedMove;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
+pub type NodeId = usize;
+
+/// Position reached before any move was made
+pub const ROOT: NodeId = 0;
+
+/// One move of a game record with its annotations, the first child continues the line
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    /// `None` only for the root
+    pub played: Option<PlayedMove>,
+    pub comment: Option<String>,
+    /// Numeric annotation glyphs, see `nag_from_symbol`
+    pub nags: Vec<u8>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// Game record with variations, nodes keep their id until they are deleted
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::GameTreeData"))]
+pub struct GameTree {
+    nodes: Vec<Option<Node>>,
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameTree {
+    pub fn new() -> Self {
+        GameTree {
+            nodes: vec![Some(Node {
+                played: None,
+                comment: None,
+                nags: Vec::new(),
+                parent: None,
+                children: Vec::new(),
+            })],
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id).and_then(|node| node.as_ref())
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id).and_then(|node| node.as_mut())
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).and_then(|node| node.parent)
+    }
+
+    /// Continuations after `id`, the mainline first
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map_or(&[], |node| &node.children[..])
+    }
+
+    /// Adds `played` after `parent`, or returns the existing node if that move is already there
+    pub fn add_move(&mut self, parent: NodeId, played: PlayedMove) -> Result<NodeId, String> {
+        if let Some(child) = self.find_child(parent, &played, None) {
+            return Ok(child);
+        }
+        let id = self.nodes.len();
+        self.node_mut(parent)
+            .ok_or_else(|| format!("There is no node {}", parent))?
+            .children
+            .push(id);
+        self.nodes.push(Some(Node {
+            played: Some(played),
+            comment: None,
+            nags: Vec::new(),
+            parent: Some(parent),
+            children: Vec::new(),
+        }));
+        Ok(id)
+    }
+
+    /// Folds `id` into an earlier sibling holding the same move and returns the node that is
+    /// kept. For moves completed after they were added, like a promotion once its piece is
+    /// chosen.
+    pub fn merge_into_sibling(&mut self, id: NodeId) -> NodeId {
+        let (parent, played) = match self.node(id) {
+            Some(Node {
+                parent: Some(parent),
+                played: Some(played),
+                children,
+                ..
+            }) if children.is_empty() => (*parent, played.clone()),
+            _ => return id,
+        };
+        match self.find_child(parent, &played, Some(id)) {
+            Some(sibling) => {
+                self.delete(id).unwrap();
+                sibling
+            }
+            None => id,
+        }
+    }
+
+    /// Child of `parent` that holds the move of `played`, other than `except`
+    fn find_child(
+        &self,
+        parent: NodeId,
+        played: &PlayedMove,
+        except: Option<NodeId>,
+    ) -> Option<NodeId> {
+        self.children(parent).iter().copied().find(|&child| {
+            Some(child) != except
+                && matches!(&self.node(child).unwrap().played, Some(other)
+                    if other.from == played.from
+                        && other.to == played.to
+                        && other.promotion == played.promotion)
+        })
+    }
+
+    /// Nodes from the first move up to and including `id`
+    pub fn path(&self, id: NodeId) -> Option<Vec<NodeId>> {
+        self.node(id)?;
+        let mut path = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            path.push(current);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Nodes of the main line, following the first child from the root
+    pub fn mainline(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut current = ROOT;
+        while let Some(&next) = self.children(current).first() {
+            line.push(next);
+            current = next;
+        }
+        line
+    }
+
+    /// Makes the line through `id` the main line, all the way up to the root
+    pub fn promote(&mut self, id: NodeId) -> Result<(), String> {
+        self.node(id)
+            .ok_or_else(|| format!("There is no node {}", id))?;
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            let children = &mut self.node_mut(parent).unwrap().children;
+            children.retain(|&child| child != current);
+            children.insert(0, current);
+            current = parent;
+        }
+        Ok(())
+    }
+
+    /// Removes `id` with every move after it
+    pub fn delete(&mut self, id: NodeId) -> Result<(), String> {
+        let parent = self
+            .parent(id)
+            .ok_or_else(|| format!("Can't delete node {}", id))?;
+        self.node_mut(parent)
+            .unwrap()
+            .children
+            .retain(|&child| child != id);
+        let mut removed = vec![id];
+        while let Some(node) = removed.pop() {
+            if let Some(node) = self.nodes[node].take() {
+                removed.extend(node.children);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Numeric annotation glyph for the usual move suffixes
+pub fn nag_from_symbol(symbol: &str) -> Option<u8> {
+    match symbol {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+pub fn nag_symbol(nag: u8) -> Option<&'static str> {
+    match nag {
+        1 => Some("!"),
+        2 => Some("?"),
+        3 => Some("!!"),
+        4 => Some("??"),
+        5 => Some("!?"),
+        6 => Some("?!"),
+        _ => None,
+    }
+}
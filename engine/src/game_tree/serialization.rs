@@ -0,0 +1,54 @@
+use super::*;
+use std::convert::TryFrom;
+
+/// Unchecked nodes of a `GameTree`, validated before a tree is built from them
+#[derive(serde::Deserialize)]
+pub struct GameTreeData {
+    nodes: Vec<Option<Node>>,
+}
+
+impl TryFrom<GameTreeData> for GameTree {
+    type Error = String;
+
+    fn try_from(data: GameTreeData) -> Result<Self, Self::Error> {
+        let nodes = data.nodes;
+        match nodes.first() {
+            Some(Some(root)) if root.parent.is_none() && root.played.is_none() => (),
+            _ => return Err("The record has no root".to_string()),
+        }
+        // every node has to be reached once from the root, by children pointing back
+        let mut reached = vec![false; nodes.len()];
+        reached[ROOT] = true;
+        let mut unvisited = vec![ROOT];
+        while let Some(id) = unvisited.pop() {
+            for &child in &nodes[id].as_ref().unwrap().children {
+                let valid = matches!(nodes.get(child), Some(Some(node))
+                    if node.parent == Some(id) && node.played.is_some());
+                if !valid || reached[child] {
+                    return Err(format!("Invalid child {} of node {}", child, id));
+                }
+                reached[child] = true;
+                unvisited.push(child);
+            }
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            let node = match node {
+                Some(node) => node,
+                None => continue,
+            };
+            if !reached[id] {
+                return Err(format!("Node {} is not connected to the root", id));
+            }
+            if let Some(played) = &node.played {
+                let on_board = |(x, y): (usize, usize)| x < 8 && y < 8;
+                if !on_board(played.from) || !on_board(played.to) {
+                    return Err(format!(
+                        "Move {} of node {} is off the board",
+                        played.san, id
+                    ));
+                }
+            }
+        }
+        Ok(GameTree { nodes })
+    }
+}
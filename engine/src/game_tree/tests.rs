@@ -0,0 +1,76 @@
+use super::*;
+use crate::piece_logic::PieceType;
+
+fn played(from: (usize, usize), to: (usize, usize), san: &str) -> PlayedMove {
+    PlayedMove {
+        from,
+        to,
+        promotion: None,
+        san: san.to_string(),
+        clock: None,
+    }
+}
+
+#[test]
+fn builds_variations() {
+    let mut tree = GameTree::new();
+    let e4 = tree.add_move(ROOT, played((4, 1), (4, 3), "e4")).unwrap();
+    let e5 = tree.add_move(e4, played((4, 6), (4, 4), "e5")).unwrap();
+    let c5 = tree.add_move(e4, played((2, 6), (2, 4), "c5")).unwrap();
+    assert_eq!(tree.add_move(e4, played((2, 6), (2, 4), "c5")).unwrap(), c5);
+    assert_eq!(tree.children(e4), &[e5, c5]);
+    assert_eq!(tree.mainline(), vec![e4, e5]);
+    assert_eq!(tree.path(c5), Some(vec![e4, c5]));
+    assert_eq!(tree.parent(c5), Some(e4));
+}
+
+#[test]
+fn promotes_and_deletes() {
+    let mut tree = GameTree::new();
+    let e4 = tree.add_move(ROOT, played((4, 1), (4, 3), "e4")).unwrap();
+    let e5 = tree.add_move(e4, played((4, 6), (4, 4), "e5")).unwrap();
+    let c5 = tree.add_move(e4, played((2, 6), (2, 4), "c5")).unwrap();
+    let nf3 = tree.add_move(c5, played((6, 0), (5, 2), "Nf3")).unwrap();
+    tree.promote(nf3).unwrap();
+    assert_eq!(tree.mainline(), vec![e4, c5, nf3]);
+    tree.delete(c5).unwrap();
+    assert_eq!(tree.mainline(), vec![e4, e5]);
+    assert!(tree.node(nf3).is_none());
+    assert!(tree.delete(ROOT).is_err());
+    assert!(tree.promote(c5).is_err());
+}
+
+#[test]
+fn merges_completed_promotions() {
+    let mut tree = GameTree::new();
+    let promotion = |piece_type| PlayedMove {
+        promotion: piece_type,
+        ..played((4, 6), (4, 7), "e8")
+    };
+    let queen = tree
+        .add_move(ROOT, promotion(Some(PieceType::Queen)))
+        .unwrap();
+    assert_eq!(
+        tree.add_move(ROOT, promotion(Some(PieceType::Queen)))
+            .unwrap(),
+        queen
+    );
+    // the piece is chosen after the move is added
+    let pending = tree.add_move(ROOT, promotion(None)).unwrap();
+    assert_ne!(pending, queen);
+    tree.node_mut(pending).unwrap().played = Some(promotion(Some(PieceType::Queen)));
+    assert_eq!(tree.merge_into_sibling(pending), queen);
+    assert!(tree.node(pending).is_none());
+    assert_eq!(tree.children(ROOT), &[queen]);
+    let knight = tree.add_move(ROOT, promotion(None)).unwrap();
+    tree.node_mut(knight).unwrap().played = Some(promotion(Some(PieceType::Knight)));
+    assert_eq!(tree.merge_into_sibling(knight), knight);
+    assert_eq!(tree.children(ROOT), &[queen, knight]);
+}
+
+#[test]
+fn translates_nags() {
+    assert_eq!(nag_from_symbol("!?"), Some(5));
+    assert_eq!(nag_symbol(4), Some("??"));
+    assert_eq!(nag_from_symbol("!!!"), None);
+}
@@ -1,6 +1,19 @@
 pub mod board_logic;
+pub mod clock;
 pub mod console_display;
+pub mod database;
+pub mod eco;
+pub mod engine_match;
+pub mod epd;
+pub mod events;
+pub mod explorer;
+pub mod game_tree;
+pub mod pgn;
 pub mod piece_logic;
+pub mod player;
+pub mod puzzle;
+pub mod search;
+pub mod solver;
 
 /// Engine for the boardgame "chess"
 ///
@@ -31,12 +44,115 @@ pub mod piece_logic;
 /// ````
 pub mod chess_game {
     use super::*;
-    use crate::{board_logic::*, piece_logic::*};
+    use crate::{board_logic::*, clock::*, events::*, game_tree::*, piece_logic::*};
+    use std::sync::mpsc::Receiver;
+    use std::time::Duration;
 
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(try_from = "serialization::ChessGameData"))]
     pub struct ChessGame {
         chess_board: ChessBoard,
-        history: Vec<String>,
         turn: (Color, usize),
+        variant: Variant,
+        /// position and turn at the root of the record
+        start: (ChessBoard, (Color, usize)),
+        /// half moves without capture or pawn move before the root
+        start_halfmove_clock: usize,
+        record: GameTree,
+        current: NodeId,
+        /// PGN tag pairs besides the result
+        tags: Vec<(String, String)>,
+        /// result that doesn't follow from the board, like a resignation
+        outcome: Option<GameStatus>,
+        draw_offer: Option<Color>,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        clock: Option<Clock>,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        observers: Observers,
+        /// extra time for a side, added when the clock is set
+        time_odds: Option<(Color, Duration)>,
+    }
+
+    /// A move as it was played, kept for the game record
+    #[derive(PartialEq, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayedMove {
+        pub from: (usize, usize),
+        pub to: (usize, usize),
+        pub promotion: Option<PieceType>,
+        pub san: String,
+        /// Time left on the mover's clock after the move
+        pub clock: Option<Duration>,
+    }
+
+    impl PlayedMove {
+        pub fn to_move(&self) -> Move {
+            Move {
+                from: self.from,
+                to: self.to,
+                promotion: self.promotion.clone(),
+            }
+        }
+    }
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum GameStatus {
+        Ongoing,
+        /// Won by the given color
+        Checkmate(Color),
+        KingCaptured(Color),
+        OutOfTime(Color),
+        Resigned(Color),
+        Draw(DrawReason),
+    }
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum DrawReason {
+        /// A player ran out of time but the opponent can't mate
+        InsufficientMaterial,
+        Stalemate,
+        Agreement,
+    }
+
+    impl GameStatus {
+        /// Result as written in PGN
+        pub fn result(&self) -> &'static str {
+            match self {
+                GameStatus::Ongoing => "*",
+                GameStatus::Draw(_) => "1/2-1/2",
+                GameStatus::Checkmate(Color::White)
+                | GameStatus::KingCaptured(Color::White)
+                | GameStatus::OutOfTime(Color::White)
+                | GameStatus::Resigned(Color::White) => "1-0",
+                _ => "0-1",
+            }
+        }
+    }
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Variant {
+        Standard,
+        /// Fog of war: players only see what their pieces reach, there is no check
+        /// and the game is won by capturing the king
+        Dark,
+    }
+
+    /// Odds given by the stronger player, see `init_handicap_chess`
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum Handicap {
+        /// Without the f-pawn, and the opponent moves first
+        PawnAndMove,
+        /// Without the queen's knight
+        Knight,
+        /// Without the queen's rook, so there is no castling on that side
+        Rook,
+        Queen,
+        /// The opponent gets this much more time on the clock
+        Time(Duration),
     }
 
     impl ChessGame {
@@ -44,6 +160,10 @@ pub mod chess_game {
             self.chess_board.get_board()
         }
 
+        pub fn chess_board(&self) -> &ChessBoard {
+            &self.chess_board
+        }
+
         pub fn pick_piece(&self, input: String) -> Result<Vec<String>, String> {
             let piece_position = to_coords(input).expect("Error:");
             if let Some(piece) = self.chess_board.ref_piece(piece_position) {
@@ -64,7 +184,308 @@ pub mod chess_game {
             self.turn.0
         }
 
+        /// Pieces giving check to the side to move
+        pub fn checkers(&self) -> Vec<(usize, usize)> {
+            self.chess_board.checkers(self.current_player())
+        }
+
+        pub fn variant(&self) -> Variant {
+            self.variant
+        }
+
+        /// Moves leading up to the current position
+        pub fn history(&self) -> Vec<PlayedMove> {
+            self.record
+                .path(self.current)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| self.record.node(id).unwrap().played.clone())
+                .collect()
+        }
+
+        pub fn record(&self) -> &GameTree {
+            &self.record
+        }
+
+        pub fn current_node(&self) -> NodeId {
+            self.current
+        }
+
+        /// Calls `callback` with every event of the game from now on
+        pub fn subscribe(
+            &mut self,
+            callback: impl FnMut(&GameEvent) + Send + 'static,
+        ) -> ObserverId {
+            self.observers.add(Box::new(callback))
+        }
+
+        /// Returns whether there was such an observer
+        pub fn unsubscribe(&mut self, id: ObserverId) -> bool {
+            self.observers.remove(id)
+        }
+
+        /// Channel receiving every event of the game from now on
+        pub fn events(&mut self) -> Receiver<GameEvent> {
+            self.observers.channel()
+        }
+
+        /// Replaces the game with `other`, e.g. one read from FEN or PGN, keeping the observers
+        pub fn load(&mut self, other: ChessGame) {
+            let observers = std::mem::take(&mut self.observers);
+            *self = other;
+            self.observers = observers;
+            self.position_loaded();
+        }
+
+        fn position_loaded(&mut self) {
+            if !self.observers.is_empty() {
+                let fen = self.to_fen();
+                self.observers.emit(GameEvent::PositionLoaded { fen });
+            }
+        }
+
+        /// Check and the end of the game after a move
+        fn announce_position(&mut self) {
+            if self.observers.is_empty() {
+                return;
+            }
+            let color = self.current_player();
+            if self.variant != Variant::Dark && self.chess_board.is_checked(color) {
+                self.observers.emit(GameEvent::Check(color));
+            }
+            let status = self.status();
+            if status != GameStatus::Ongoing {
+                self.observers.emit(GameEvent::GameEnded(status));
+            }
+        }
+
+        /// What a move from `from` to `to` takes and what kind of move it is
+        fn move_details(
+            &self,
+            from: (usize, usize),
+            to: (usize, usize),
+        ) -> (Option<PieceType>, MoveKind) {
+            let captured = self
+                .chess_board
+                .ref_piece(to)
+                .map(|piece| piece.piece_type.clone());
+            let across = to.0 as isize - from.0 as isize;
+            let piece_type = self
+                .chess_board
+                .ref_piece(from)
+                .map(|piece| &piece.piece_type);
+            match piece_type {
+                Some(PieceType::King) if across == 2 => (None, MoveKind::CastleKingSide),
+                Some(PieceType::King) if across == -2 => (None, MoveKind::CastleQueenSide),
+                Some(PieceType::Pawn) if across != 0 && captured.is_none() => {
+                    (Some(PieceType::Pawn), MoveKind::EnPassant)
+                }
+                Some(PieceType::Pawn) if from.1.abs_diff(to.1) == 2 => {
+                    (None, MoveKind::PawnDoubleStep)
+                }
+                _ => (captured, MoveKind::Normal),
+            }
+        }
+
+        /// Sets up the position at `node` by replaying the moves from the root
+        pub fn go_to(&mut self, node: NodeId) -> Result<(), String> {
+            self.replay(node)?;
+            self.position_loaded();
+            Ok(())
+        }
+
+        fn replay(&mut self, node: NodeId) -> Result<(), String> {
+            let path = self
+                .record
+                .path(node)
+                .ok_or_else(|| format!("There is no node {}", node))?;
+            let (board, turn) = self.position_after(&path)?;
+            self.draw_offer = None;
+            self.chess_board = board;
+            self.turn = turn;
+            self.current = node;
+            Ok(())
+        }
+
+        fn position_after(&self, path: &[NodeId]) -> Result<(ChessBoard, (Color, usize)), String> {
+            let (mut board, mut turn) = self.start.clone();
+            for &id in path {
+                let played = self.record.node(id).unwrap().played.as_ref().unwrap();
+                board.move_piece(played.from, played.to)?;
+                if let Some(piece_type) = &played.promotion {
+                    board.promote(played.to, piece_type.clone())?;
+                }
+                turn = (opponent(turn.0), turn.1 + 1);
+            }
+            Ok((board, turn))
+        }
+
+        /// Moves of the line through the current position, continuing with the first
+        /// child after it
+        pub fn line(&self) -> Vec<NodeId> {
+            let mut line = self.record.path(self.current).unwrap_or_default();
+            let mut last = self.current;
+            while let Some(&next) = self.record.children(last).first() {
+                line.push(next);
+                last = next;
+            }
+            line
+        }
+
+        /// Half moves played before the current position
+        pub fn ply(&self) -> usize {
+            self.record.path(self.current).map_or(0, |path| path.len())
+        }
+
+        /// Board after `ply` half moves of `line`, without changing the game
+        pub fn position_at(&self, ply: usize) -> Result<ChessBoard, String> {
+            let line = self.line();
+            if ply > line.len() {
+                return Err(format!("The game has only {} half moves", line.len()));
+            }
+            Ok(self.position_after(&line[..ply])?.0)
+        }
+
+        /// Goes to the position after `ply` half moves of `line`
+        pub fn go_to_ply(&mut self, ply: usize) -> Result<(), String> {
+            let line = self.line();
+            match ply {
+                0 => self.go_to(ROOT),
+                _ => match line.get(ply - 1) {
+                    Some(&node) => self.go_to(node),
+                    None => Err(format!("The game has only {} half moves", line.len())),
+                },
+            }
+        }
+
+        pub fn go_first(&mut self) -> Result<(), String> {
+            self.go_to(ROOT)
+        }
+
+        pub fn go_back(&mut self) -> Result<(), String> {
+            let parent = self
+                .record
+                .parent(self.current)
+                .ok_or("Already at the start")?;
+            self.go_to(parent)
+        }
+
+        pub fn go_forward(&mut self) -> Result<(), String> {
+            let next = *self
+                .record
+                .children(self.current)
+                .first()
+                .ok_or("Already at the end")?;
+            self.go_to(next)
+        }
+
+        pub fn go_last(&mut self) -> Result<(), String> {
+            let last = self.line().last().copied().unwrap_or(ROOT);
+            self.go_to(last)
+        }
+
+        /// Drops every move after the current position so play continues from here,
+        /// along with the result the game ended in
+        pub fn branch_from_here(&mut self) -> Result<(), String> {
+            for child in self.record.children(self.current).to_vec() {
+                self.record.delete(child)?;
+            }
+            self.outcome = None;
+            self.tags.retain(|(name, _)| name != "Result");
+            Ok(())
+        }
+
+        pub fn set_comment(&mut self, node: NodeId, comment: Option<String>) -> Result<(), String> {
+            self.record
+                .node_mut(node)
+                .ok_or_else(|| format!("There is no node {}", node))?
+                .comment = comment;
+            Ok(())
+        }
+
+        /// Adds an annotation like `!?` or `$14` to the move at `node`
+        pub fn add_nag(&mut self, node: NodeId, nag: &str) -> Result<(), String> {
+            let nag = nag_from_symbol(nag)
+                .or_else(|| nag.strip_prefix('$').and_then(|n| n.parse().ok()))
+                .ok_or_else(|| format!("Unknown annotation {:?}", nag))?;
+            self.record
+                .node_mut(node)
+                .ok_or_else(|| format!("There is no node {}", node))?
+                .nags
+                .push(nag);
+            Ok(())
+        }
+
+        pub fn promote_variation(&mut self, node: NodeId) -> Result<(), String> {
+            self.record.promote(node)
+        }
+
+        /// Deletes `node` and the moves after it, stepping back if the current position goes with it
+        pub fn delete_variation(&mut self, node: NodeId) -> Result<(), String> {
+            let parent = self
+                .record
+                .parent(node)
+                .ok_or_else(|| format!("Can't delete node {}", node))?;
+            let current_path = self.record.path(self.current).unwrap_or_default();
+            self.record.delete(node)?;
+            if current_path.contains(&node) {
+                self.go_to(parent)?;
+            }
+            Ok(())
+        }
+
+        /// Starts the clock for the player to move
+        pub fn set_clock(&mut self, mut clock: Clock) {
+            if let Some((color, time)) = self.time_odds {
+                clock.add_time(color, time);
+            }
+            clock.start(self.current_player());
+            self.clock = Some(clock);
+        }
+
+        pub fn clock(&self) -> Option<&Clock> {
+            self.clock.as_ref()
+        }
+
+        pub fn status(&self) -> GameStatus {
+            if let Some(outcome) = self.outcome {
+                return outcome;
+            }
+            for &color in [Color::White, Color::Black].iter() {
+                if !self.chess_board.has_king(color) {
+                    return GameStatus::KingCaptured(opponent(color));
+                }
+            }
+            if self.variant != Variant::Dark && self.chess_board.is_checkmate(self.current_player())
+            {
+                return GameStatus::Checkmate(opponent(self.current_player()));
+            }
+            if self.variant != Variant::Dark && self.chess_board.is_stalemate(self.current_player())
+            {
+                return GameStatus::Draw(DrawReason::Stalemate);
+            }
+            if let Some(color) = self.clock.as_ref().and_then(|clock| clock.flagged()) {
+                return if self.chess_board.has_mating_material(opponent(color)) {
+                    GameStatus::OutOfTime(opponent(color))
+                } else {
+                    GameStatus::Draw(DrawReason::InsufficientMaterial)
+                };
+            }
+            GameStatus::Ongoing
+        }
+
+        pub fn visible_squares(&self, color: Color) -> Vec<(usize, usize)> {
+            self.chess_board.visible_squares(color)
+        }
+
+        pub fn get_redacted_board(&self, color: Color) -> RedactedBoard {
+            self.chess_board.redacted_board(color)
+        }
+
         pub fn move_piece(&mut self, input: String) -> Result<String, String> {
+            if self.status() != GameStatus::Ongoing {
+                return Err("Game is over!".to_string());
+            }
             if input.len() == 5 {
                 let mut input = input.split_whitespace();
                 let move_from = to_coords(input.next().unwrap().to_string())?;
@@ -76,13 +497,24 @@ pub mod chess_game {
                 } else {
                     return Err(format!("There is no piece at {:?}", move_from));
                 }
+                let san = self.chess_board.san(move_from, move_to);
+                let (captured, kind) = self.move_details(move_from, move_to);
                 let mut result = self.chess_board.move_piece(move_from, move_to);
                 if result.is_ok() {
-                    let mov = result.clone().unwrap();
-                    let mov = mov.trim();
-                    let mut history = format!("{}. ", self.turn.1);
-                    history.push_str(mov);
-                    self.history.push(history);
+                    let mover = self.current_player();
+                    let clock = self.clock.as_mut().map(|clock| {
+                        clock.press();
+                        clock.remaining(mover)
+                    });
+                    let played = PlayedMove {
+                        from: move_from,
+                        to: move_to,
+                        promotion: None,
+                        san: san? + self.check_marker(opponent(mover)),
+                        clock,
+                    };
+                    self.current = self.record.add_move(self.current, played)?;
+                    self.draw_offer = None;
                     self.turn = (
                         match self.current_player() {
                             Color::Black => Color::White,
@@ -90,12 +522,38 @@ pub mod chess_game {
                         },
                         1 + self.turn.1,
                     );
+                    self.observers.emit(GameEvent::MoveMade {
+                        color: mover,
+                        from: move_from,
+                        to: move_to,
+                        captured,
+                        kind,
+                    });
+                    // with a promotion to come the position is announced after it
+                    if !matches!(&result, Ok(message) if message.ends_with("Promotion")) {
+                        self.announce_position();
+                    }
+                    if self.variant == Variant::Dark {
+                        if !self.chess_board.has_king(self.current_player()) {
+                            return Ok("Game is over! The king was captured!".to_string());
+                        }
+                        return result;
+                    }
                     if self.chess_board.is_checked(self.current_player()) {
                         result = Ok(result.unwrap() + " Check!");
                     }
                     if self.chess_board.is_checkmate(self.current_player()) {
+                        if let Some(clock) = self.clock.as_mut() {
+                            clock.stop();
+                        }
                         return Ok("Game is over! It's a checkmate!".to_string());
                     }
+                    if self.chess_board.is_stalemate(self.current_player()) {
+                        if let Some(clock) = self.clock.as_mut() {
+                            clock.stop();
+                        }
+                        return Ok("Game is over! It's a stalemate!".to_string());
+                    }
                 }
                 result
             } else {
@@ -121,7 +579,27 @@ pub mod chess_game {
                 if piece_type == PieceType::Pawn {
                     return Err("Must provide promotion input as e.g:a8Q".to_string());
                 }
-                self.chess_board.promote(position, piece_type)
+                let result = self.chess_board.promote(position, piece_type.clone())?;
+                let marker = self.check_marker(self.current_player());
+                if let Some(last) = self
+                    .record
+                    .node_mut(self.current)
+                    .and_then(|node| node.played.as_mut())
+                {
+                    if last.to == position {
+                        let san = last.san.trim_end_matches(['+', '#']);
+                        last.san = format!("{}={}{}", san, piece_type, marker);
+                        last.promotion = Some(piece_type.clone());
+                        // playing a promotion again follows the line it began before
+                        self.current = self.record.merge_into_sibling(self.current);
+                    }
+                }
+                self.observers.emit(GameEvent::Promotion {
+                    square: position,
+                    piece_type,
+                });
+                self.announce_position();
+                Ok(result)
             } else {
                 Err("Must provide promotion input as e.g:a8Q".to_string())
             }
@@ -129,28 +607,519 @@ pub mod chess_game {
         pub fn print_board(&self) {
             console_display::print_board(self.chess_board.ref_board());
         }
+
+        /// Game record in PGN, with `%clk` comments when a clock is used
+        pub fn to_pgn(&self) -> String {
+            let result = match (self.status(), self.tag("Result")) {
+                (GameStatus::Ongoing, Some(result)) => result,
+                (status, _) => status.result(),
+            };
+            let mut tags: Vec<(&str, String)> = [
+                ("Event", "?"),
+                ("Site", "?"),
+                ("Date", "????.??.??"),
+                ("Round", "?"),
+                ("White", "?"),
+                ("Black", "?"),
+            ]
+            .iter()
+            .map(|&(name, default)| (name, self.tag(name).unwrap_or(default).to_string()))
+            .collect();
+            tags.push(("Result", result.to_string()));
+            let start_fen = self.start_fen();
+            if start_fen != STARTING_FEN {
+                tags.push(("SetUp", "1".to_string()));
+                tags.push(("FEN", start_fen));
+            }
+            if let (Some(opening), None) = (self.opening(), self.tag("ECO")) {
+                tags.push(("ECO", opening.eco.to_string()));
+                tags.push(("Opening", opening.name.to_string()));
+            }
+            if let Some(clock) = &self.clock {
+                tags.push(("TimeControl", clock.control().to_string()));
+                if let Some((color, time)) = self.time_odds {
+                    let base = clock.control().stages.first().map_or(time, |s| s.time);
+                    for &side in [Color::White, Color::Black].iter() {
+                        let start = if side == color { base + time } else { base };
+                        let name = if side == Color::White {
+                            "WhiteClock"
+                        } else {
+                            "BlackClock"
+                        };
+                        tags.push((name, format_clock(start)));
+                    }
+                }
+            }
+            for (name, value) in &self.tags {
+                if !tags.iter().any(|(written, _)| written == name) {
+                    tags.push((name, value.clone()));
+                }
+            }
+            let first_ply = if self.start.1 .0 == Color::White {
+                0
+            } else {
+                1
+            };
+            pgn::write_pgn(&tags, &self.record, first_ply, result)
+        }
+
+        /// Starts a game from a position in FEN
+        pub fn from_fen(fen: &str) -> Result<ChessGame, String> {
+            let position = ChessBoard::from_fen(fen)?;
+            let ply = (position.fullmove - 1)
+                .checked_mul(2)
+                .and_then(|ply| ply.checked_add(1 + (position.turn == Color::Black) as usize))
+                .ok_or_else(|| format!("Move number {} is too large", position.fullmove))?;
+            Ok(new_game(
+                position.board,
+                (position.turn, ply),
+                position.halfmove_clock,
+            ))
+        }
+
+        /// Starts a game from a position made with `BoardSetup`, after validating it
+        pub fn from_setup(setup: &BoardSetup) -> Result<ChessGame, String> {
+            let (board, turn) = setup.build()?;
+            let ply = if turn == Color::White { 1 } else { 2 };
+            Ok(new_game(board, (turn, ply), 0))
+        }
+
+        pub fn to_fen(&self) -> String {
+            self.chess_board
+                .to_fen(self.turn.0, self.halfmove_clock(), self.turn.1.div_ceil(2))
+        }
+
+        fn start_fen(&self) -> String {
+            let (board, (color, ply)) = &self.start;
+            board.to_fen(*color, self.start_halfmove_clock, ply.div_ceil(2))
+        }
+
+        /// Half moves since the last capture or pawn move
+        pub fn halfmove_clock(&self) -> usize {
+            self.history()
+                .iter()
+                .fold(self.start_halfmove_clock, |count, played| {
+                    let pawn_move = played.san.starts_with(|c: char| ('a'..='h').contains(&c));
+                    if pawn_move || played.san.contains('x') {
+                        0
+                    } else {
+                        count + 1
+                    }
+                })
+        }
+
+        /// Last named opening the game passed through on the way to the current position
+        pub fn opening(&self) -> Option<&'static eco::Opening> {
+            let (mut board, (mut color, _)) = (self.start.0.clone(), self.start.1);
+            let mut opening = eco::classify(&board, color);
+            for played in self.history() {
+                let mov = Move {
+                    from: played.from,
+                    to: played.to,
+                    promotion: played.promotion,
+                };
+                board.make_move(&mov).ok()?;
+                color = match color {
+                    Color::White => Color::Black,
+                    Color::Black => Color::White,
+                };
+                opening = eco::classify(&board, color).or(opening);
+            }
+            opening
+        }
+
+        /// Reads the first game of a PGN text with its tags, variations and comments
+        pub fn from_pgn(text: &str) -> Result<ChessGame, String> {
+            let (tags, tokens) = pgn::read_pgn(text)?;
+            let mut game = match tags.iter().find(|(name, _)| name == "FEN") {
+                Some((_, fen)) => ChessGame::from_fen(fen)?,
+                None => init_standard_chess(),
+            };
+            let mut variations: Vec<NodeId> = Vec::new();
+            for token in tokens {
+                match token {
+                    pgn::PgnToken::Move(san) => {
+                        game.play(&san)
+                            .map_err(|error| format!("Can't play {}: {}", san, error))?;
+                    }
+                    pgn::PgnToken::Nag(nag) => {
+                        game.record.node_mut(game.current).unwrap().nags.push(nag);
+                    }
+                    pgn::PgnToken::Comment(text) => {
+                        let (comment, time) = pgn::split_clock(&text);
+                        let node = game.record.node_mut(game.current).unwrap();
+                        if let (Some(played), Some(_)) = (node.played.as_mut(), time) {
+                            played.clock = time;
+                        }
+                        node.comment = match (node.comment.take(), comment) {
+                            (Some(old), Some(new)) => Some(format!("{} {}", old, new)),
+                            (old, new) => new.or(old),
+                        };
+                    }
+                    pgn::PgnToken::VariationStart => {
+                        let parent = game
+                            .record
+                            .parent(game.current)
+                            .ok_or("Variation before the first move")?;
+                        variations.push(game.current);
+                        game.go_to(parent)?;
+                    }
+                    pgn::PgnToken::VariationEnd => {
+                        let node = variations.pop().ok_or("Unmatched ) in movetext")?;
+                        game.go_to(node)?;
+                    }
+                    pgn::PgnToken::Result(_) => (),
+                }
+            }
+            if !variations.is_empty() {
+                return Err("Unterminated variation in movetext".to_string());
+            }
+            game.tags = tags;
+            Ok(game)
+        }
+
+        pub fn tag(&self, name: &str) -> Option<&str> {
+            self.tags
+                .iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str())
+        }
+
+        /// Sets a PGN tag pair like `White` or `Event`
+        pub fn set_tag(&mut self, name: &str, value: &str) {
+            match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+                Some(tag) => tag.1 = value.to_string(),
+                None => self.tags.push((name.to_string(), value.to_string())),
+            }
+        }
+
+        /// Reads a move for the current player in coordinates (`e2e4`, `e2 e4`, `e7e8q`)
+        /// or in SAN (`Nf3`, `exd5`, `O-O`, `e8=Q+`)
+        pub fn parse_move(&self, input: &str) -> Result<Move, String> {
+            let text: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+            let text = text.trim_end_matches(['+', '#', '!', '?']);
+            let moves = self.chess_board.legal_moves(self.current_player());
+            let coordinates = text.replace('-', "");
+            if coordinates.len() >= 4 && coordinates.is_ascii() {
+                if let (Ok(from), Ok(to)) = (
+                    to_coords(coordinates[..2].to_string()),
+                    to_coords(coordinates[2..4].to_string()),
+                ) {
+                    let promotion = match coordinates[4..].trim_start_matches('=') {
+                        "" => None,
+                        letter => Some(promotion_piece(letter)?),
+                    };
+                    let candidates: Vec<Move> = moves
+                        .into_iter()
+                        .filter(|m| m.from == from && m.to == to)
+                        .collect();
+                    return match candidates.iter().find(|m| m.promotion == promotion) {
+                        Some(mov) => Ok(mov.clone()),
+                        None if promotion.is_none() && !candidates.is_empty() => {
+                            Err("Add the piece to promote to, e.g. e7e8q".to_string())
+                        }
+                        None => Err(format!("{} is not a legal move", input.trim())),
+                    };
+                }
+            }
+            let wanted = normalize_san(text);
+            let mut matching = moves.into_iter().filter(|m| {
+                let king = matches!(self.chess_board.ref_piece(m.from),
+                    Some(piece) if piece.piece_type == PieceType::King);
+                (king || wanted.contains(&to_notation(m.to).unwrap()))
+                    && self.chess_board.san(m.from, m.to).is_ok_and(|san| {
+                        let promotion = m.promotion.as_ref().map(|p| p.to_string());
+                        normalize_san(&(san + &promotion.unwrap_or_default())) == wanted
+                    })
+            });
+            match (matching.next(), matching.next()) {
+                (Some(mov), None) => Ok(mov),
+                (Some(_), Some(_)) => Err(format!("{} is ambiguous", input.trim())),
+                (None, _) => Err(format!("{} is not a legal move", input.trim())),
+            }
+        }
+
+        /// Plays a move given in coordinates or SAN, see `parse_move`
+        pub fn play(&mut self, input: &str) -> Result<String, String> {
+            if self.status() != GameStatus::Ongoing {
+                return Err("Game is over!".to_string());
+            }
+            let mov = self.parse_move(input)?;
+            let result = self.move_piece(format!(
+                "{} {}",
+                to_notation(mov.from)?,
+                to_notation(mov.to)?
+            ))?;
+            match mov.promotion {
+                Some(piece_type) => {
+                    self.promotion(format!("{}{}", to_notation(mov.to)?, piece_type))
+                }
+                None => Ok(result),
+            }
+        }
+
+        /// Takes back the last move, removing it from the record
+        pub fn undo(&mut self) -> Result<(), String> {
+            if self.outcome.is_some() {
+                return Err("Game is over!".to_string());
+            }
+            let node = self.current;
+            let parent = self
+                .record
+                .parent(node)
+                .ok_or("There is no move to take back")?;
+            let played = self.record.node(node).and_then(|node| node.played.clone());
+            self.replay(parent)?;
+            self.record.delete(node)?;
+            if let Some(played) = played {
+                self.observers.emit(GameEvent::Undo(played));
+            }
+            Ok(())
+        }
+
+        pub fn resign(&mut self, color: Color) -> Result<(), String> {
+            if self.status() != GameStatus::Ongoing {
+                return Err("Game is over!".to_string());
+            }
+            self.outcome = Some(GameStatus::Resigned(opponent(color)));
+            self.stop_clock();
+            self.observers
+                .emit(GameEvent::GameEnded(GameStatus::Resigned(opponent(color))));
+            Ok(())
+        }
+
+        /// Offers a draw to the opponent of `color`, the offer stands until the next move
+        pub fn offer_draw(&mut self, color: Color) -> Result<(), String> {
+            if self.status() != GameStatus::Ongoing {
+                return Err("Game is over!".to_string());
+            }
+            self.draw_offer = Some(color);
+            Ok(())
+        }
+
+        pub fn draw_offer(&self) -> Option<Color> {
+            self.draw_offer
+        }
+
+        /// Accepts the draw offered to `color`
+        pub fn accept_draw(&mut self, color: Color) -> Result<(), String> {
+            if self.draw_offer != Some(opponent(color)) {
+                return Err("There is no draw offer to accept".to_string());
+            }
+            self.outcome = Some(GameStatus::Draw(DrawReason::Agreement));
+            self.draw_offer = None;
+            self.stop_clock();
+            self.observers.emit(GameEvent::GameEnded(GameStatus::Draw(
+                DrawReason::Agreement,
+            )));
+            Ok(())
+        }
+
+        fn stop_clock(&mut self) {
+            if let Some(clock) = self.clock.as_mut() {
+                clock.stop();
+            }
+        }
+
+        /// Marker for a move that leaves `color` in check or checkmate
+        fn check_marker(&self, color: Color) -> &'static str {
+            if self.variant == Variant::Dark {
+                ""
+            } else if self.chess_board.is_checkmate(color) {
+                "#"
+            } else if self.chess_board.is_checked(color) {
+                "+"
+            } else {
+                ""
+            }
+        }
+
+        pub fn render(&self, options: &console_display::RenderOptions) -> String {
+            console_display::render_board(self.chess_board.ref_board(), options)
+        }
+
+        /// Spaces of the last move and of a king in check, for highlighting
+        pub fn highlights(&self) -> Vec<(usize, usize)> {
+            let mut highlights = Vec::new();
+            if let Some(played) = self
+                .record
+                .node(self.current)
+                .and_then(|node| node.played.as_ref())
+            {
+                highlights.push(played.from);
+                highlights.push(played.to);
+            }
+            let color = self.current_player();
+            if self.variant != Variant::Dark && self.chess_board.is_checked(color) {
+                highlights.extend(self.chess_board.king_position(color));
+            }
+            highlights
+        }
+
+        pub fn print_redacted_board(&self, color: Color) {
+            console_display::print_redacted_board(&self.get_redacted_board(color));
+        }
     }
     pub fn init_standard_chess() -> ChessGame {
         let mut board = init_board();
         board.standard_pieces(Color::White);
         board.standard_pieces(Color::Black);
+        new_game(board, (Color::White, 1), 0)
+    }
+
+    fn new_game(board: ChessBoard, turn: (Color, usize), halfmove_clock: usize) -> ChessGame {
         ChessGame {
+            start: (board.clone(), turn),
+            start_halfmove_clock: halfmove_clock,
             chess_board: board,
-            history: Vec::new(),
-            turn: (Color::White, 1),
+            record: GameTree::new(),
+            current: ROOT,
+            turn,
+            variant: Variant::Standard,
+            tags: Vec::new(),
+            outcome: None,
+            draw_offer: None,
+            clock: None,
+            observers: Observers::default(),
+            time_odds: None,
+        }
+    }
+
+    /// Standard game where `giver` plays with the given odds. Missing pieces are kept
+    /// in the PGN through the SetUp and FEN tags, time odds apply once a clock is set.
+    pub fn init_handicap_chess(giver: Color, handicaps: &[Handicap]) -> ChessGame {
+        let mut setup = BoardSetup::standard();
+        let mut rights = setup.castling_rights();
+        let home = if giver == Color::White { 0 } else { 7 };
+        let mut time_odds = None;
+        for handicap in handicaps {
+            match handicap {
+                Handicap::PawnAndMove => {
+                    let pawn_rank = if giver == Color::White { 1 } else { 6 };
+                    setup.remove((5, pawn_rank));
+                    setup.side_to_move(opponent(giver));
+                }
+                Handicap::Knight => {
+                    setup.remove((1, home));
+                }
+                Handicap::Rook => {
+                    setup.remove((0, home));
+                    match giver {
+                        Color::White => rights.white_queen_side = false,
+                        Color::Black => rights.black_queen_side = false,
+                    }
+                }
+                Handicap::Queen => {
+                    setup.remove((3, home));
+                }
+                Handicap::Time(time) => time_odds = Some((opponent(giver), *time)),
+            }
+        }
+        setup.castling(rights);
+        let mut game = ChessGame::from_setup(&setup).unwrap();
+        game.time_odds = time_odds;
+        game
+    }
+
+    pub fn init_dark_chess() -> ChessGame {
+        let mut game = init_standard_chess();
+        game.chess_board.set_king_capture(true);
+        game.start.0.set_king_capture(true);
+        game.variant = Variant::Dark;
+        game
+    }
+
+    fn promotion_piece(letter: &str) -> Result<PieceType, String> {
+        match letter.to_ascii_uppercase().as_str() {
+            "Q" => Ok(PieceType::Queen),
+            "R" => Ok(PieceType::Rook),
+            "B" => Ok(PieceType::Bishop),
+            "N" => Ok(PieceType::Knight),
+            _ => Err(format!("Can't promote to {:?}", letter)),
+        }
+    }
+
+    /// SAN without the characters people often leave out or write differently
+    fn normalize_san(san: &str) -> String {
+        san.chars()
+            .filter(|c| !"x=-+#:".contains(*c))
+            .map(|c| if c == '0' { 'O' } else { c })
+            .collect()
+    }
+
+    fn opponent(color: Color) -> Color {
+        match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serialization {
+        use super::*;
+        use std::convert::TryFrom;
+
+        /// Unchecked fields of a `ChessGame`, the record has to lead to the position
+        #[derive(serde::Deserialize)]
+        pub struct ChessGameData {
+            chess_board: ChessBoard,
+            turn: (Color, usize),
+            variant: Variant,
+            start: (ChessBoard, (Color, usize)),
+            start_halfmove_clock: usize,
+            record: GameTree,
+            current: NodeId,
+            tags: Vec<(String, String)>,
+            outcome: Option<GameStatus>,
+            draw_offer: Option<Color>,
+            time_odds: Option<(Color, Duration)>,
+        }
+
+        impl TryFrom<ChessGameData> for ChessGame {
+            type Error = String;
+
+            fn try_from(data: ChessGameData) -> Result<Self, Self::Error> {
+                let mut game = ChessGame {
+                    chess_board: data.chess_board,
+                    turn: data.turn,
+                    variant: data.variant,
+                    start: data.start,
+                    start_halfmove_clock: data.start_halfmove_clock,
+                    record: data.record,
+                    current: data.current,
+                    tags: data.tags,
+                    outcome: data.outcome,
+                    draw_offer: data.draw_offer,
+                    clock: None,
+                    observers: Observers::default(),
+                    time_odds: data.time_odds,
+                };
+                let fen = game.to_fen();
+                game.replay(game.current)
+                    .map_err(|error| format!("Invalid game record: {}", error))?;
+                if game.to_fen() != fen {
+                    return Err("The position doesn't follow from the game record".to_string());
+                }
+                game.draw_offer = data.draw_offer;
+                Ok(game)
+            }
         }
     }
 }
 
 pub fn to_coords(input: String) -> Result<(usize, usize), String> {
-    if input.len() == 2 {
+    if input.len() == 2 && input.is_ascii() {
         let mut input = input.chars();
         let mut pos_x = input.next().unwrap() as isize - 96;
-        let mut pos_y: isize = input.next().unwrap().to_string().parse().unwrap();
+        let mut pos_y: isize = input
+            .next()
+            .unwrap()
+            .to_string()
+            .parse()
+            .map_err(|_| String::from("invalid notation, cannot find coords on board"))?;
         pos_x -= 1;
         pos_y -= 1;
-        if pos_x < 0 || pos_x > 7 || pos_y < 0 || pos_y > 7 {
-            println!("{}{}", pos_x, pos_y);
+        if !(0..=7).contains(&pos_x) || !(0..=7).contains(&pos_y) {
             return Err(String::from("tried to access non-existent boardspace"));
         }
         Ok((pos_x as usize, pos_y as usize))
@@ -0,0 +1,19 @@
+//! Play chess in the terminal, against another person or the built-in AI
+use std::{env, process};
+
+/// The options of the command line and the tasks they start, one module for each
+mod cli;
+
+fn main() {
+    let mut session = match cli::Session::from_args(env::args().skip(1).collect()) {
+        Ok(session) => session,
+        Err(error) => {
+            eprintln!("{}\n{}", error, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(error) = session.start() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
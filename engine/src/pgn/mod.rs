@@ -0,0 +1,286 @@
+use crate::clock::format_clock;
+use crate::game_tree::*;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests;
+
+/// Writes a game with the given tag pairs, variations in parentheses and comments in braces.
+/// `first_ply` is 1 when the game starts with a Black move. The movetext wraps at 80 columns.
+pub fn write_pgn(
+    tags: &[(&str, String)],
+    tree: &GameTree,
+    first_ply: usize,
+    result: &str,
+) -> String {
+    let mut pgn = String::new();
+    for (name, value) in tags {
+        pgn.push_str(&format!(
+            "[{} \"{}\"]\n",
+            name,
+            value.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    pgn.push('\n');
+    let mut tokens: Vec<String> = Vec::new();
+    push_line(tree, ROOT, first_ply, true, &mut tokens);
+    tokens.push(result.to_string());
+    pgn.push_str(&wrap(&tokens, 80));
+    pgn.push('\n');
+    pgn
+}
+
+fn push_line(
+    tree: &GameTree,
+    parent: NodeId,
+    ply: usize,
+    numbered: bool,
+    tokens: &mut Vec<String>,
+) {
+    let children = tree.children(parent);
+    let main = match children.first() {
+        Some(&main) => main,
+        None => return,
+    };
+    let commented = push_move(tree, main, ply, numbered, tokens);
+    for &variation in &children[1..] {
+        tokens.push("(".to_string());
+        push_move(tree, variation, ply, true, tokens);
+        push_line(tree, variation, ply + 1, false, tokens);
+        tokens.push(")".to_string());
+    }
+    push_line(tree, main, ply + 1, commented || children.len() > 1, tokens);
+}
+
+/// Returns whether a comment was written, after which a Black move needs its number again
+fn push_move(
+    tree: &GameTree,
+    id: NodeId,
+    ply: usize,
+    numbered: bool,
+    tokens: &mut Vec<String>,
+) -> bool {
+    let node = tree.node(id).unwrap();
+    if ply % 2 == 1 {
+        if numbered {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+    } else {
+        tokens.push(format!("{}.", ply / 2 + 1));
+    }
+    if let Some(played) = &node.played {
+        tokens.push(played.san.clone());
+    }
+    for nag in &node.nags {
+        tokens.push(format!("${}", nag));
+    }
+    let mut comment: Vec<String> = node.comment.iter().cloned().collect();
+    if let Some(time) = node.played.as_ref().and_then(|played| played.clock) {
+        comment.push(format!("[%clk {}]", format_clock(time)));
+    }
+    if !comment.is_empty() {
+        tokens.push(format!("{{{}}}", comment.join(" ")));
+    }
+    !comment.is_empty()
+}
+
+fn wrap(tokens: &[String], width: usize) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > width {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 && token != ")" && !text.ends_with('(') {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(token);
+        line_length += token.len();
+    }
+    text
+}
+
+/// Tag pairs in the order they appear
+pub type TagPairs = Vec<(String, String)>;
+
+/// Piece of movetext, move numbers are dropped while reading
+#[derive(PartialEq, Clone, Debug)]
+pub enum PgnToken {
+    /// Move in SAN with any `!`/`?` suffix split off as a `Nag`
+    Move(String),
+    Nag(u8),
+    Comment(String),
+    VariationStart,
+    VariationEnd,
+    Result(String),
+}
+
+/// Reads the tag pairs and movetext of the first game in `text`
+pub fn read_pgn(text: &str) -> Result<(TagPairs, Vec<PgnToken>), String> {
+    let mut tags: TagPairs = Vec::new();
+    let mut tokens: Vec<PgnToken> = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+    while let Some(&c) = chars.peek() {
+        if c == '%' && line_start {
+            while chars.next_if(|&c| c != '\n').is_some() {}
+            continue;
+        }
+        line_start = c == '\n';
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        match c {
+            '[' => {
+                if !tokens.is_empty() {
+                    break;
+                }
+                chars.next();
+                let mut tag = String::new();
+                let mut quoted = false;
+                loop {
+                    match chars.next() {
+                        Some('\\') if quoted => tag.extend(chars.next()),
+                        Some('"') => {
+                            quoted = !quoted;
+                            tag.push('"');
+                        }
+                        Some(']') if !quoted => break,
+                        Some(c) => tag.push(c),
+                        None => return Err("Unterminated tag pair".to_string()),
+                    }
+                }
+                let (name, value) = tag
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| format!("Invalid tag pair [{}]", tag))?;
+                let value = value.trim();
+                if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+                    return Err(format!("Invalid tag pair [{}]", tag));
+                }
+                tags.push((name.to_string(), value[1..value.len() - 1].to_string()));
+            }
+            '{' => {
+                chars.next();
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+            }
+            ';' => {
+                let comment: String = chars.by_ref().skip(1).take_while(|&c| c != '\n').collect();
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+                line_start = true;
+            }
+            '(' => {
+                chars.next();
+                tokens.push(PgnToken::VariationStart);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(PgnToken::VariationEnd);
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !"(){};[".contains(c))
+                {
+                    word.push(c);
+                }
+                if let Some(nag) = word.strip_prefix('$') {
+                    let nag = nag
+                        .parse()
+                        .map_err(|_| format!("Invalid annotation {:?}", word))?;
+                    tokens.push(PgnToken::Nag(nag));
+                    continue;
+                }
+                if ["1-0", "0-1", "1/2-1/2", "*"].contains(&word.as_str()) {
+                    tokens.push(PgnToken::Result(word));
+                    break;
+                }
+                if word.is_empty() {
+                    return Err(format!("Unexpected {:?} in movetext", c));
+                }
+                let san = match word.find(|c: char| !c.is_ascii_digit()) {
+                    Some(i) if word[i..].starts_with('.') => word[i..].trim_start_matches('.'),
+                    _ => &word[..],
+                };
+                let suffix_start = san.trim_end_matches(['!', '?']).len();
+                let (san, suffix) = san.split_at(suffix_start);
+                if !san.is_empty() {
+                    tokens.push(PgnToken::Move(san.to_string()));
+                }
+                if !suffix.is_empty() {
+                    let nag = nag_from_symbol(suffix)
+                        .ok_or_else(|| format!("Invalid annotation {:?}", suffix))?;
+                    tokens.push(PgnToken::Nag(nag));
+                }
+            }
+        }
+    }
+    Ok((tags, tokens))
+}
+
+/// Splits a file with several games into the text of each game
+pub fn split_games(text: &str) -> Vec<&str> {
+    let mut games: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut movetext = false;
+    let mut in_comment = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if !in_comment && trimmed.starts_with('[') && movetext {
+            games.push(&text[start..offset]);
+            start = offset;
+            movetext = false;
+        } else if !in_comment && !trimmed.is_empty() && !trimmed.starts_with('[') {
+            movetext = true;
+        }
+        for c in line.chars() {
+            match c {
+                '{' => in_comment = true,
+                '}' => in_comment = false,
+                _ => (),
+            }
+        }
+        offset += line.len();
+    }
+    if !text[start..].trim().is_empty() {
+        games.push(&text[start..]);
+    }
+    games
+}
+
+/// Splits a `[%clk h:mm:ss]` command off a comment, returning the remaining text if any
+pub fn split_clock(comment: &str) -> (Option<String>, Option<Duration>) {
+    let start = match comment.find("[%clk") {
+        Some(start) => start,
+        None => return (Some(comment.to_string()), None),
+    };
+    let end = match comment[start..].find(']') {
+        Some(end) => start + end,
+        None => return (Some(comment.to_string()), None),
+    };
+    let mut seconds = 0.0;
+    for part in comment[start + 5..end].trim().split(':') {
+        match part.parse::<f64>() {
+            Ok(value) => seconds = seconds * 60.0 + value,
+            Err(_) => return (Some(comment.to_string()), None),
+        }
+    }
+    // NaN, infinite and negative times or ones too large for a Duration
+    let time = match Duration::try_from_secs_f64(seconds) {
+        Ok(time) => time,
+        Err(_) => return (Some(comment.to_string()), None),
+    };
+    let rest = format!("{} {}", comment[..start].trim(), comment[end + 1..].trim());
+    let rest = rest.trim();
+    (
+        if rest.is_empty() {
+            None
+        } else {
+            Some(rest.to_string())
+        },
+        Some(time),
+    )
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn reads_movetext() {
+    let text = "[Event \"Casual \\\"game\\\"\"]\n[White \"Anna\"]\n\n\
+                1. e4 {Best by test} e5 2. Nf3!? (2. f4 exf4) 2... Nc6 $1 ; line comment\n\
+                3. Bb5 a6 1-0 [Event \"Next\"]";
+    let (tags, tokens) = read_pgn(text).unwrap();
+    assert_eq!(
+        tags,
+        vec![
+            ("Event".to_string(), "Casual \"game\"".to_string()),
+            ("White".to_string(), "Anna".to_string())
+        ]
+    );
+    let moves = |sans: &[&str]| -> Vec<PgnToken> {
+        sans.iter()
+            .map(|san| PgnToken::Move(san.to_string()))
+            .collect()
+    };
+    let mut expected = moves(&["e4"]);
+    expected.push(PgnToken::Comment("Best by test".to_string()));
+    expected.extend(moves(&["e5", "Nf3"]));
+    expected.push(PgnToken::Nag(5));
+    expected.push(PgnToken::VariationStart);
+    expected.extend(moves(&["f4", "exf4"]));
+    expected.push(PgnToken::VariationEnd);
+    expected.extend(moves(&["Nc6"]));
+    expected.push(PgnToken::Nag(1));
+    expected.push(PgnToken::Comment("line comment".to_string()));
+    expected.extend(moves(&["Bb5", "a6"]));
+    expected.push(PgnToken::Result("1-0".to_string()));
+    assert_eq!(tokens, expected);
+}
+
+#[test]
+fn splits_clock_comments() {
+    assert_eq!(
+        split_clock("[%clk 0:04:59]"),
+        (None, Some(Duration::from_secs(299)))
+    );
+    assert_eq!(
+        split_clock("Sharp [%clk 1:00:00.5]"),
+        (
+            Some("Sharp".to_string()),
+            Some(Duration::from_millis(3_600_500))
+        )
+    );
+    assert_eq!(
+        split_clock("No clock"),
+        (Some("No clock".to_string()), None)
+    );
+    for comment in [
+        "[%clk 0:00:NaN]",
+        "[%clk 1e400]",
+        "[%clk -5]",
+        "[%clk 99999999999999999999:00:00]",
+    ]
+    .iter()
+    {
+        assert_eq!(split_clock(comment), (Some(comment.to_string()), None));
+    }
+}
+
+#[test]
+fn splits_games() {
+    let text = "[Event \"A\"]\n[White \"X\"]\n\n1. e4 e5 {\n[not a tag]} 1-0\n\n[Event \"B\"]\n\n1. d4 *\n";
+    let games = split_games(text);
+    assert_eq!(games.len(), 2);
+    assert!(games[0].ends_with("1-0\n\n"));
+    assert_eq!(games[1], "[Event \"B\"]\n\n1. d4 *\n");
+    assert!(split_games("\n\n").is_empty());
+}
@@ -0,0 +1,215 @@
+use super::Color;
+use std::sync::Arc;
+
+/// Movement of a piece, described with Betza notation
+///
+/// Supported atoms are the leapers `W F D N A H C Z G` (`L` and `J` as aliases for `C` and `Z`)
+/// and the compounds `K Q R B`. An atom written twice (`NN`) rides in a line,
+/// and a trailing number limits the range of a rider (`R4`).
+///
+/// Each atom can be prefixed with modifiers:
+/// - `m` move only, `c` capture only (both or none means it can do either)
+/// - `i` only allowed while the piece has not moved
+/// - `f b l r` forward, backward, left, right and `v s` vertical, sideways.
+///   Letters on different axes narrow each other down (`flF`, `fsN`), and a doubled
+///   letter means the narrow variant (`ffN` is `fvN`). When a combination would leave
+///   nothing, as `fsW` does, its letters are joined instead.
+///
+/// Directions are seen from White's side of the board, Black pieces are mirrored.
+/// The rules are shared, so cloning a piece never allocates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Movement {
+    rules: Arc<[MoveRule]>,
+}
+
+/// One atom of a Betza string, expanded into concrete steps
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveRule {
+    pub steps: Vec<(isize, isize)>,
+    pub range: Option<usize>,
+    pub moves: bool,
+    pub captures: bool,
+    pub initial_only: bool,
+}
+
+impl Movement {
+    pub fn parse(notation: &str) -> Result<Movement, String> {
+        let mut rules: Vec<MoveRule> = Vec::new();
+        let mut chars = notation.chars().peekable();
+        while chars.peek().is_some() {
+            let mut modifiers = String::new();
+            while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_lowercase()) {
+                modifiers.push(c);
+                chars.next();
+            }
+            let atom = chars
+                .next()
+                .ok_or_else(|| format!("Betza string {:?} ends with modifiers", notation))?;
+            let mut range = Some(1);
+            if chars.peek() == Some(&atom) {
+                chars.next();
+                range = None;
+            }
+            let mut digits = String::new();
+            while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_digit()) {
+                digits.push(c);
+                chars.next();
+            }
+            let atoms: Vec<((isize, isize), bool)> = match atom {
+                'W' => vec![((0, 1), false)],
+                'F' => vec![((1, 1), false)],
+                'D' => vec![((0, 2), false)],
+                'N' => vec![((1, 2), false)],
+                'A' => vec![((2, 2), false)],
+                'H' => vec![((0, 3), false)],
+                'C' | 'L' => vec![((1, 3), false)],
+                'Z' | 'J' => vec![((2, 3), false)],
+                'G' => vec![((3, 3), false)],
+                'K' => vec![((0, 1), false), ((1, 1), false)],
+                'R' => vec![((0, 1), true)],
+                'B' => vec![((1, 1), true)],
+                'Q' => vec![((0, 1), true), ((1, 1), true)],
+                _ => return Err(format!("Unknown Betza atom {:?} in {:?}", atom, notation)),
+            };
+            if range.is_none() && atoms.iter().any(|(_, rides)| *rides) {
+                return Err(format!("Betza atom {:?} already rides", atom));
+            }
+            if !digits.is_empty() {
+                let limit: usize = digits.parse().map_err(|_| "Invalid Betza range")?;
+                if limit == 0 {
+                    return Err(format!("Betza range for {:?} must be positive", atom));
+                }
+                range = Some(limit);
+            }
+            let (moves, captures, initial_only, directions) = parse_modifiers(&modifiers)?;
+            for (vector, rides) in atoms {
+                let steps = filter_steps(vector, &directions);
+                if steps.is_empty() {
+                    return Err(format!("{}{} has no moves left", modifiers, atom));
+                }
+                rules.push(MoveRule {
+                    steps,
+                    range: if rides && digits.is_empty() {
+                        None
+                    } else {
+                        range
+                    },
+                    moves,
+                    captures,
+                    initial_only,
+                });
+            }
+        }
+        if rules.is_empty() {
+            return Err("Empty Betza string".to_string());
+        }
+        Ok(Movement {
+            rules: rules.into(),
+        })
+    }
+
+    pub fn rules(&self) -> &[MoveRule] {
+        &self.rules
+    }
+}
+
+impl MoveRule {
+    /// Steps as seen from the board for a piece of the given color
+    pub fn directions(&self, color: Color) -> Vec<(isize, isize)> {
+        self.steps_for(color).collect()
+    }
+
+    /// Like `directions`, without collecting them
+    pub fn steps_for(&self, color: Color) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.steps.iter().map(move |&(x, y)| {
+            if color == Color::White {
+                (x, y)
+            } else {
+                (x, -y)
+            }
+        })
+    }
+}
+
+fn parse_modifiers(modifiers: &str) -> Result<(bool, bool, bool, Vec<String>), String> {
+    let mut moves = false;
+    let mut captures = false;
+    let mut initial_only = false;
+    let mut directions: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in modifiers.chars() {
+        match c {
+            'm' => moves = true,
+            'c' => captures = true,
+            'i' => initial_only = true,
+            'f' | 'b' | 'l' | 'r' | 'v' | 's' => {
+                let axis = |d: char| match d {
+                    'f' | 'b' => 0,
+                    'l' | 'r' => 1,
+                    _ => 2,
+                };
+                if current.ends_with(c) && axis(c) != 2 && !current.contains(['v', 's']) {
+                    current.push(if axis(c) == 0 { 'v' } else { 's' });
+                } else if current.chars().any(|d| axis(d) == axis(c)) {
+                    directions.push(std::mem::take(&mut current));
+                    current.push(c);
+                } else {
+                    current.push(c);
+                }
+            }
+            _ => return Err(format!("Unsupported Betza modifier {:?}", c)),
+        }
+    }
+    if !current.is_empty() {
+        directions.push(current);
+    }
+    if !moves && !captures {
+        moves = true;
+        captures = true;
+    }
+    Ok((moves, captures, initial_only, directions))
+}
+
+fn filter_steps(vector: (isize, isize), directions: &[String]) -> Vec<(isize, isize)> {
+    let (a, b) = vector;
+    let mut all_steps: Vec<(isize, isize)> = Vec::new();
+    for &(x, y) in [(a, b), (b, a)].iter() {
+        for &(sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)].iter() {
+            if !all_steps.contains(&(x * sx, y * sy)) {
+                all_steps.push((x * sx, y * sy));
+            }
+        }
+    }
+    if directions.is_empty() {
+        return all_steps;
+    }
+    let matches = |d: char, (x, y): (isize, isize)| match d {
+        'f' => y > 0,
+        'b' => y < 0,
+        'l' => x < 0,
+        'r' => x > 0,
+        'v' => y.abs() > x.abs(),
+        _ => x.abs() > y.abs(),
+    };
+    let mut steps: Vec<(isize, isize)> = Vec::new();
+    for clause in directions {
+        let mut found: Vec<(isize, isize)> = all_steps
+            .iter()
+            .copied()
+            .filter(|&step| clause.chars().all(|d| matches(d, step)))
+            .collect();
+        if found.is_empty() {
+            found = all_steps
+                .iter()
+                .copied()
+                .filter(|&step| clause.chars().any(|d| matches(d, step)))
+                .collect();
+        }
+        for step in found {
+            if !steps.contains(&step) {
+                steps.push(step);
+            }
+        }
+    }
+    steps
+}
@@ -1,11 +1,20 @@
 use std::fmt;
+use std::sync::OnceLock;
 
-#[derive(PartialEq, Copy, Clone)]
+pub mod betza;
+pub use betza::Movement;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
 }
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -13,15 +22,20 @@ pub enum PieceType {
     Bishop,
     King,
     Queen,
+    /// Fairy piece with its symbol and movement in Betza notation, e.g. `Custom('C', "WF".into())`
+    Custom(char, String),
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::PieceData"))]
 pub struct Piece {
     pub color: Color,
     pub piece_type: PieceType,
-    pub movement: ((isize, isize), Option<(isize, isize)>),
+    /// follows from `piece_type`, so it is left out when serializing
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub movement: Movement,
     pub has_moved: bool,
-    pub moves_continous: bool,
 }
 
 impl fmt::Display for PieceType {
@@ -33,6 +47,7 @@ impl fmt::Display for PieceType {
             PieceType::Bishop => 'B',
             PieceType::King => 'K',
             PieceType::Queen => 'Q',
+            PieceType::Custom(symbol, _) => *symbol,
         };
         write!(f, "{}", symbol)
     }
@@ -44,20 +59,50 @@ impl Piece {
             self.has_moved = true;
         }
     }
+
+    /// Material value in centipawns, fairy pieces are valued by how far they reach
+    pub fn value(&self) -> i32 {
+        match self.piece_type {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 320,
+            PieceType::Bishop => 330,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 0,
+            PieceType::Custom(_, _) => self
+                .movement
+                .rules()
+                .iter()
+                .map(|rule| {
+                    let reach = rule.range.map_or(4, |range| range.min(4));
+                    (rule.steps.len() * reach) as i32 * 10
+                })
+                .sum(),
+        }
+    }
+
+    /// Letter for the piece, upper case for White and lower case for Black
+    pub fn letter(&self) -> char {
+        let letter = match &self.piece_type {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::King => 'K',
+            PieceType::Queen => 'Q',
+            PieceType::Custom(symbol, _) => *symbol,
+        };
+        if self.color == Color::White {
+            letter.to_ascii_uppercase()
+        } else {
+            letter.to_ascii_lowercase()
+        }
+    }
 }
 
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let symbol: char = if self.color == Color::White {
-            match self.piece_type {
-                PieceType::Pawn => '\u{265F}',
-                PieceType::Rook => '\u{265C}',
-                PieceType::Knight => '\u{265E}',
-                PieceType::Bishop => '\u{265D}',
-                PieceType::King => '\u{265A}',
-                PieceType::Queen => '\u{265B}',
-            }
-        } else {
             match self.piece_type {
                 PieceType::Pawn => '\u{2659}',
                 PieceType::Rook => '\u{2656}',
@@ -65,32 +110,63 @@ impl fmt::Display for Piece {
                 PieceType::Bishop => '\u{2657}',
                 PieceType::King => '\u{2654}',
                 PieceType::Queen => '\u{2655}',
+                PieceType::Custom(symbol, _) => symbol.to_ascii_uppercase(),
+            }
+        } else {
+            match self.piece_type {
+                PieceType::Pawn => '\u{265F}',
+                PieceType::Rook => '\u{265C}',
+                PieceType::Knight => '\u{265E}',
+                PieceType::Bishop => '\u{265D}',
+                PieceType::King => '\u{265A}',
+                PieceType::Queen => '\u{265B}',
+                PieceType::Custom(symbol, _) => symbol.to_ascii_lowercase(),
             }
         };
         write!(f, "{}", symbol)
     }
 }
 
+/// Betza notation of the standard pieces in the order of `PieceType`
+const STANDARD_MOVEMENT: [&str; 6] = ["mfWcfF", "R", "N", "B", "K", "Q"];
+
+/// Panics if a custom piece has invalid Betza notation, see `custom_piece`
 pub fn piece_make(color: Color, piece_type: PieceType) -> Piece {
+    static STANDARD: OnceLock<Vec<Movement>> = OnceLock::new();
+    let index = match &piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Rook => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::King => 4,
+        PieceType::Queen => 5,
+        PieceType::Custom(_, notation) => {
+            return Piece {
+                color,
+                has_moved: false,
+                movement: Movement::parse(notation).expect("Invalid piece movement:"),
+                piece_type,
+            }
+        }
+    };
+    let standard = STANDARD.get_or_init(|| {
+        STANDARD_MOVEMENT
+            .iter()
+            .map(|notation| Movement::parse(notation).unwrap())
+            .collect()
+    });
     Piece {
         color,
         has_moved: false,
-        movement: match &piece_type {
-            PieceType::Pawn => ((0, 1), Some((1, 1))),
-            PieceType::Rook => ((0, 1), None),
-            PieceType::Knight => ((1, 2), Some((2, 1))),
-            PieceType::Bishop => ((1, 1), None),
-            PieceType::King => ((0, 1), Some((1, 1))),
-            PieceType::Queen => ((0, 1), Some((1, 1))),
-        },
-        moves_continous: match &piece_type {
-            PieceType::Pawn => false,
-            PieceType::Rook => true,
-            PieceType::Knight => false,
-            PieceType::Bishop => true,
-            PieceType::King => false,
-            PieceType::Queen => true,
-        },
+        movement: standard[index].clone(),
         piece_type,
     }
 }
+
+pub fn custom_piece(color: Color, symbol: char, notation: &str) -> Result<Piece, String> {
+    Movement::parse(notation)?;
+    Ok(piece_make(
+        color,
+        PieceType::Custom(symbol, notation.to_string()),
+    ))
+}
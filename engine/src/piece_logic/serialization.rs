@@ -0,0 +1,23 @@
+use super::*;
+use std::convert::TryFrom;
+
+/// Fields of a `Piece` without its movement, which is rebuilt from the piece type
+#[derive(serde::Deserialize)]
+pub struct PieceData {
+    color: Color,
+    piece_type: PieceType,
+    has_moved: bool,
+}
+
+impl TryFrom<PieceData> for Piece {
+    type Error = String;
+
+    fn try_from(data: PieceData) -> Result<Self, Self::Error> {
+        let mut piece = match data.piece_type {
+            PieceType::Custom(symbol, notation) => custom_piece(data.color, symbol, &notation)?,
+            piece_type => piece_make(data.color, piece_type),
+        };
+        piece.has_moved = data.has_moved;
+        Ok(piece)
+    }
+}
@@ -0,0 +1,298 @@
+use crate::board_logic::Move;
+use crate::chess_game::*;
+use crate::piece_logic::Color;
+use crate::search::*;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::mpsc::{Receiver, Sender};
+
+#[cfg(test)]
+mod tests;
+
+/// Why a player has no move to give
+#[derive(PartialEq, Clone, Debug)]
+pub enum PlayerError {
+    /// The player can't go on and loses, e.g. someone who left
+    Resign(String),
+    /// The game can't go on, e.g. when a peer declines our move and the boards differ
+    Abort(String),
+}
+
+impl From<String> for PlayerError {
+    fn from(error: String) -> Self {
+        PlayerError::Resign(error)
+    }
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerError::Resign(error) | PlayerError::Abort(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Someone or something that plays one side of a game
+pub trait Player: Send {
+    /// Move for the side to move in `game`
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError>;
+
+    /// The opponent played `mov`, `game` is the position after it
+    fn opponent_moved(&mut self, _game: &ChessGame, _mov: &PlayedMove) {}
+
+    fn game_ended(&mut self, _game: &ChessGame, _status: GameStatus) {}
+}
+
+/// A person at this computer, whose moves come from an interface through a channel.
+/// Input that isn't a legal move is answered on `rejected`, if given, and ignored.
+pub struct HumanPlayer {
+    input: Receiver<String>,
+    rejected: Option<Sender<String>>,
+}
+
+impl HumanPlayer {
+    pub fn new(input: Receiver<String>) -> Self {
+        HumanPlayer {
+            input,
+            rejected: None,
+        }
+    }
+
+    pub fn with_rejections(input: Receiver<String>, rejected: Sender<String>) -> Self {
+        HumanPlayer {
+            input,
+            rejected: Some(rejected),
+        }
+    }
+}
+
+impl Player for HumanPlayer {
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError> {
+        loop {
+            let input = self
+                .input
+                .recv()
+                .map_err(|_| "The player left".to_string())?;
+            match game.parse_move(&input) {
+                Ok(mov) => return Ok(mov),
+                Err(error) => {
+                    if let Some(rejected) = &self.rejected {
+                        let _ = rejected.send(error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The search of this crate, keeping its transposition table from move to move
+pub struct AiPlayer {
+    pub limits: SearchLimits,
+    pub engine: Engine,
+}
+
+impl AiPlayer {
+    pub fn new(limits: SearchLimits) -> Self {
+        AiPlayer {
+            limits,
+            engine: Engine::new(),
+        }
+    }
+}
+
+impl Player for AiPlayer {
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError> {
+        self.engine
+            .search(game.chess_board(), game.current_player(), &self.limits)
+            .map(|result| result.best_move)
+            .ok_or_else(|| PlayerError::Resign("No move found".to_string()))
+    }
+}
+
+/// Plays any legal move, the same ones for the same seed
+pub struct RandomPlayer {
+    state: u64,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        RandomPlayer { state: seed | 1 }
+    }
+
+    /// Next number of the xorshift64 sequence
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+impl Player for RandomPlayer {
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError> {
+        let mut moves = game.chess_board().legal_moves(game.current_player());
+        if moves.is_empty() {
+            return Err(PlayerError::Resign("No legal move".to_string()));
+        }
+        let index = self.next() % moves.len() as u64;
+        Ok(moves.swap_remove(index as usize))
+    }
+}
+
+/// Plays moves from a list in coordinates or SAN, e.g. to replay a game or in tests
+pub struct ScriptedPlayer {
+    moves: VecDeque<String>,
+}
+
+impl ScriptedPlayer {
+    pub fn new(moves: &[&str]) -> Self {
+        ScriptedPlayer {
+            moves: moves.iter().map(|mov| mov.to_string()).collect(),
+        }
+    }
+}
+
+impl Player for ScriptedPlayer {
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError> {
+        let mov = self
+            .moves
+            .pop_front()
+            .ok_or_else(|| "The script has ended".to_string())?;
+        Ok(game.parse_move(&mov)?)
+    }
+}
+
+/// What goes between this computer and a remote peer
+#[derive(PartialEq, Clone, Debug)]
+pub enum PeerMessage {
+    /// A move in coordinates, e.g. `e2e4`
+    Move(String),
+    /// The last move that was sent is legal
+    Accept,
+    Decline,
+}
+
+/// The opponent at the other end of a connection. The transport, like a TCP stream, is
+/// left to whoever holds the other ends of the channels.
+pub struct RemotePlayer {
+    to_peer: Sender<PeerMessage>,
+    from_peer: Receiver<PeerMessage>,
+}
+
+impl RemotePlayer {
+    pub fn new(to_peer: Sender<PeerMessage>, from_peer: Receiver<PeerMessage>) -> Self {
+        RemotePlayer { to_peer, from_peer }
+    }
+
+    fn send(&self, message: PeerMessage) -> Result<(), String> {
+        self.to_peer
+            .send(message)
+            .map_err(|_| "The connection is closed".to_string())
+    }
+}
+
+impl Player for RemotePlayer {
+    /// Waits for a move of the peer, answering whether it is legal
+    fn request_move(&mut self, game: &ChessGame) -> Result<Move, PlayerError> {
+        loop {
+            let message = self
+                .from_peer
+                .recv()
+                .map_err(|_| "The connection is closed".to_string())?;
+            match message {
+                PeerMessage::Move(mov) => match game.parse_move(&mov) {
+                    Ok(mov) => {
+                        self.send(PeerMessage::Accept)?;
+                        return Ok(mov);
+                    }
+                    Err(_) => self.send(PeerMessage::Decline)?,
+                },
+                PeerMessage::Accept => (),
+                PeerMessage::Decline => {
+                    return Err(PlayerError::Abort(
+                        "The peer declined our move, the boards differ".to_string(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn opponent_moved(&mut self, _game: &ChessGame, mov: &PlayedMove) {
+        let _ = self.send(PeerMessage::Move(mov.to_move().to_coordinates()));
+    }
+}
+
+/// Plays a game between two players, White first
+pub struct GameRunner {
+    game: ChessGame,
+    players: [Box<dyn Player>; 2],
+}
+
+impl GameRunner {
+    pub fn new(game: ChessGame, white: Box<dyn Player>, black: Box<dyn Player>) -> Self {
+        GameRunner {
+            game,
+            players: [white, black],
+        }
+    }
+
+    pub fn game(&self) -> &ChessGame {
+        &self.game
+    }
+
+    pub fn into_game(self) -> ChessGame {
+        self.game
+    }
+
+    /// Asks the side to move for a move and plays it, returning the message of the game.
+    /// A player that fails to give a legal move resigns, unless the game has to be aborted.
+    /// Then it is left unfinished and the error is returned.
+    pub fn step(&mut self) -> Result<String, String> {
+        if self.game.status() != GameStatus::Ongoing {
+            return Err("Game is over!".to_string());
+        }
+        let color = self.game.current_player();
+        let (mover, other) = if color == Color::White {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        let played = match self.players[mover].request_move(&self.game) {
+            Ok(mov) => self
+                .game
+                .play(&mov.to_coordinates())
+                .map_err(PlayerError::Resign),
+            Err(error) => Err(error),
+        };
+        let message = match played {
+            Ok(message) => {
+                if let Some(mov) = self.game.history().last() {
+                    self.players[other].opponent_moved(&self.game, mov);
+                }
+                message
+            }
+            // running out of time while thinking ends the game through the clock
+            Err(_) if self.game.status() != GameStatus::Ongoing => "Game is over!".to_string(),
+            Err(PlayerError::Abort(error)) => return Err(error),
+            Err(PlayerError::Resign(error)) => {
+                self.game.resign(color)?;
+                format!("{:?} resigns: {}", color, error)
+            }
+        };
+        let status = self.game.status();
+        if status != GameStatus::Ongoing {
+            for player in self.players.iter_mut() {
+                player.game_ended(&self.game, status);
+            }
+        }
+        Ok(message)
+    }
+
+    /// Plays until the game is over, `on_move` gets the game and the message after each move
+    pub fn run(&mut self, mut on_move: impl FnMut(&ChessGame, &str)) -> GameStatus {
+        while let Ok(message) = self.step() {
+            on_move(&self.game, &message);
+        }
+        self.game.status()
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::piece_logic::Color;
+use std::sync::mpsc;
+use std::thread;
+
+#[test]
+fn runs_scripted_game() {
+    let mut runner = GameRunner::new(
+        init_standard_chess(),
+        Box::new(ScriptedPlayer::new(&["f3", "g2g4"])),
+        Box::new(ScriptedPlayer::new(&["e5", "Qh4#"])),
+    );
+    let mut moves = 0;
+    let status = runner.run(|_, _| moves += 1);
+    assert_eq!(status, GameStatus::Checkmate(Color::Black));
+    assert_eq!(moves, 4);
+    assert!(runner.step().is_err());
+}
+
+#[test]
+fn resigns_without_moves() {
+    let mut runner = GameRunner::new(
+        init_standard_chess(),
+        Box::new(ScriptedPlayer::new(&["e4", "Nf3"])),
+        Box::new(ScriptedPlayer::new(&["e5"])),
+    );
+    assert_eq!(runner.run(|_, _| ()), GameStatus::Resigned(Color::White));
+    assert_eq!(runner.game().history().len(), 3);
+}
+
+#[test]
+fn plays_random_moves() {
+    let play = |seed| {
+        let mut runner = GameRunner::new(
+            init_standard_chess(),
+            Box::new(RandomPlayer::new(seed)),
+            Box::new(RandomPlayer::new(seed + 1)),
+        );
+        for _ in 0..10 {
+            runner.step().unwrap();
+        }
+        runner.into_game().to_fen()
+    };
+    assert_eq!(play(7), play(7));
+    assert_ne!(play(7), play(8));
+}
+
+#[test]
+fn talks_to_peer() {
+    let (to_peer, peer_inbox) = mpsc::channel();
+    let (peer_outbox, from_peer) = mpsc::channel();
+    let peer = thread::spawn(move || {
+        let mut received = Vec::new();
+        for reply in ["e7e6", "e1e2", "d8h4"] {
+            received.push(peer_inbox.recv().unwrap());
+            peer_outbox
+                .send(PeerMessage::Move(reply.to_string()))
+                .unwrap();
+        }
+        while let Ok(message) = peer_inbox.recv() {
+            received.push(message);
+        }
+        received
+    });
+    let mut runner = GameRunner::new(
+        init_standard_chess(),
+        Box::new(ScriptedPlayer::new(&["f4", "g4"])),
+        Box::new(RemotePlayer::new(to_peer, from_peer)),
+    );
+    assert_eq!(runner.run(|_, _| ()), GameStatus::Checkmate(Color::Black));
+    drop(runner);
+    let move_of = |mov: &str| PeerMessage::Move(mov.to_string());
+    assert_eq!(
+        peer.join().unwrap(),
+        vec![
+            move_of("f2f4"),
+            PeerMessage::Accept,
+            move_of("g2g4"),
+            PeerMessage::Decline,
+            PeerMessage::Accept,
+        ]
+    );
+}
+
+#[test]
+fn aborts_when_peer_declines() {
+    let (to_peer, peer_inbox) = mpsc::channel();
+    let (peer_outbox, from_peer) = mpsc::channel();
+    let mut runner = GameRunner::new(
+        ChessGame::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap(),
+        Box::new(ScriptedPlayer::new(&["e8=Q"])),
+        Box::new(RemotePlayer::new(to_peer, from_peer)),
+    );
+    runner.step().unwrap();
+    assert_eq!(
+        peer_inbox.recv().unwrap(),
+        PeerMessage::Move("e7e8q".to_string())
+    );
+    peer_outbox.send(PeerMessage::Decline).unwrap();
+    assert!(runner.step().is_err());
+    assert_eq!(runner.game().status(), GameStatus::Ongoing);
+}
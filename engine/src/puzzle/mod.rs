@@ -0,0 +1,231 @@
+use crate::chess_game::*;
+use crate::piece_logic::Color;
+use std::fs;
+
+#[cfg(test)]
+mod tests;
+
+/// Tactics puzzle as exported by Lichess. The first move is the opponent's,
+/// the player answers it.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Puzzle {
+    pub id: String,
+    pub fen: String,
+    /// Moves in coordinates, e.g. `e7e8q`, starting with the opponent's
+    pub moves: Vec<String>,
+    pub rating: u32,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    /// Reads one line of the Lichess puzzle database:
+    /// `PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags`
+    pub fn from_csv(line: &str) -> Result<Puzzle, String> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        if fields.len() < 4 {
+            return Err(format!("Expected at least 4 fields in puzzle {:?}", line));
+        }
+        let moves: Vec<String> = fields[2].split_whitespace().map(String::from).collect();
+        if moves.len() < 2 {
+            return Err(format!("Puzzle {} has no moves to find", fields[0]));
+        }
+        Ok(Puzzle {
+            id: fields[0].to_string(),
+            fen: fields[1].to_string(),
+            moves,
+            rating: fields[3]
+                .parse()
+                .map_err(|_| format!("Invalid rating {:?} in puzzle {}", fields[3], fields[0]))?,
+            themes: fields
+                .get(7)
+                .map(|themes| themes.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Sets up the game after the opponent's first move
+    pub fn start(&self) -> Result<PuzzleSession, String> {
+        let mut game = ChessGame::from_fen(&self.fen)?;
+        game.play(&self.moves[0])?;
+        Ok(PuzzleSession {
+            player: game.current_player(),
+            puzzle: self.clone(),
+            game,
+            next: 1,
+            failed: false,
+        })
+    }
+}
+
+/// Reads a Lichess puzzle CSV, the header line is optional
+pub fn read_puzzles(csv: &str) -> Result<Vec<Puzzle>, String> {
+    csv.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with("PuzzleId"))
+        .map(Puzzle::from_csv)
+        .collect()
+}
+
+/// What became of a move tried in a puzzle
+#[derive(PartialEq, Clone, Debug)]
+pub enum Attempt {
+    /// The move was right, the opponent's answer in SAN has been played
+    Correct(String),
+    /// The move was right and finished the puzzle
+    Solved,
+    /// The move wasn't the solution and was not played
+    Wrong,
+}
+
+/// A puzzle being solved
+pub struct PuzzleSession {
+    puzzle: Puzzle,
+    game: ChessGame,
+    player: Color,
+    /// Index of the next move of the solution
+    next: usize,
+    failed: bool,
+}
+
+impl PuzzleSession {
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn game(&self) -> &ChessGame {
+        &self.game
+    }
+
+    /// The side the player solves for
+    pub fn player(&self) -> Color {
+        self.player
+    }
+
+    /// Checks a move of the player against the solution. Any move that mates is correct.
+    /// Input that isn't a legal move is an error and doesn't count as a try.
+    pub fn try_move(&mut self, input: &str) -> Result<Attempt, String> {
+        if self.is_finished() {
+            return Err("The puzzle is already solved".to_string());
+        }
+        let mov = self.game.parse_move(input)?;
+        let expected = self.game.parse_move(&self.puzzle.moves[self.next])?;
+        let mut next = self.game.chess_board().clone();
+        next.make_move(&mov)?;
+        let mates = next.is_checkmate(opponent(self.player));
+        if mov != expected && !mates {
+            self.failed = true;
+            return Ok(Attempt::Wrong);
+        }
+        self.game.play(&mov.to_coordinates())?;
+        self.next += 1;
+        if mates || self.next == self.puzzle.moves.len() {
+            self.next = self.puzzle.moves.len();
+            return Ok(Attempt::Solved);
+        }
+        let reply = &self.puzzle.moves[self.next];
+        self.game.play(reply)?;
+        self.next += 1;
+        Ok(Attempt::Correct(
+            self.game.history().last().unwrap().san.clone(),
+        ))
+    }
+
+    /// The next move of the solution in coordinates, asking for it counts as a failure
+    pub fn hint(&mut self) -> Option<&str> {
+        let mov = self.puzzle.moves.get(self.next)?;
+        self.failed = true;
+        Some(mov)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.puzzle.moves.len()
+    }
+
+    /// Finished without a wrong move or a hint
+    pub fn is_solved(&self) -> bool {
+        self.is_finished() && !self.failed
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// Results of solved and failed puzzles, kept in a text file with one
+/// `<puzzle id> solved|failed` line per attempt
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct PuzzleProgress {
+    attempts: Vec<(String, bool)>,
+}
+
+impl PuzzleProgress {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reads progress from `path`, a missing file means no progress yet
+    pub fn load(path: &str) -> Result<PuzzleProgress, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("Can't read {}: {}", path, error)),
+        };
+        let mut progress = PuzzleProgress::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [id, "solved"] => progress.record(id, true),
+                [id, "failed"] => progress.record(id, false),
+                _ => return Err(format!("Invalid progress line {:?} in {}", line, path)),
+            }
+        }
+        Ok(progress)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text: String = self
+            .attempts
+            .iter()
+            .map(|(id, solved)| format!("{} {}\n", id, if *solved { "solved" } else { "failed" }))
+            .collect();
+        fs::write(path, text).map_err(|error| format!("Can't write {}: {}", path, error))
+    }
+
+    pub fn record(&mut self, id: &str, solved: bool) {
+        self.attempts.push((id.to_string(), solved));
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.attempts.len()
+    }
+
+    pub fn solved(&self) -> usize {
+        self.attempts.iter().filter(|(_, solved)| *solved).count()
+    }
+
+    /// Share of attempts that were solved, from 0 to 1
+    pub fn success_rate(&self) -> f64 {
+        if self.attempts.is_empty() {
+            0.0
+        } else {
+            self.solved() as f64 / self.attempts() as f64
+        }
+    }
+
+    /// Whether the puzzle has been solved in any attempt
+    pub fn is_solved(&self, id: &str) -> bool {
+        self.attempts
+            .iter()
+            .any(|(other, solved)| other == id && *solved)
+    }
+
+    /// First of `puzzles` that hasn't been solved yet
+    pub fn next_unsolved<'a>(&self, puzzles: &'a [Puzzle]) -> Option<&'a Puzzle> {
+        puzzles.iter().find(|puzzle| !self.is_solved(&puzzle.id))
+    }
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+const CSV: &str = "\
+PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game Italian_Game_Classical_Variation
+backRank,6k1/5ppp/8/8/8/8/5PPP/RR4K1 b - - 0 1,g8h8 a1a8,600,75,90,10,backRankMate mateIn1,,
+";
+
+#[test]
+fn reads_lichess_csv() {
+    let puzzles = read_puzzles(CSV).unwrap();
+    assert_eq!(puzzles.len(), 2);
+    assert_eq!(puzzles[0].id, "00sHx");
+    assert_eq!(puzzles[0].moves, vec!["e8d7", "a2e6", "d7d8", "f7f8"]);
+    assert_eq!(puzzles[0].rating, 1760);
+    assert_eq!(
+        puzzles[0].themes,
+        vec!["mate", "mateIn2", "middlegame", "short"]
+    );
+    assert!(Puzzle::from_csv("id,8/8/8/8/8/8/8/8 w - - 0 1,e2e4,abc").is_err());
+}
+
+#[test]
+fn plays_the_solution() {
+    let puzzles = read_puzzles(CSV).unwrap();
+    let mut session = puzzles[0].start().unwrap();
+    assert_eq!(session.player(), Color::White);
+    assert_eq!(session.game().history().len(), 1);
+    assert_eq!(session.try_move("Qxg7").unwrap(), Attempt::Wrong);
+    assert_eq!(
+        session.try_move("Be6+").unwrap(),
+        Attempt::Correct("Kd8".to_string())
+    );
+    assert_eq!(session.try_move("f7f8").unwrap(), Attempt::Solved);
+    assert!(session.is_finished());
+    assert!(!session.is_solved());
+    assert!(session.try_move("Kg1").is_err());
+}
+
+#[test]
+fn any_mate_is_correct() {
+    let puzzles = read_puzzles(CSV).unwrap();
+    let mut session = puzzles[1].start().unwrap();
+    assert!(session.try_move("e2e5").is_err());
+    assert_eq!(session.try_move("Rb8#").unwrap(), Attempt::Solved);
+    assert!(session.is_solved());
+}
+
+#[test]
+fn stores_progress() {
+    let path = std::env::temp_dir().join("maltebl_chess_puzzle_progress.txt");
+    let path = path.to_str().unwrap();
+    let _ = fs::remove_file(path);
+    let mut progress = PuzzleProgress::load(path).unwrap();
+    assert_eq!(progress.attempts(), 0);
+    progress.record("00sHx", false);
+    progress.record("00sHx", true);
+    progress.record("backRank", false);
+    progress.save(path).unwrap();
+    let progress = PuzzleProgress::load(path).unwrap();
+    fs::remove_file(path).unwrap();
+    assert_eq!(progress.attempts(), 3);
+    assert!((progress.success_rate() - 1.0 / 3.0).abs() < 1e-9);
+    let puzzles = read_puzzles(CSV).unwrap();
+    assert_eq!(progress.next_unsolved(&puzzles).unwrap().id, "backRank");
+}
@@ -0,0 +1,444 @@
+use crate::board_logic::*;
+use crate::piece_logic::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+mod tt;
+
+pub use tt::{Bound, Entry, TranspositionTable};
+
+/// Score for giving mate right away, mates further ahead score one less per half move
+pub const MATE: i32 = 100_000;
+
+/// Transposition table of an engine, 2^20 entries of 16 bytes
+const TABLE_BITS: u32 = 20;
+
+/// Helper threads get room for deep searches, each ply keeps its moves on the stack
+const HELPER_STACK: usize = 32 << 20;
+
+/// When the search has to stop, whichever limit comes first
+#[derive(Clone, Debug)]
+pub struct SearchLimits {
+    pub depth: usize,
+    pub time: Option<Duration>,
+    /// Threads searching together, one gives the same result on every run
+    pub threads: usize,
+    /// Set from elsewhere to end the search, the deepest finished iteration is returned
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Move,
+    /// Centipawns from the point of view of the side to move
+    pub score: i32,
+    /// Deepest iteration that was finished
+    pub depth: usize,
+    pub nodes: u64,
+    /// Expected line of play, starting with `best_move`
+    pub pv: Vec<Move>,
+}
+
+/// Small bonus for where a piece stands: pawns for advancing,
+/// minor pieces and queens for the centre, the king for staying out of it
+fn position_bonus(piece: &Piece, (x, y): (usize, usize)) -> i32 {
+    let centrality = 14 - (2 * x as i32 - 7).abs() - (2 * y as i32 - 7).abs();
+    let advance = if piece.color == Color::White {
+        y as i32 - 1
+    } else {
+        6 - y as i32
+    };
+    match piece.piece_type {
+        PieceType::Pawn => advance * 5 + if (2..6).contains(&x) { 5 } else { 0 },
+        PieceType::Knight => centrality * 3,
+        PieceType::Bishop | PieceType::Queen => centrality * 2,
+        PieceType::King => -centrality * 2,
+        _ => centrality,
+    }
+}
+
+/// Static evaluation in centipawns from the point of view of `color`
+pub fn evaluate(board: &ChessBoard, color: Color) -> i32 {
+    let mut score = 0;
+    for (y, row) in board.ref_board().iter().enumerate() {
+        for (x, space) in row.iter().enumerate() {
+            if let Some(piece) = space {
+                let value = piece.value() + position_bonus(piece, (x, y));
+                if piece.color == color {
+                    score += value;
+                } else {
+                    score -= value;
+                }
+            }
+        }
+    }
+    score
+}
+
+/// Time to spend on a move under a clock: an even share of what is left over the moves to
+/// go, 30 when the time control doesn't say, plus most of the increment
+pub fn time_for_move(
+    remaining: Duration,
+    increment: Duration,
+    moves_to_go: Option<u32>,
+) -> Duration {
+    remaining / moves_to_go.unwrap_or(30).max(1) + increment * 3 / 4
+}
+
+/// Searches that share one transposition table, so each search starts from what the
+/// earlier ones found. The table is allocated on the first search and kept until the
+/// engine is dropped, `clear` forgets its contents when a new game starts.
+#[derive(Default)]
+pub struct Engine {
+    table: OnceLock<TranspositionTable>,
+}
+
+/// One search with a table of its own, use an `Engine` to keep it for the next search
+pub fn search(board: &ChessBoard, color: Color, limits: &SearchLimits) -> Option<SearchResult> {
+    Engine::new().search(board, color, limits)
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine::default()
+    }
+
+    /// Forgets every earlier search
+    pub fn clear(&mut self) {
+        if let Some(table) = self.table.get_mut() {
+            table.clear();
+        }
+    }
+
+    /// Finds the best move for `color` with iterative deepening alpha-beta search.
+    /// Returns `None` when there is no legal move.
+    ///
+    /// With more threads, helpers search the same position at staggered depths and share
+    /// what they find through the transposition table (lazy SMP). The result is the one of
+    /// the main thread, with the nodes of all of them.
+    pub fn search(
+        &self,
+        board: &ChessBoard,
+        color: Color,
+        limits: &SearchLimits,
+    ) -> Option<SearchResult> {
+        let shared = Shared {
+            table: self
+                .table
+                .get_or_init(|| TranspositionTable::new(TABLE_BITS)),
+            deadline: limits.time.map(|time| Instant::now() + time),
+            stop: AtomicBool::new(false),
+            external: limits.stop.clone(),
+        };
+        let depth = limits.depth.max(1);
+        if limits.threads <= 1 {
+            return Searcher::new(&shared, depth, false).iterate(board, color, 1, depth);
+        }
+        thread::scope(|scope| {
+            let shared = &shared;
+            let helpers: Vec<_> = (1..limits.threads)
+                .map(|index| {
+                    thread::Builder::new()
+                        .stack_size(HELPER_STACK)
+                        .spawn_scoped(scope, move || {
+                            let mut searcher = Searcher::new(shared, depth, true);
+                            searcher.iterate(board, color, 1 + index % 2, depth);
+                            searcher.nodes
+                        })
+                        .expect("Can't start a search thread")
+                })
+                .collect();
+            let mut result = Searcher::new(shared, depth, false).iterate(board, color, 1, depth);
+            shared.stop.store(true, Ordering::Relaxed);
+            let helper_nodes: u64 = helpers
+                .into_iter()
+                .map(|helper| helper.join().unwrap_or(0))
+                .sum();
+            if let Some(result) = result.as_mut() {
+                result.nodes += helper_nodes;
+            }
+            result
+        })
+    }
+}
+
+/// What the threads of one search have in common
+struct Shared<'a> {
+    table: &'a TranspositionTable,
+    deadline: Option<Instant>,
+    /// Set when the main thread is done, so the helpers stop too
+    stop: AtomicBool,
+    external: Option<Arc<AtomicBool>>,
+}
+
+struct Searcher<'a> {
+    shared: &'a Shared<'a>,
+    nodes: u64,
+    stopped: bool,
+    /// The main thread always finishes its first iteration, to have a move to return
+    may_stop: bool,
+    /// Best line found below each ply, with room for the deepest one so nothing is
+    /// allocated while searching
+    pv: Vec<Vec<Move>>,
+    /// Line of the previous iteration, tried first
+    previous: Vec<Move>,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(shared: &'a Shared<'a>, depth: usize, helper: bool) -> Self {
+        Searcher {
+            shared,
+            nodes: 0,
+            stopped: false,
+            may_stop: helper,
+            pv: (0..=depth).map(|_| Vec::with_capacity(depth)).collect(),
+            previous: Vec::with_capacity(depth),
+        }
+    }
+
+    /// Iterative deepening from `first` to `last` depth
+    fn iterate(
+        &mut self,
+        board: &ChessBoard,
+        color: Color,
+        first: usize,
+        last: usize,
+    ) -> Option<SearchResult> {
+        let mut best: Option<SearchResult> = None;
+        for depth in first..=last {
+            let score = self.negamax(board, color, depth, 0, -MATE - 1, MATE + 1, true);
+            if self.stopped && best.is_some() {
+                break;
+            }
+            let pv = self.pv[0].clone();
+            if pv.is_empty() {
+                return None;
+            }
+            self.previous.clone_from(&pv);
+            best = Some(SearchResult {
+                best_move: pv[0].clone(),
+                score,
+                depth,
+                nodes: self.nodes,
+                pv,
+            });
+            self.may_stop = true;
+            if self.out_of_time() || score.abs() > MATE - 1000 {
+                break;
+            }
+        }
+        if let Some(result) = best.as_mut() {
+            result.nodes = self.nodes;
+        }
+        best
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        if !self.stopped && self.may_stop {
+            let shared = self.shared;
+            self.stopped = shared.stop.load(Ordering::Relaxed)
+                || matches!(&shared.external, Some(stop) if stop.load(Ordering::Relaxed))
+                || matches!(shared.deadline, Some(deadline) if Instant::now() >= deadline);
+        }
+        self.stopped
+    }
+
+    /// Leaves the best line in `pv[ply]`, `on_pv` tells whether the moves so far followed
+    /// the line of the previous iteration. Captures are generated and searched before the
+    /// quiet moves, which a cutoff can make unnecessary.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &ChessBoard,
+        color: Color,
+        depth: usize,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        on_pv: bool,
+    ) -> i32 {
+        self.nodes += 1;
+        self.pv[ply].clear();
+        if !board.has_king(color) {
+            return -MATE + ply as i32;
+        }
+        if depth == 0 {
+            return self.quiescence(board, color, alpha, beta);
+        }
+        let hash = board.zobrist_hash(color);
+        let stored = self.shared.table.probe(hash, ply);
+        if let Some(entry) = &stored {
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            if ply > 0 && entry.depth >= depth && usable {
+                return entry.score;
+            }
+        }
+        let first = match (on_pv, stored) {
+            (true, _) if ply < self.previous.len() => Some(self.previous[ply].clone()),
+            (_, Some(entry)) => entry.best_move,
+            _ => None,
+        };
+        let original_alpha = alpha;
+        let mut searched = false;
+        let mut moves = MoveList::new();
+        'stages: for &stage in [None, Some(MoveStage::Captures), Some(MoveStage::Quiets)].iter() {
+            moves.clear();
+            match stage {
+                None => match &first {
+                    // a stored move can come from another position with the same hash
+                    Some(mov) if matches!(board.ref_piece(mov.from), Some(piece) if piece.color == color) => {
+                        moves.push(mov.clone())
+                    }
+                    _ => continue,
+                },
+                Some(stage) => {
+                    board.generate_moves(color, stage, &mut moves);
+                    order_moves(board, &mut moves, first.as_ref());
+                }
+            }
+            for (index, mov) in moves.iter().enumerate() {
+                if stage.is_some() && Some(mov) == first.as_ref() {
+                    continue;
+                }
+                let mut next = board.clone();
+                if next.apply_move(mov).is_err() {
+                    continue;
+                }
+                let score = -self.negamax(
+                    &next,
+                    opponent(color),
+                    depth - 1,
+                    ply + 1,
+                    -beta,
+                    -alpha,
+                    on_pv && stage.is_none() && index == 0,
+                );
+                if self.out_of_time() {
+                    return alpha;
+                }
+                let improves = score > alpha || !searched;
+                searched = true;
+                if improves {
+                    alpha = alpha.max(score);
+                    let (line, rest) = self.pv.split_at_mut(ply + 1);
+                    line[ply].clear();
+                    line[ply].push(mov.clone());
+                    line[ply].extend_from_slice(&rest[0]);
+                    if alpha >= beta {
+                        break 'stages;
+                    }
+                }
+            }
+        }
+        if !searched {
+            return if board.is_checked(color) {
+                -MATE + ply as i32
+            } else {
+                0
+            };
+        }
+        let bound = if alpha >= beta {
+            Bound::Lower
+        } else if alpha > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.shared.table.store(
+            hash,
+            ply,
+            &Entry {
+                best_move: self.pv[ply].first().cloned(),
+                depth,
+                score: alpha,
+                bound,
+            },
+        );
+        alpha
+    }
+
+    /// Only captures and promotions that don't lose material are searched, so exchanges
+    /// are finished before evaluating
+    fn quiescence(&mut self, board: &ChessBoard, color: Color, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if !board.has_king(color) {
+            return -MATE;
+        }
+        let stand_pat = evaluate(board, color);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        let mut captures = MoveList::new();
+        board.generate_moves(color, MoveStage::Captures, &mut captures);
+        order_moves(board, &mut captures, None);
+        for mov in captures.iter() {
+            if self.out_of_time() {
+                break;
+            }
+            if board.see(mov) < 0 {
+                continue;
+            }
+            let mut next = board.clone();
+            if next.apply_move(mov).is_err() {
+                continue;
+            }
+            let score = -self.quiescence(&next, opponent(color), -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// Puts `first` in front, then captures of valuable pieces by cheap ones, then promotions.
+/// Sorts in place and keeps the order of equal moves, without allocating.
+fn order_moves(board: &ChessBoard, moves: &mut [Move], first: Option<&Move>) {
+    let mut keys = [0; MAX_MOVES];
+    for (key, mov) in keys.iter_mut().zip(moves.iter()) {
+        *key = move_key(board, mov, first);
+    }
+    for index in 1..moves.len() {
+        let mut current = index;
+        while current > 0 && keys[current - 1] > keys[current] {
+            keys.swap(current - 1, current);
+            moves.swap(current - 1, current);
+            current -= 1;
+        }
+    }
+}
+
+fn move_key(board: &ChessBoard, mov: &Move, first: Option<&Move>) -> i32 {
+    if Some(mov) == first {
+        return i32::MIN;
+    }
+    let victim = board.ref_piece(mov.to).map_or(0, Piece::value);
+    let attacker = board.ref_piece(mov.from).map_or(0, Piece::value);
+    let promotion = match &mov.promotion {
+        Some(PieceType::Queen) => 800,
+        Some(_) => -100,
+        None => 0,
+    };
+    if victim > 0 {
+        -(victim * 10 - attacker + promotion)
+    } else {
+        -promotion
+    }
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
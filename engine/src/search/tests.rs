@@ -0,0 +1,164 @@
+use super::*;
+
+fn best(fen: &str, depth: usize) -> SearchResult {
+    let position = ChessBoard::from_fen(fen).unwrap();
+    let limits = SearchLimits {
+        depth,
+        time: None,
+        threads: 1,
+        stop: None,
+    };
+    search(&position.board, position.turn, &limits).unwrap()
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let result = best("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2);
+    assert_eq!(result.best_move.to_coordinates(), "a1a8");
+    assert_eq!(result.score, MATE - 1);
+}
+
+#[test]
+fn wins_material() {
+    let result = best("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1", 2);
+    assert_eq!(result.best_move.to_coordinates(), "d2d5");
+    // the knight is defended, taking it loses the queen
+    let result = best("4k3/8/4p3/3n4/8/8/8/3QK3 w - - 0 1", 2);
+    assert_ne!(result.best_move.to_coordinates(), "d1d5");
+}
+
+#[test]
+fn promotes() {
+    let result = best("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", 1);
+    assert_eq!(result.best_move.to_coordinates(), "e7e8q");
+}
+
+#[test]
+fn no_move_in_stalemate() {
+    let position = ChessBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    let limits = SearchLimits {
+        depth: 2,
+        time: None,
+        threads: 1,
+        stop: None,
+    };
+    assert!(search(&position.board, position.turn, &limits).is_none());
+    assert!(position.board.is_stalemate(Color::Black));
+}
+
+#[test]
+fn single_thread_is_deterministic() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let first = best(kiwipete, 3);
+    let second = best(kiwipete, 3);
+    assert_eq!(first.pv, second.pv);
+    assert_eq!((first.score, first.nodes), (second.score, second.nodes));
+}
+
+#[test]
+fn keeps_the_table_between_searches() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let position = ChessBoard::from_fen(kiwipete).unwrap();
+    let limits = SearchLimits {
+        depth: 3,
+        time: None,
+        threads: 1,
+        stop: None,
+    };
+    let mut engine = Engine::new();
+    let first = engine
+        .search(&position.board, position.turn, &limits)
+        .unwrap();
+    let again = engine
+        .search(&position.board, position.turn, &limits)
+        .unwrap();
+    assert!(again.nodes < first.nodes);
+    assert_eq!(again.best_move, first.best_move);
+    engine.clear();
+    let cleared = engine
+        .search(&position.board, position.turn, &limits)
+        .unwrap();
+    assert_eq!((cleared.pv, cleared.nodes), (first.pv, first.nodes));
+}
+
+#[test]
+fn searches_with_threads() {
+    let position = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let limits = SearchLimits {
+        depth: 4,
+        time: None,
+        threads: 4,
+        stop: None,
+    };
+    let result = search(&position.board, position.turn, &limits).unwrap();
+    assert_eq!(result.best_move.to_coordinates(), "a1a8");
+    assert_eq!(result.score, MATE - 1);
+}
+
+#[test]
+fn stops_on_request() {
+    let position = ChessBoard::from_fen(STARTING_FEN).unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let limits = SearchLimits {
+        depth: 30,
+        time: None,
+        threads: 2,
+        stop: Some(stop.clone()),
+    };
+    let started = Instant::now();
+    let stopper = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        stop.store(true, Ordering::Relaxed);
+    });
+    let result = search(&position.board, position.turn, &limits).unwrap();
+    stopper.join().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert!(result.depth < 30);
+}
+
+#[test]
+fn stores_positions() {
+    let table = TranspositionTable::new(4);
+    let mov = Move {
+        from: (4, 6),
+        to: (4, 7),
+        promotion: Some(PieceType::Knight),
+    };
+    let entry = Entry {
+        best_move: Some(mov.clone()),
+        depth: 3,
+        score: MATE - 5,
+        bound: Bound::Lower,
+    };
+    table.store(0x1234_5678_9abc_def0, 2, &entry);
+    assert!(table.probe(0x1234_5678_9abc_def1, 2).is_none());
+    let found = table.probe(0x1234_5678_9abc_def0, 4).unwrap();
+    assert_eq!(found.best_move, Some(mov));
+    assert_eq!((found.depth, found.bound), (3, Bound::Lower));
+    // the same mate seen from two plies further away
+    assert_eq!(found.score, MATE - 7);
+}
+
+#[test]
+fn evaluation_is_symmetric() {
+    let fens = [
+        STARTING_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens.iter() {
+        let board = ChessBoard::from_fen(fen).unwrap().board;
+        for &color in [Color::White, Color::Black].iter() {
+            let score = evaluate(&board, color);
+            for (transformed, turn) in [
+                board.mirror_vertical(color),
+                board.mirror_horizontal(color),
+                board.rotate(color),
+            ]
+            .iter()
+            {
+                assert_eq!(evaluate(transformed, *turn), score);
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::MATE;
+use crate::board_logic::Move;
+use crate::piece_logic::PieceType;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How a stored score relates to the real one
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Bound {
+    Exact,
+    /// The real score is at least this high
+    Lower,
+    /// The real score is at most this high
+    Upper,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub best_move: Option<Move>,
+    pub depth: usize,
+    pub score: i32,
+    pub bound: Bound,
+}
+
+/// Results of earlier searches by Zobrist hash, shared between the search threads without
+/// locks. Each slot keeps the hash xor-ed with its data, so a slot torn by two threads
+/// writing at once no longer matches any position and is ignored.
+pub struct TranspositionTable {
+    slots: Vec<(AtomicU64, AtomicU64)>,
+}
+
+impl TranspositionTable {
+    /// Room for `2^bits` positions, 16 bytes each
+    pub fn new(bits: u32) -> Self {
+        TranspositionTable {
+            slots: (0..1usize << bits)
+                .map(|_| (AtomicU64::new(0), AtomicU64::new(0)))
+                .collect(),
+        }
+    }
+
+    /// Empties every slot
+    pub fn clear(&mut self) {
+        for (key, data) in self.slots.iter_mut() {
+            *key.get_mut() = 0;
+            *data.get_mut() = 0;
+        }
+    }
+
+    /// `ply` is the distance from the root, mate scores are stored relative to the position
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<Entry> {
+        let (key, data) = &self.slots[hash as usize & (self.slots.len() - 1)];
+        let data = data.load(Ordering::Relaxed);
+        if data == 0 || key.load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+        let mut score = (data >> 32) as u32 as i32;
+        if score > MATE - 1000 {
+            score -= ply as i32;
+        } else if score < -MATE + 1000 {
+            score += ply as i32;
+        }
+        Some(Entry {
+            best_move: unpack_move(data as u16),
+            depth: (data >> 16 & 0xff) as usize,
+            score,
+            bound: match data >> 24 & 3 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+        })
+    }
+
+    /// Keeps the deeper result when the slot holds the same position
+    pub fn store(&self, hash: u64, ply: usize, entry: &Entry) {
+        let (key, data) = &self.slots[hash as usize & (self.slots.len() - 1)];
+        let old = data.load(Ordering::Relaxed);
+        if key.load(Ordering::Relaxed) ^ old == hash && (old >> 16 & 0xff) as usize > entry.depth {
+            return;
+        }
+        let mut score = entry.score;
+        if score > MATE - 1000 {
+            score += ply as i32;
+        } else if score < -MATE + 1000 {
+            score -= ply as i32;
+        }
+        let bound = match entry.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let new = (score as u32 as u64) << 32
+            | bound << 24
+            | (entry.depth.min(255) as u64) << 16
+            | entry.best_move.as_ref().map_or(0, pack_move) as u64;
+        key.store(hash ^ new, Ordering::Relaxed);
+        data.store(new, Ordering::Relaxed);
+    }
+}
+
+/// 6 bits for each square, 3 for the promotion and the top bit to tell it from no move
+fn pack_move(mov: &Move) -> u16 {
+    let square = |(x, y): (usize, usize)| (y * 8 + x) as u16;
+    let promotion = match mov.promotion {
+        None => 0,
+        Some(PieceType::Queen) => 1,
+        Some(PieceType::Rook) => 2,
+        Some(PieceType::Bishop) => 3,
+        Some(PieceType::Knight) => 4,
+        Some(_) => return 0,
+    };
+    1 << 15 | promotion << 12 | square(mov.to) << 6 | square(mov.from)
+}
+
+fn unpack_move(packed: u16) -> Option<Move> {
+    if packed >> 15 == 0 {
+        return None;
+    }
+    let square = |bits: u16| ((bits & 7) as usize, (bits >> 3 & 7) as usize);
+    Some(Move {
+        from: square(packed),
+        to: square(packed >> 6),
+        promotion: match packed >> 12 & 7 {
+            1 => Some(PieceType::Queen),
+            2 => Some(PieceType::Rook),
+            3 => Some(PieceType::Bishop),
+            4 => Some(PieceType::Knight),
+            _ => None,
+        },
+    })
+}
@@ -0,0 +1,218 @@
+use crate::board_logic::*;
+use crate::piece_logic::*;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests;
+
+/// What the side to move has to achieve in the given number of its moves
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Stipulation {
+    /// Mate the opponent against any defence
+    Mate,
+    /// Force the opponent to give mate, while the opponent tries not to
+    Selfmate,
+    /// Both sides work together so the opponent mates the side to move
+    Helpmate,
+}
+
+/// Move of a solution with every answer to it, the answers of the side trying to reach
+/// the goal only contain moves that still do
+#[derive(Clone, Debug)]
+pub struct SolutionNode {
+    pub mov: Move,
+    pub san: String,
+    pub replies: Vec<SolutionNode>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub stipulation: Stipulation,
+    pub moves: usize,
+    /// One tree for each key move
+    pub tree: Vec<SolutionNode>,
+}
+
+impl Solution {
+    pub fn keys(&self) -> Vec<&Move> {
+        self.tree.iter().map(|node| &node.mov).collect()
+    }
+
+    /// A sound problem has exactly one key
+    pub fn is_sound(&self) -> bool {
+        self.tree.len() == 1
+    }
+
+    /// Solutions besides the first one
+    pub fn cooks(&self) -> &[SolutionNode] {
+        &self.tree[self.tree.len().min(1)..]
+    }
+}
+
+/// Finds every key move that reaches the stipulation within `moves` moves of `turn`.
+/// The search is exhaustive, so an empty tree proves there is no solution.
+pub fn solve(board: &ChessBoard, turn: Color, stipulation: Stipulation, moves: usize) -> Solution {
+    let tree = if moves == 0 {
+        Vec::new()
+    } else {
+        Solver::new(stipulation, moves).solutions(board, turn, moves)
+    };
+    Solution {
+        stipulation,
+        moves,
+        tree,
+    }
+}
+
+/// Proves moves and builds their trees in one pass. A move of the side reaching for the
+/// goal fails at the first defence it has no answer to, so that defence is tried first
+/// against the next move.
+struct Solver {
+    stipulation: Stipulation,
+    /// Working moves by Zobrist hash and moves left, positions are often reached by
+    /// several move orders
+    known: HashMap<(u64, usize), Vec<SolutionNode>>,
+    /// Last defence that refuted a move, by moves left
+    refutations: Vec<Option<Move>>,
+}
+
+impl Solver {
+    fn new(stipulation: Stipulation, moves: usize) -> Self {
+        Solver {
+            stipulation,
+            known: HashMap::new(),
+            refutations: vec![None; moves + 1],
+        }
+    }
+
+    /// Every move of `first`, who is to move, that reaches the goal within `n` moves
+    fn solutions(&mut self, board: &ChessBoard, first: Color, n: usize) -> Vec<SolutionNode> {
+        let key = (board.zobrist_hash(first), n);
+        if let Some(known) = self.known.get(&key) {
+            return known.clone();
+        }
+        let found: Vec<SolutionNode> = board
+            .legal_moves(first)
+            .into_iter()
+            .filter_map(|mov| self.attempt(board, first, mov, n))
+            .collect();
+        self.known.insert(key, found.clone());
+        found
+    }
+
+    /// Tree below `mov` of `first`, `None` when it doesn't reach the goal within `n` moves
+    fn attempt(
+        &mut self,
+        board: &ChessBoard,
+        first: Color,
+        mov: Move,
+        n: usize,
+    ) -> Option<SolutionNode> {
+        let next = after(board, &mov);
+        let second = opponent(first);
+        let mated = next.is_checkmate(second);
+        let replies = match self.stipulation {
+            Stipulation::Mate if mated => Vec::new(),
+            Stipulation::Mate if n == 1 => return None,
+            Stipulation::Selfmate if mated => return None,
+            Stipulation::Helpmate => {
+                let replies: Vec<SolutionNode> = next
+                    .legal_moves(second)
+                    .into_iter()
+                    .filter_map(|reply| self.answer(&next, first, reply, n))
+                    .collect();
+                if replies.is_empty() {
+                    return None;
+                }
+                replies
+            }
+            Stipulation::Mate | Stipulation::Selfmate => {
+                let mut defences = next.legal_moves(second);
+                if defences.is_empty() {
+                    return None;
+                }
+                if let Some(refutation) = &self.refutations[n] {
+                    if let Some(i) = defences.iter().position(|reply| reply == refutation) {
+                        defences.swap(0, i);
+                    }
+                }
+                let mut replies = Vec::with_capacity(defences.len());
+                for reply in defences {
+                    match self.answer(&next, first, reply.clone(), n) {
+                        Some(node) => replies.push(node),
+                        None => {
+                            self.refutations[n] = Some(reply);
+                            return None;
+                        }
+                    }
+                }
+                replies
+            }
+        };
+        Some(SolutionNode {
+            san: notation(board, &mov, &next),
+            mov,
+            replies,
+        })
+    }
+
+    /// `reply` of the second side with the ways `first` goes on after it, `None` when
+    /// `first` can't reach the goal from there in the moves left
+    fn answer(
+        &mut self,
+        board: &ChessBoard,
+        first: Color,
+        reply: Move,
+        n: usize,
+    ) -> Option<SolutionNode> {
+        let position = after(board, &reply);
+        let continuations = if position.is_checkmate(first) {
+            if self.stipulation == Stipulation::Mate {
+                return None;
+            }
+            Vec::new()
+        } else {
+            let continuations = match n {
+                1 => Vec::new(),
+                _ => self.solutions(&position, first, n - 1),
+            };
+            if continuations.is_empty() {
+                return None;
+            }
+            continuations
+        };
+        Some(SolutionNode {
+            san: notation(board, &reply, &position),
+            mov: reply,
+            replies: continuations,
+        })
+    }
+}
+
+fn after(board: &ChessBoard, mov: &Move) -> ChessBoard {
+    let mut next = board.clone();
+    next.make_move(mov).expect("Solver tried an illegal move");
+    next
+}
+
+/// SAN with promotion and check markers, `next` is the position after `mov`
+fn notation(board: &ChessBoard, mov: &Move, next: &ChessBoard) -> String {
+    let color = board.ref_piece(mov.from).unwrap().color;
+    let mut san = board.san(mov.from, mov.to).unwrap_or_default();
+    if let Some(piece_type) = &mov.promotion {
+        san = format!("{}={}", san, piece_type);
+    }
+    if next.is_checkmate(opponent(color)) {
+        san.push('#');
+    } else if next.is_checked(opponent(color)) {
+        san.push('+');
+    }
+    san
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
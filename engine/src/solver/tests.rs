@@ -0,0 +1,75 @@
+use super::*;
+
+fn solve_fen(fen: &str, stipulation: Stipulation, moves: usize) -> Solution {
+    let position = ChessBoard::from_fen(fen).unwrap();
+    solve(&position.board, position.turn, stipulation, moves)
+}
+
+fn keys(solution: &Solution) -> Vec<String> {
+    solution
+        .keys()
+        .iter()
+        .map(|mov| mov.to_coordinates())
+        .collect()
+}
+
+#[test]
+fn mate_in_one() {
+    let solution = solve_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Stipulation::Mate, 1);
+    assert!(solution.is_sound());
+    assert_eq!(solution.tree[0].san, "Ra8#");
+    let cooked = solve_fen("6k1/5ppp/8/8/8/8/8/RR4K1 w - - 0 1", Stipulation::Mate, 1);
+    assert_eq!(keys(&cooked), vec!["a1a8", "b1b8"]);
+    assert_eq!(cooked.cooks().len(), 1);
+}
+
+#[test]
+fn mate_in_two() {
+    // Morphy's problem: 1. Ra6! bxa6 2. b7#
+    let solution = solve_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1", Stipulation::Mate, 2);
+    assert_eq!(keys(&solution), vec!["a1a6"]);
+    let key = &solution.tree[0];
+    assert_eq!(key.replies.len(), 7);
+    let capture = key.replies.iter().find(|r| r.san == "bxa6").unwrap();
+    assert_eq!(capture.replies.len(), 1);
+    assert_eq!(capture.replies[0].san, "b7#");
+    assert!(
+        solve_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1", Stipulation::Mate, 1)
+            .tree
+            .is_empty()
+    );
+}
+
+#[test]
+fn mate_in_three() {
+    // the king has to come closer first, as every rook check lets the king out
+    let position = "k7/8/8/2K5/8/8/8/1R6 w - - 0 1";
+    assert!(solve_fen(position, Stipulation::Mate, 2).tree.is_empty());
+    let solution = solve_fen(position, Stipulation::Mate, 3);
+    assert_eq!(keys(&solution), vec!["c5b6"]);
+    // every defence is answered, and each line ends in mate by the third move
+    fn depth(node: &SolutionNode) -> usize {
+        1 + node.replies.iter().map(depth).max().unwrap_or(0)
+    }
+    assert!(!solution.tree[0].replies.is_empty());
+    assert_eq!(depth(&solution.tree[0]), 5);
+}
+
+#[test]
+fn selfmate_in_one() {
+    // any waiting move of the rook forces g2#
+    let solution = solve_fen("8/8/8/8/R7/6pk/8/6BK w - - 0 1", Stipulation::Selfmate, 1);
+    assert_eq!(
+        keys(&solution),
+        vec!["a4b4", "a4c4", "a4d4", "a4e4", "a4f4"]
+    );
+    assert_eq!(solution.tree[0].replies.len(), 1);
+    assert_eq!(solution.tree[0].replies[0].san, "g2#");
+}
+
+#[test]
+fn helpmate_in_one() {
+    let solution = solve_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1", Stipulation::Helpmate, 1);
+    assert_eq!(keys(&solution), vec!["a8b8"]);
+    assert_eq!(solution.tree[0].replies[0].san, "Rh8#");
+}
@@ -1,4 +1,38 @@
-use maltebl_chess::chess_game::*;
+#![allow(clippy::bool_assert_comparison)]
+use maltebl_chess::{
+    board_logic::*, chess_game::*, clock::*, engine_match::*, piece_logic::Color, search::*,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Counts the allocations of each thread, to check that search doesn't allocate
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
 #[test]
 fn test_chessgame() {
     let mut chess = init_standard_chess();
@@ -31,3 +65,394 @@ fn pick_piece() {
         chess.pick_piece("f2".to_string()).unwrap()
     )
 }
+
+#[test]
+fn dark_chess() {
+    let mut chess = init_dark_chess();
+    let visible = chess.visible_squares(Color::White);
+    assert_eq!(visible.len(), 32);
+    assert!(visible.contains(&(4, 3)));
+    assert!(!visible.contains(&(4, 6)));
+    let redacted = chess.get_redacted_board(Color::White);
+    assert!(redacted[6][4].is_none());
+    assert!(redacted[2][4].as_ref().unwrap().is_none());
+    chess.print_redacted_board(Color::White);
+
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    chess.move_piece("f7 f6".to_string()).unwrap();
+    chess.move_piece("d1 h5".to_string()).unwrap();
+    assert!(chess.get_redacted_board(Color::White)[7][4].is_some());
+    // black ignores the check, which is allowed without check rules
+    assert!(!chess
+        .move_piece("a7 a6".to_string())
+        .unwrap()
+        .contains("Check"));
+    assert!(chess
+        .move_piece("h5 e8".to_string())
+        .unwrap()
+        .contains("king was captured"));
+    assert!(chess.move_piece("a6 a5".to_string()).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_game() {
+    let mut chess = init_standard_chess();
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    let json = serde_json::to_string(&chess).unwrap();
+    let mut loaded: ChessGame = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.current_player(), Color::Black);
+    assert!(loaded.move_piece("e2 e3".to_string()).is_err());
+    loaded.move_piece("e7 e5".to_string()).unwrap();
+    assert_eq!(
+        vec!["f3", "f4"],
+        loaded.pick_piece("f2".to_string()).unwrap()
+    );
+
+    // movement follows from the piece type and the record has to hold together
+    assert!(!json.contains("movement"));
+    let custom = json.replacen("\"Rook\"", "{\"Custom\":[\"R\",\"X\"]}", 1);
+    assert!(serde_json::from_str::<ChessGame>(&custom).is_err());
+    for (from, to) in [
+        ("\"children\":[1]", "\"children\":[7]"),
+        ("\"current\":1", "\"current\":3"),
+    ]
+    .iter()
+    {
+        let broken = json.replace(from, to);
+        assert_ne!(broken, json);
+        assert!(serde_json::from_str::<ChessGame>(&broken).is_err());
+    }
+    let wrong_position = json.replacen("\"Pawn\"", "\"Queen\"", 1);
+    assert!(serde_json::from_str::<ChessGame>(&wrong_position)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("doesn't follow from the game record"));
+
+    // a dark game can end with a king off the board
+    let mut dark = init_dark_chess();
+    for mov in ["e2e4", "f7f6", "d1h5", "a7a6", "h5e8"].iter() {
+        dark.play(mov).unwrap();
+    }
+    let json = serde_json::to_string(&dark).unwrap();
+    let loaded: ChessGame = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.status(), GameStatus::KingCaptured(Color::White));
+    assert_eq!(loaded.to_fen(), dark.to_fen());
+    // only without check rules
+    let standard = json.replace("\"king_capture\":true", "\"king_capture\":false");
+    assert_ne!(standard, json);
+    assert!(serde_json::from_str::<ChessGame>(&standard).is_err());
+}
+
+#[test]
+fn records_san() {
+    let mut chess = init_standard_chess();
+    for mov in ["e2 e4", "d7 d5", "e4 d5", "g8 f6", "f1 b5", "c7 c6"].iter() {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    let san: Vec<String> = chess.history().into_iter().map(|m| m.san).collect();
+    assert_eq!(san, vec!["e4", "d5", "exd5", "Nf6", "Bb5+", "c6"]);
+    assert!(chess
+        .to_pgn()
+        .ends_with("1. e4 d5 2. exd5 Nf6 3. Bb5+ c6 *\n"));
+}
+
+#[test]
+fn loses_on_time() {
+    let time = ManualTimeSource::new();
+    let mut chess = init_standard_chess();
+    chess.set_clock(Clock::new(
+        TimeControl::fischer(Duration::from_secs(60), Duration::from_secs(2)),
+        Box::new(time.clone()),
+    ));
+    time.advance(Duration::from_secs(5));
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    time.advance(Duration::from_secs(1));
+    chess.move_piece("e7 e5".to_string()).unwrap();
+    let clock = chess.clock().unwrap();
+    assert_eq!(clock.remaining(Color::White), Duration::from_secs(57));
+    assert_eq!(clock.active(), Some(Color::White));
+    assert!(chess
+        .to_pgn()
+        .contains("[TimeControl \"60+2\"]\n\n1. e4 {[%clk 0:00:57]} 1... e5 {[%clk 0:01:01]} *"));
+    time.advance(Duration::from_secs(60));
+    assert_eq!(chess.status(), GameStatus::OutOfTime(Color::Black));
+    assert!(chess.move_piece("d2 d4".to_string()).is_err());
+    assert!(chess.to_pgn().ends_with("0-1\n"));
+}
+
+#[test]
+fn gives_odds() {
+    let chess = init_handicap_chess(Color::White, &[Handicap::Rook]);
+    assert_eq!(
+        chess.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1"
+    );
+    assert!(chess.to_pgn().contains(
+        "[SetUp \"1\"]\n[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1\"]"
+    ));
+    let chess = init_handicap_chess(Color::White, &[Handicap::PawnAndMove, Handicap::Knight]);
+    assert_eq!(
+        chess.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPP1PP/R1BQKBNR b KQkq - 0 1"
+    );
+    let mut chess = init_handicap_chess(Color::Black, &[Handicap::Queen]);
+    chess.play("e4").unwrap();
+    assert_eq!(
+        chess.to_fen(),
+        "rnb1kbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+
+    let mut chess = init_handicap_chess(Color::Black, &[Handicap::Time(Duration::from_secs(120))]);
+    chess.set_clock(Clock::new(
+        TimeControl::sudden_death(Duration::from_secs(300)),
+        Box::new(ManualTimeSource::new()),
+    ));
+    assert_eq!(
+        chess.clock().unwrap().remaining(Color::White),
+        Duration::from_secs(420)
+    );
+    let pgn = chess.to_pgn();
+    assert!(pgn.contains("[WhiteClock \"0:07:00\"]\n[BlackClock \"0:05:00\"]"));
+    assert!(!pgn.contains("[SetUp"));
+}
+
+#[test]
+fn analyses_variations() {
+    let mut chess = init_standard_chess();
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    let e4 = chess.current_node();
+    chess.move_piece("e7 e5".to_string()).unwrap();
+    chess.move_piece("g1 f3".to_string()).unwrap();
+    chess.go_to(e4).unwrap();
+    assert_eq!(chess.current_player(), Color::Black);
+    chess.move_piece("c7 c5".to_string()).unwrap();
+    let c5 = chess.current_node();
+    chess.set_comment(c5, Some("Sicilian".to_string())).unwrap();
+    chess.add_nag(c5, "!?").unwrap();
+    chess.move_piece("g1 f3".to_string()).unwrap();
+    assert_eq!(
+        chess
+            .history()
+            .iter()
+            .map(|m| &m.san[..])
+            .collect::<Vec<_>>(),
+        vec!["e4", "c5", "Nf3"]
+    );
+    assert!(chess
+        .to_pgn()
+        .ends_with("1. e4 e5 (1... c5 $5 {Sicilian} 2. Nf3) 2. Nf3 *\n"));
+
+    chess.promote_variation(c5).unwrap();
+    assert!(chess
+        .to_pgn()
+        .ends_with("1. e4 c5 $5 {Sicilian} (1... e5 2. Nf3) 2. Nf3 *\n"));
+    chess.delete_variation(c5).unwrap();
+    assert_eq!(chess.current_node(), e4);
+    assert_eq!(chess.get_board()[6][2].is_some(), true);
+    assert!(chess.to_pgn().ends_with("1. e4 e5 2. Nf3 *\n"));
+}
+
+#[test]
+fn navigates_history() {
+    let text = "[Result \"0-1\"]\n\n1. f3 e5 2. g4 (2. e4 Nc6) 2... Qh4# 0-1";
+    let mut chess = ChessGame::from_pgn(text).unwrap();
+    assert_eq!(chess.ply(), 4);
+    assert_eq!(
+        chess.position_at(2).unwrap().to_fen(Color::White, 0, 2),
+        "rnbqkbnr/pppp1ppp/8/4p3/8/5P2/PPPPP1PP/RNBQKBNR w KQkq e6 0 2"
+    );
+    assert!(chess.position_at(5).is_err());
+    chess.go_first().unwrap();
+    assert!(chess.go_back().is_err());
+    chess.go_forward().unwrap();
+    chess.go_forward().unwrap();
+    assert_eq!(chess.current_player(), Color::White);
+    chess.go_last().unwrap();
+    assert_eq!(chess.status(), GameStatus::Checkmate(Color::Black));
+    assert_eq!(chess.checkers(), vec![(7, 3)]);
+    // replaying a promotion follows the move already in the record
+    let mut promoting = ChessGame::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    promoting.play("e7e8q").unwrap();
+    promoting.go_back().unwrap();
+    promoting.play("e7e8q").unwrap();
+    assert!(promoting.to_pgn().ends_with("1. e8=Q *\n"));
+    promoting.go_back().unwrap();
+    promoting.play("e7e8n").unwrap();
+    assert!(promoting.to_pgn().ends_with("1. e8=Q (1. e8=N) *\n"));
+    chess.go_to_ply(3).unwrap();
+    assert_eq!(chess.history().last().unwrap().san, "g4");
+    assert!(chess.go_to_ply(5).is_err());
+
+    chess.go_back().unwrap();
+    chess.branch_from_here().unwrap();
+    assert_eq!(chess.line().len(), 2);
+    chess.play("d4").unwrap();
+    assert!(chess.to_pgn().ends_with("1. f3 e5 2. d4 *\n"));
+}
+
+#[test]
+fn plays_notation() {
+    let mut chess = init_standard_chess();
+    for mov in [
+        "e4", "e7e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O",
+    ]
+    .iter()
+    {
+        chess.play(mov).unwrap();
+    }
+    assert_eq!(
+        chess.to_fen(),
+        "r1bqkbnr/1pp2ppp/p1p5/4p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 1 5"
+    );
+    assert!(chess.play("Qd1").is_err());
+    assert!(chess.play("Nc6").is_err());
+    chess.undo().unwrap();
+    assert_eq!(chess.current_player(), Color::White);
+    assert_eq!(chess.history().len(), 8);
+    chess.play("d4").unwrap();
+    assert_eq!(chess.record().mainline().len(), 9);
+
+    let mut chess = ChessGame::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(chess.play("e7e8").is_err());
+    chess.play("e8=N+").unwrap();
+    assert_eq!(chess.history()[0].san, "e8=N+");
+}
+
+#[test]
+fn reads_pgn() {
+    let text = "[Event \"Test\"]\n[White \"Anna\"]\n[Result \"0-1\"]\n\n\
+                1. f3 e5 {[%clk 0:05:00]} 2. g4 (2. e4 Nc6) 2... Qh4# 0-1";
+    let chess = ChessGame::from_pgn(text).unwrap();
+    assert_eq!(chess.status(), GameStatus::Checkmate(Color::Black));
+    assert_eq!(chess.tag("White"), Some("Anna"));
+    assert_eq!(chess.history()[1].clock, Some(Duration::from_secs(300)));
+    let pgn = chess.to_pgn();
+    assert!(pgn.starts_with("[Event \"Test\"]\n[Site \"?\"]"));
+    assert!(pgn.ends_with("1. f3 e5 {[%clk 0:05:00]} 2. g4 (2. e4 Nc6) 2... Qh4# 0-1\n"));
+    assert_eq!(ChessGame::from_pgn(&pgn).unwrap().to_pgn(), pgn);
+
+    let chess = ChessGame::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 3 40").unwrap();
+    let pgn = chess.to_pgn();
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 3 40\"]"));
+    let endless = format!("4k3/8/8/8/8/8/4P3/4K3 b - - 3 {}", usize::MAX);
+    assert!(ChessGame::from_fen(&endless).is_err());
+    assert!(ChessGame::from_pgn("1. e4 e5 2. Ke3").is_err());
+}
+
+#[test]
+fn ends_by_agreement() {
+    let mut chess = init_standard_chess();
+    chess.play("e4").unwrap();
+    assert!(chess.accept_draw(Color::White).is_err());
+    chess.offer_draw(Color::Black).unwrap();
+    chess.play("e5").unwrap();
+    assert!(chess.accept_draw(Color::White).is_err());
+    chess.offer_draw(Color::White).unwrap();
+    chess.accept_draw(Color::Black).unwrap();
+    assert_eq!(chess.status(), GameStatus::Draw(DrawReason::Agreement));
+    assert!(chess.play("Nf3").is_err());
+
+    let mut chess = init_standard_chess();
+    chess.resign(Color::White).unwrap();
+    assert_eq!(chess.status().result(), "0-1");
+
+    let chess = ChessGame::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(chess.status(), GameStatus::Draw(DrawReason::Stalemate));
+}
+
+#[test]
+fn names_openings() {
+    let mut chess = init_standard_chess();
+    assert!(chess.opening().is_none());
+    chess.play("e4").unwrap();
+    chess.play("c5").unwrap();
+    assert_eq!(chess.opening().unwrap().name, "Sicilian Defense");
+    for mov in ["Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6", "Nc3", "a6", "h3"].iter() {
+        chess.play(mov).unwrap();
+    }
+    let opening = chess.opening().unwrap();
+    assert_eq!(opening.eco, "B90");
+    assert_eq!(opening.name, "Sicilian Defense: Najdorf Variation");
+    let pgn = chess.to_pgn();
+    assert!(pgn.contains("[ECO \"B90\"]\n[Opening \"Sicilian Defense: Najdorf Variation\"]\n"));
+}
+
+#[test]
+fn searches_without_allocating() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let position = ChessBoard::from_fen(kiwipete).unwrap();
+    let before = allocations();
+    assert_eq!(position.board.perft(position.turn, 2), 2039);
+    assert!(position.board.checkers(position.turn).is_empty());
+    assert_eq!(allocations(), before);
+    let limits = SearchLimits {
+        depth: 3,
+        time: None,
+        threads: 1,
+        stop: None,
+    };
+    let before = allocations();
+    let result = search(&position.board, position.turn, &limits).unwrap();
+    // only the lines kept for each iteration
+    assert!(result.nodes > 1000);
+    assert!(allocations() - before < 50);
+}
+
+#[test]
+fn plays_uci_engine() {
+    let uci = EngineSpec::parse(&format!(
+        "uci={} --uci,name=uci,depth=2",
+        env!("CARGO_BIN_EXE_maltebl-chess")
+    ))
+    .unwrap();
+    let mut settings = MatchSettings::new(
+        [EngineSpec::builtin("builtin", 2), uci],
+        vec!["6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string()],
+    );
+    settings.time_control = Some(TimeControl::fischer(
+        Duration::from_secs(3),
+        Duration::from_millis(10),
+    ));
+    settings.max_plies = 10;
+    let report = run_match(&settings, |_, _| ()).unwrap();
+    assert_eq!(report.record.games(), 2);
+    for game in &report.games {
+        assert_eq!(game.result, "1-0");
+        assert_eq!(game.termination, "checkmate");
+    }
+    assert!(report.to_pgn().contains("[Black \"builtin\"]"));
+}
+
+#[test]
+fn stops_infinite_uci_search() {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_maltebl-chess"))
+        .arg("--uci")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = engine.stdin.take().unwrap();
+    let mut output = BufReader::new(engine.stdout.take().unwrap()).lines();
+    writeln!(input, "setoption name Threads value 0\nisready").unwrap();
+    // a bad option is reported and the engine goes on
+    assert!(output.next().unwrap().unwrap().starts_with("info string"));
+    assert_eq!(output.next().unwrap().unwrap(), "readyok");
+    writeln!(input, "position startpos moves e2e4\ngo infinite\nisready").unwrap();
+    // the engine answers while it searches
+    assert_eq!(output.next().unwrap().unwrap(), "readyok");
+    thread::sleep(Duration::from_millis(200));
+    let stopped = Instant::now();
+    writeln!(input, "stop").unwrap();
+    let bestmove = output
+        .map(Result::unwrap)
+        .find(|line| line.starts_with("bestmove"))
+        .unwrap();
+    assert!(stopped.elapsed() < Duration::from_secs(5));
+    let game =
+        ChessGame::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert!(game.parse_move(&bestmove["bestmove ".len()..]).is_ok());
+    writeln!(input, "quit").unwrap();
+    assert!(engine.wait().unwrap().success());
+}
@@ -0,0 +1,207 @@
+/*
+ * C API of the maltebl-chess engine.
+ *
+ * Link with libmaltebl_chess_ffi (built by `cargo build -p maltebl-chess-ffi --release`).
+ * Every function returns an error code. On failure mchess_last_error() describes what
+ * went wrong. Strings handed out by the library are freed with mchess_string_free().
+ */
+
+#ifndef MALTEBL_CHESS_H
+#define MALTEBL_CHESS_H
+
+/* Generated by cbindgen from src/lib.rs, after changing the API update it with
+ * MCHESS_WRITE_HEADER=1 cargo test -p maltebl-chess-ffi --test header */
+
+#include <stdint.h>
+
+typedef enum {
+  MCHESS_ERROR_OK = 0,
+  MCHESS_ERROR_NULL_POINTER = 1,
+  /**
+   * A string that isn't UTF-8, or a limit out of range
+   */
+  MCHESS_ERROR_INVALID_ARGUMENT = 2,
+  MCHESS_ERROR_INVALID_FEN = 3,
+  MCHESS_ERROR_ILLEGAL_MOVE = 4,
+  MCHESS_ERROR_GAME_OVER = 5,
+  /**
+   * There is no legal move to search
+   */
+  MCHESS_ERROR_NO_MOVE = 6,
+  /**
+   * The engine panicked, the game should not be used anymore
+   */
+  MCHESS_ERROR_PANIC = 7,
+} MchessError;
+
+typedef enum {
+  MCHESS_STATUS_ONGOING = 0,
+  MCHESS_STATUS_CHECKMATE = 1,
+  MCHESS_STATUS_KING_CAPTURED = 2,
+  MCHESS_STATUS_OUT_OF_TIME = 3,
+  MCHESS_STATUS_RESIGNED = 4,
+  MCHESS_STATUS_STALEMATE = 5,
+  MCHESS_STATUS_DRAW_BY_AGREEMENT = 6,
+  MCHESS_STATUS_INSUFFICIENT_MATERIAL = 7,
+} MchessStatus;
+
+typedef enum {
+  MCHESS_COLOR_WHITE = 0,
+  MCHESS_COLOR_BLACK = 1,
+  /**
+   * No winner, for games that are drawn or go on
+   */
+  MCHESS_COLOR_NONE = -1,
+} MchessColor;
+
+/**
+ * A game of standard chess, owned by the caller until `mchess_game_free`
+ */
+typedef struct MchessGame MchessGame;
+
+typedef struct {
+  /**
+   * Most half moves to look ahead, at least 1
+   */
+  unsigned int depth;
+  /**
+   * Most milliseconds to search, 0 for no limit
+   */
+  unsigned int time_ms;
+  /**
+   * 0 and 1 both search on the calling thread only
+   */
+  unsigned int threads;
+} MchessSearchLimits;
+
+typedef struct {
+  /**
+   * UCI notation, e.g. `e7e8q`, ended by a zero
+   */
+  char best_move[8];
+  /**
+   * Centipawns for the side to move
+   */
+  int score;
+  unsigned int depth;
+  uint64_t nodes;
+} MchessSearchResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a game from the standard starting position
+ *
+ * # Safety
+ * `out` must point to writable memory for a pointer.
+ */
+MchessError mchess_game_new(MchessGame **out);
+
+/**
+ * Creates a game from a position in FEN
+ *
+ * # Safety
+ * `fen` must be a zero-terminated string and `out` point to writable memory for a pointer.
+ */
+MchessError mchess_game_from_fen(const char *fen, MchessGame **out);
+
+/**
+ * Frees a game, null is ignored
+ *
+ * # Safety
+ * `game` must come from `mchess_game_new` or `mchess_game_from_fen` and not be used after.
+ */
+void mchess_game_free(MchessGame *game);
+
+/**
+ * Frees a string returned by the API, null is ignored
+ *
+ * # Safety
+ * `text` must come from this API and not be used after.
+ */
+void mchess_string_free(char *text);
+
+/**
+ * Plays a move in UCI (`e2e4`, `e7e8q`) or SAN (`Nf3`, `O-O`)
+ *
+ * # Safety
+ * `game` must be a live game and `mov` a zero-terminated string.
+ */
+MchessError mchess_game_play(MchessGame *game, const char *mov);
+
+/**
+ * Legal moves of the side to move in UCI notation, separated by spaces
+ *
+ * # Safety
+ * `game` must be a live game and `out` point to writable memory for a pointer.
+ */
+MchessError mchess_game_legal_moves(const MchessGame *game, char **out);
+
+/**
+ * The current position in FEN
+ *
+ * # Safety
+ * `game` must be a live game and `out` point to writable memory for a pointer.
+ */
+MchessError mchess_game_fen(const MchessGame *game, char **out);
+
+/**
+ * Starts the game over from a position in FEN, the game is unchanged on error.
+ * Earlier searches are forgotten.
+ *
+ * # Safety
+ * `game` must be a live game and `fen` a zero-terminated string.
+ */
+MchessError mchess_game_set_fen(MchessGame *game, const char *fen);
+
+/**
+ * Whether the game goes on, and if not how it ended and who won
+ *
+ * # Safety
+ * `game` must be a live game, `status` and `winner` point to writable memory.
+ */
+MchessError mchess_game_status(const MchessGame *game, MchessStatus *status, MchessColor *winner);
+
+/**
+ * The side to move
+ *
+ * # Safety
+ * `game` must be a live game and `color` point to writable memory.
+ */
+MchessError mchess_game_side_to_move(const MchessGame *game, MchessColor *color);
+
+/**
+ * Searches the best move for the side to move, without playing it. What the search
+ * finds is kept for the next searches of the same game.
+ *
+ * # Safety
+ * `game` must be a live game, `limits` readable and `result` writable.
+ */
+MchessError mchess_search(const MchessGame *game,
+                          const MchessSearchLimits *limits,
+                          MchessSearchResult *result);
+
+/**
+ * Panics inside the library on purpose, to check that a panic comes back as
+ * `MCHESS_ERROR_PANIC` instead of unwinding into the caller
+ */
+MchessError mchess_panic_test(void);
+
+/**
+ * Message of the last error on this thread, empty after a success. The string belongs
+ * to the library and stays valid until the next call on the same thread.
+ */
+const char *mchess_last_error(void);
+
+/**
+ * Short description of an error code, a static string
+ */
+const char *mchess_error_name(int error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MALTEBL_CHESS_H */
@@ -0,0 +1,430 @@
+//! C API of the chess engine, see `include/maltebl_chess.h`.
+//!
+//! Every function returns an `MchessError` code, the message of the last error on the
+//! calling thread is kept for `mchess_last_error`. Panics are caught at the boundary and
+//! reported as `MCHESS_ERROR_PANIC`.
+use maltebl_chess::{chess_game::*, piece_logic::Color, search::*};
+use std::any::Any;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::Duration;
+
+/// A game of standard chess, owned by the caller until `mchess_game_free`
+pub struct MchessGame {
+    game: ChessGame,
+    /// Keeps the transposition table between searches of the game
+    engine: Engine,
+}
+
+impl MchessGame {
+    fn new(game: ChessGame) -> Self {
+        MchessGame {
+            game,
+            engine: Engine::new(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MchessError {
+    Ok = 0,
+    NullPointer = 1,
+    /// A string that isn't UTF-8, or a limit out of range
+    InvalidArgument = 2,
+    InvalidFen = 3,
+    IllegalMove = 4,
+    GameOver = 5,
+    /// There is no legal move to search
+    NoMove = 6,
+    /// The engine panicked, the game should not be used anymore
+    Panic = 7,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MchessColor {
+    White = 0,
+    Black = 1,
+    /// No winner, for games that are drawn or go on
+    None = -1,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MchessStatus {
+    Ongoing = 0,
+    Checkmate = 1,
+    KingCaptured = 2,
+    OutOfTime = 3,
+    Resigned = 4,
+    Stalemate = 5,
+    DrawByAgreement = 6,
+    InsufficientMaterial = 7,
+}
+
+#[repr(C)]
+pub struct MchessSearchLimits {
+    /// Most half moves to look ahead, at least 1
+    pub depth: c_uint,
+    /// Most milliseconds to search, 0 for no limit
+    pub time_ms: c_uint,
+    /// 0 and 1 both search on the calling thread only
+    pub threads: c_uint,
+}
+
+#[repr(C)]
+pub struct MchessSearchResult {
+    /// UCI notation, e.g. `e7e8q`, ended by a zero
+    pub best_move: [c_char; 8],
+    /// Centipawns for the side to move
+    pub score: c_int,
+    pub depth: c_uint,
+    pub nodes: u64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+type Failure = (MchessError, String);
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Runs `body`, turning its error or panic into a code and the last error message
+fn guard(body: impl FnOnce() -> Result<(), Failure>) -> MchessError {
+    let (code, message) = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => (MchessError::Ok, String::new()),
+        Ok(Err(failure)) => failure,
+        Err(payload) => (
+            MchessError::Panic,
+            format!("panic in the engine: {}", panic_message(payload)),
+        ),
+    };
+    set_last_error(&message);
+    code
+}
+
+fn null() -> Failure {
+    (MchessError::NullPointer, "null pointer".to_string())
+}
+
+unsafe fn game_ref<'a>(game: *const MchessGame) -> Result<&'a ChessGame, Failure> {
+    game.as_ref().map(|game| &game.game).ok_or_else(null)
+}
+
+unsafe fn game_mut<'a>(game: *mut MchessGame) -> Result<&'a mut ChessGame, Failure> {
+    game.as_mut().map(|game| &mut game.game).ok_or_else(null)
+}
+
+unsafe fn read_str<'a>(text: *const c_char) -> Result<&'a str, Failure> {
+    if text.is_null() {
+        return Err(null());
+    }
+    CStr::from_ptr(text).to_str().map_err(|_| {
+        (
+            MchessError::InvalidArgument,
+            "string is not UTF-8".to_string(),
+        )
+    })
+}
+
+/// Hands a string to the caller, who frees it with `mchess_string_free`
+unsafe fn write_string(text: String, out: *mut *mut c_char) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(null());
+    }
+    *out = CString::new(text).unwrap_or_default().into_raw();
+    Ok(())
+}
+
+fn invalid_fen(error: String) -> Failure {
+    (MchessError::InvalidFen, error)
+}
+
+/// Creates a game from the standard starting position
+///
+/// # Safety
+/// `out` must point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_new(out: *mut *mut MchessGame) -> MchessError {
+    guard(|| {
+        if out.is_null() {
+            return Err(null());
+        }
+        *out = Box::into_raw(Box::new(MchessGame::new(init_standard_chess())));
+        Ok(())
+    })
+}
+
+/// Creates a game from a position in FEN
+///
+/// # Safety
+/// `fen` must be a zero-terminated string and `out` point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_from_fen(
+    fen: *const c_char,
+    out: *mut *mut MchessGame,
+) -> MchessError {
+    guard(|| {
+        if out.is_null() {
+            return Err(null());
+        }
+        let game = ChessGame::from_fen(read_str(fen)?).map_err(invalid_fen)?;
+        *out = Box::into_raw(Box::new(MchessGame::new(game)));
+        Ok(())
+    })
+}
+
+/// Frees a game, null is ignored
+///
+/// # Safety
+/// `game` must come from `mchess_game_new` or `mchess_game_from_fen` and not be used after.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_free(game: *mut MchessGame) {
+    if !game.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(game))));
+    }
+}
+
+/// Frees a string returned by the API, null is ignored
+///
+/// # Safety
+/// `text` must come from this API and not be used after.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Plays a move in UCI (`e2e4`, `e7e8q`) or SAN (`Nf3`, `O-O`)
+///
+/// # Safety
+/// `game` must be a live game and `mov` a zero-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_play(
+    game: *mut MchessGame,
+    mov: *const c_char,
+) -> MchessError {
+    guard(|| {
+        let game = game_mut(game)?;
+        let mov = read_str(mov)?;
+        if game.status() != GameStatus::Ongoing {
+            return Err((MchessError::GameOver, "the game is over".to_string()));
+        }
+        game.play(mov)
+            .map(|_| ())
+            .map_err(|error| (MchessError::IllegalMove, error))
+    })
+}
+
+/// Legal moves of the side to move in UCI notation, separated by spaces
+///
+/// # Safety
+/// `game` must be a live game and `out` point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_legal_moves(
+    game: *const MchessGame,
+    out: *mut *mut c_char,
+) -> MchessError {
+    guard(|| {
+        let game = game_ref(game)?;
+        let moves: Vec<String> = game
+            .chess_board()
+            .legal_moves(game.current_player())
+            .iter()
+            .map(|mov| mov.to_coordinates())
+            .collect();
+        write_string(moves.join(" "), out)
+    })
+}
+
+/// The current position in FEN
+///
+/// # Safety
+/// `game` must be a live game and `out` point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_fen(
+    game: *const MchessGame,
+    out: *mut *mut c_char,
+) -> MchessError {
+    guard(|| write_string(game_ref(game)?.to_fen(), out))
+}
+
+/// Starts the game over from a position in FEN, the game is unchanged on error.
+/// Earlier searches are forgotten.
+///
+/// # Safety
+/// `game` must be a live game and `fen` a zero-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_set_fen(
+    game: *mut MchessGame,
+    fen: *const c_char,
+) -> MchessError {
+    guard(|| {
+        let game = game.as_mut().ok_or_else(null)?;
+        game.game = ChessGame::from_fen(read_str(fen)?).map_err(invalid_fen)?;
+        game.engine.clear();
+        Ok(())
+    })
+}
+
+/// Whether the game goes on, and if not how it ended and who won
+///
+/// # Safety
+/// `game` must be a live game, `status` and `winner` point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_status(
+    game: *const MchessGame,
+    status: *mut MchessStatus,
+    winner: *mut MchessColor,
+) -> MchessError {
+    guard(|| {
+        let game = game_ref(game)?;
+        if status.is_null() || winner.is_null() {
+            return Err(null());
+        }
+        let (kind, color) = match game.status() {
+            GameStatus::Ongoing => (MchessStatus::Ongoing, None),
+            GameStatus::Checkmate(color) => (MchessStatus::Checkmate, Some(color)),
+            GameStatus::KingCaptured(color) => (MchessStatus::KingCaptured, Some(color)),
+            GameStatus::OutOfTime(color) => (MchessStatus::OutOfTime, Some(color)),
+            GameStatus::Resigned(color) => (MchessStatus::Resigned, Some(color)),
+            GameStatus::Draw(DrawReason::Stalemate) => (MchessStatus::Stalemate, None),
+            GameStatus::Draw(DrawReason::Agreement) => (MchessStatus::DrawByAgreement, None),
+            GameStatus::Draw(DrawReason::InsufficientMaterial) => {
+                (MchessStatus::InsufficientMaterial, None)
+            }
+        };
+        *status = kind;
+        *winner = color.map_or(MchessColor::None, to_color);
+        Ok(())
+    })
+}
+
+/// The side to move
+///
+/// # Safety
+/// `game` must be a live game and `color` point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_game_side_to_move(
+    game: *const MchessGame,
+    color: *mut MchessColor,
+) -> MchessError {
+    guard(|| {
+        let game = game_ref(game)?;
+        if color.is_null() {
+            return Err(null());
+        }
+        *color = to_color(game.current_player());
+        Ok(())
+    })
+}
+
+fn to_color(color: Color) -> MchessColor {
+    match color {
+        Color::White => MchessColor::White,
+        Color::Black => MchessColor::Black,
+    }
+}
+
+/// Searches the best move for the side to move, without playing it. What the search
+/// finds is kept for the next searches of the same game.
+///
+/// # Safety
+/// `game` must be a live game, `limits` readable and `result` writable.
+#[no_mangle]
+pub unsafe extern "C" fn mchess_search(
+    game: *const MchessGame,
+    limits: *const MchessSearchLimits,
+    result: *mut MchessSearchResult,
+) -> MchessError {
+    guard(|| {
+        let handle = game.as_ref().ok_or_else(null)?;
+        let game = &handle.game;
+        let limits = limits.as_ref().ok_or_else(null)?;
+        let result = result.as_mut().ok_or_else(null)?;
+        if limits.depth == 0 {
+            return Err((
+                MchessError::InvalidArgument,
+                "the depth must be at least 1".to_string(),
+            ));
+        }
+        let limits = SearchLimits {
+            depth: limits.depth as usize,
+            time: Some(limits.time_ms)
+                .filter(|&time| time > 0)
+                .map(|time| Duration::from_millis(time as u64)),
+            threads: limits.threads.max(1) as usize,
+            stop: None,
+        };
+        let found = handle
+            .engine
+            .search(game.chess_board(), game.current_player(), &limits)
+            .ok_or_else(|| (MchessError::NoMove, "there is no legal move".to_string()))?;
+        let coordinates = found.best_move.to_coordinates();
+        result.best_move = [0; 8];
+        for (slot, byte) in result.best_move.iter_mut().zip(coordinates.bytes().take(7)) {
+            *slot = byte as c_char;
+        }
+        result.score = found.score;
+        result.depth = found.depth as c_uint;
+        result.nodes = found.nodes;
+        Ok(())
+    })
+}
+
+/// Panics inside the library on purpose, to check that a panic comes back as
+/// `MCHESS_ERROR_PANIC` instead of unwinding into the caller
+#[no_mangle]
+pub extern "C" fn mchess_panic_test() -> MchessError {
+    guard(|| panic!("mchess_panic_test was called"))
+}
+
+/// Message of the last error on this thread, empty after a success. The string belongs
+/// to the library and stays valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn mchess_last_error() -> *const c_char {
+    LAST_ERROR
+        .try_with(|last| last.borrow().as_ptr())
+        .unwrap_or(ptr::null())
+}
+
+/// Short description of an error code, a static string
+#[no_mangle]
+pub extern "C" fn mchess_error_name(error: c_int) -> *const c_char {
+    const NAMES: [&[u8]; 8] = [
+        b"ok\0",
+        b"null pointer\0",
+        b"invalid argument\0",
+        b"invalid FEN\0",
+        b"illegal move\0",
+        b"game over\0",
+        b"no legal move\0",
+        b"panic\0",
+    ];
+    let name = usize::try_from(error)
+        .ok()
+        .and_then(|index| NAMES.get(index))
+        .copied()
+        .unwrap_or(b"unknown error\0");
+    name.as_ptr() as *const c_char
+}
@@ -0,0 +1,116 @@
+/* Exercises the C API, exits with 1 at the first failed check */
+#include "maltebl_chess.h"
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+static int checks = 0;
+
+#define CHECK(condition)                                                       \
+    do {                                                                       \
+        checks++;                                                              \
+        if (!(condition)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s (last error: %s)\n",     \
+                    __FILE__, __LINE__, #condition, mchess_last_error());      \
+            exit(1);                                                           \
+        }                                                                      \
+    } while (0)
+
+static void plays_moves(void) {
+    MchessGame *game = NULL;
+    CHECK(mchess_game_new(&game) == MCHESS_ERROR_OK);
+    CHECK(game != NULL);
+
+    char *moves = NULL;
+    CHECK(mchess_game_legal_moves(game, &moves) == MCHESS_ERROR_OK);
+    CHECK(strstr(moves, "e2e4") != NULL);
+    CHECK(strstr(moves, "g1f3") != NULL);
+    mchess_string_free(moves);
+
+    CHECK(mchess_game_play(game, "e2e4") == MCHESS_ERROR_OK);
+    CHECK(mchess_game_play(game, "e5") == MCHESS_ERROR_OK);
+    CHECK(mchess_game_play(game, "Nf3") == MCHESS_ERROR_OK);
+    CHECK(mchess_game_play(game, "e2e4") == MCHESS_ERROR_ILLEGAL_MOVE);
+    CHECK(strlen(mchess_last_error()) > 0);
+
+    MchessColor color;
+    CHECK(mchess_game_side_to_move(game, &color) == MCHESS_ERROR_OK);
+    CHECK(color == MCHESS_COLOR_BLACK);
+
+    char *fen = NULL;
+    CHECK(mchess_game_fen(game, &fen) == MCHESS_ERROR_OK);
+    CHECK(strcmp(fen, "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2") == 0);
+    mchess_string_free(fen);
+    mchess_game_free(game);
+}
+
+static void reports_status(void) {
+    MchessGame *game = NULL;
+    CHECK(mchess_game_from_fen("not a fen", &game) == MCHESS_ERROR_INVALID_FEN);
+    CHECK(game == NULL);
+    CHECK(mchess_game_new(&game) == MCHESS_ERROR_OK);
+    CHECK(mchess_game_set_fen(game, "8/8 w") == MCHESS_ERROR_INVALID_FEN);
+
+    const char *fools_mate[] = {"f3", "e5", "g4", "Qh4#"};
+    for (int i = 0; i < 4; i++) {
+        CHECK(mchess_game_play(game, fools_mate[i]) == MCHESS_ERROR_OK);
+    }
+    MchessStatus status;
+    MchessColor winner;
+    CHECK(mchess_game_status(game, &status, &winner) == MCHESS_ERROR_OK);
+    CHECK(status == MCHESS_STATUS_CHECKMATE);
+    CHECK(winner == MCHESS_COLOR_BLACK);
+    CHECK(mchess_game_play(game, "a3") == MCHESS_ERROR_GAME_OVER);
+
+    CHECK(mchess_game_set_fen(game, "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1") == MCHESS_ERROR_OK);
+    CHECK(mchess_game_status(game, &status, &winner) == MCHESS_ERROR_OK);
+    CHECK(status == MCHESS_STATUS_STALEMATE);
+    CHECK(winner == MCHESS_COLOR_NONE);
+    mchess_game_free(game);
+}
+
+static void searches(void) {
+    MchessGame *game = NULL;
+    CHECK(mchess_game_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &game) == MCHESS_ERROR_OK);
+    MchessSearchLimits limits = {3, 0, 1};
+    MchessSearchResult result;
+    CHECK(mchess_search(game, &limits, &result) == MCHESS_ERROR_OK);
+    CHECK(strcmp(result.best_move, "a1a8") == 0);
+    CHECK(result.depth >= 1);
+    CHECK(result.nodes > 0);
+
+    limits.depth = 0;
+    CHECK(mchess_search(game, &limits, &result) == MCHESS_ERROR_INVALID_ARGUMENT);
+    mchess_game_free(game);
+}
+
+static void rejects_bad_arguments(void) {
+    CHECK(mchess_game_new(NULL) == MCHESS_ERROR_NULL_POINTER);
+    CHECK(mchess_game_play(NULL, "e2e4") == MCHESS_ERROR_NULL_POINTER);
+    CHECK(strcmp(mchess_error_name(MCHESS_ERROR_ILLEGAL_MOVE), "illegal move") == 0);
+    CHECK(strcmp(mchess_error_name(42), "unknown error") == 0);
+    mchess_game_free(NULL);
+    mchess_string_free(NULL);
+}
+
+static void contains_panics(void) {
+    CHECK(mchess_panic_test() == MCHESS_ERROR_PANIC);
+    CHECK(strstr(mchess_last_error(), "mchess_panic_test was called") != NULL);
+    CHECK(strcmp(mchess_error_name(MCHESS_ERROR_PANIC), "panic") == 0);
+    /* the library still works after a panic */
+    MchessGame *game = NULL;
+    CHECK(mchess_game_new(&game) == MCHESS_ERROR_OK);
+    CHECK(strlen(mchess_last_error()) == 0);
+    mchess_game_free(game);
+}
+
+int main(void) {
+    plays_moves();
+    reports_status();
+    searches();
+    rejects_bad_arguments();
+    contains_panics();
+    printf("%d checks passed\n", checks);
+    return 0;
+}
@@ -0,0 +1,38 @@
+//! Builds `api_test.c` against the static library and runs it
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[cfg(target_os = "linux")]
+const SYSTEM_LIBS: &[&str] = &["-lpthread", "-ldl", "-lm"];
+#[cfg(target_os = "macos")]
+const SYSTEM_LIBS: &[&str] = &["-lSystem", "-lc", "-lm"];
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn runs_c_program() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the test binary lives in target/<profile>/deps, next to the library
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let program = deps.join("mchess_api_test");
+    let compiled = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest.join("tests/api_test.c"))
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(deps.join("libmaltebl_chess_ffi.a"))
+        .args(SYSTEM_LIBS)
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("a C compiler is needed");
+    assert!(compiled.success());
+    let output = Command::new(&program).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("checks passed"));
+}
@@ -0,0 +1,26 @@
+//! Checks that `include/maltebl_chess.h` is what cbindgen makes of the API,
+//! with `MCHESS_WRITE_HEADER` set it is written instead
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn header_is_up_to_date() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(manifest.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_src(manifest.join("src/lib.rs"))
+        .with_config(config)
+        .generate()
+        .expect("cbindgen can't read the API")
+        .write(&mut generated);
+    let header = manifest.join("include/maltebl_chess.h");
+    if env::var_os("MCHESS_WRITE_HEADER").is_some() {
+        fs::write(&header, &generated).unwrap();
+    }
+    assert!(
+        fs::read(&header).unwrap() == generated,
+        "include/maltebl_chess.h is out of date, run the test with MCHESS_WRITE_HEADER=1"
+    );
+}
@@ -3,7 +3,11 @@ use std::sync::Arc;
 
 use crate::state::AppState;
 
-use maltebl_chess::{chess_game::ChessGame, piece_logic::Color};
+use maltebl_chess::{
+    board_logic::Board,
+    chess_game::{ChessGame, Variant},
+    piece_logic::Color,
+};
 
 use druid::*;
 
@@ -11,57 +15,85 @@ pub mod action {
     use druid::Selector;
 
     pub const MAKE_MOVE: Selector<String> = Selector::new("make_move");
-    pub const MAKE_MOVE_FROM_NET: Selector<String> = Selector::new("make_move_from_net");
+    /// a move the game runner played, in coordinates
+    pub const MOVE_PLAYED: Selector<String> = Selector::new("move_played");
 }
 
+/// Shows the game played by the `GameRunner` and passes on moves made in the window
 pub struct Delegate {
+    /// copy of the runner's game, kept in step through MOVE_PLAYED
     pub game: ChessGame,
-    pub single_player: Option<Color>,
-    pub net_sender: Option<Sender<String>>,
+    /// input of the human players at this computer, white first
+    pub local_input: [Option<Sender<String>>; 2],
 }
 
 impl Delegate {
-    fn attempt_move_from_ui(&mut self, command: &str) -> Result<String, String> {
-        if let Some(local_player) = self.single_player {
-            if local_player != self.game.current_player() {
-                return Err("not your (local player) turn".to_owned());
-            }
-
-            let sender = self
-                .net_sender
-                .as_ref()
-                .ok_or_else(|| "no net_sender".to_owned())?;
-            sender
-                .send(command.to_owned())
-                .map_err(|_| "unable to send".to_owned())?;
+    /// the side shown in dark chess: the only local player, or else the one to move
+    fn viewer(&self) -> Color {
+        match &self.local_input {
+            [Some(_), None] => Color::White,
+            [None, Some(_)] => Color::Black,
+            _ => self.game.current_player(),
         }
+    }
 
-        // TODO: + send move to network + only apply if ok
-        // for now: just assume everything went smooth
-
-        self.game.move_piece(command.to_owned())
+    fn input_of(&self, color: Color) -> Option<&Sender<String>> {
+        let index = match color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        self.local_input[index].as_ref()
     }
-    // if err: send decline, if ok; pass along
-    fn attempt_move_from_network(&mut self, command: &str) -> Result<String, String> {
-        let local_player = self
-            .single_player
-            .ok_or_else(|| "single_player_white not set".to_owned())?;
-        if local_player == self.game.current_player() {
-            return Err("local players turn".to_owned());
-        }
 
-        // TODO: validate move + answer ok/decline + perform if ok
-        // for now: just assume everything went smooth
+    /// show the board, or in dark chess only the part the local player can see
+    pub fn refresh_board(&self, data: &mut AppState) {
+        if self.game.variant() != Variant::Dark {
+            data.board = Arc::new(self.game.get_board());
+            data.opening = self
+                .game
+                .opening()
+                .map(|opening| format!("{} {}", opening.eco, opening.name));
+            return;
+        }
+        let viewer = self.viewer();
+        let mut board: Board = Default::default();
+        let mut hidden = [[true; 8]; 8];
+        for (y, row) in self.game.get_redacted_board(viewer).iter().enumerate() {
+            for (x, space) in row.iter().enumerate() {
+                if let Some(piece) = space {
+                    board[y][x] = piece.clone();
+                    hidden[y][x] = false;
+                }
+            }
+        }
+        data.board = Arc::new(board);
+        data.hidden = Arc::new(hidden);
+    }
 
-        let sender = self
-            .net_sender
-            .as_ref()
-            .ok_or_else(|| "no net_sender".to_owned())?;
-        sender
-            .send("ok".to_owned())
-            .map_err(|_| "unable to send".to_owned())?;
+    /// hands a legal move to the local player to move, pawns on the last rank become queens
+    fn attempt_move_from_ui(&self, command: &str) -> Result<(), String> {
+        let input = self
+            .input_of(self.game.current_player())
+            .ok_or_else(|| "not your (local player) turn".to_owned())?;
+        let mov = self.game.parse_move(command).or_else(|error| {
+            self.game
+                .parse_move(&format!("{}q", command))
+                .map_err(|_| error)
+        })?;
+        input
+            .send(mov.to_coordinates())
+            .map_err(|_| "the game has ended".to_owned())
+    }
 
-        self.game.move_piece(command.to_owned())
+    fn apply_played_move(&mut self, coordinates: &str) -> Result<String, String> {
+        let mover = self.game.current_player();
+        let message = self.game.play(coordinates)?;
+        // the opponent's move is hidden in dark chess
+        let hidden = self.game.variant() == Variant::Dark && self.input_of(mover).is_none();
+        if hidden && !message.starts_with("Game is over") {
+            return Ok("Your turn".to_owned());
+        }
+        Ok(message)
     }
 }
 
@@ -75,18 +107,16 @@ impl AppDelegate<AppState> for Delegate {
         _env: &Env,
     ) -> bool {
         if let Some(command) = cmd.get(action::MAKE_MOVE) {
-            data.message = Some(match self.attempt_move_from_ui(command) {
-                Err(txt) => txt,
-                Ok(txt) => txt,
-            });
-            data.board = Arc::new(self.game.get_board());
+            if let Err(txt) = self.attempt_move_from_ui(command) {
+                data.message = Some(txt);
+            }
             return true;
-        } else if let Some(command) = cmd.get(action::MAKE_MOVE_FROM_NET) {
-            data.message = Some(match self.attempt_move_from_network(command) {
-                Err(_txt) => "".to_owned(),
+        } else if let Some(coordinates) = cmd.get(action::MOVE_PLAYED) {
+            data.message = Some(match self.apply_played_move(coordinates) {
+                Err(txt) => txt,
                 Ok(txt) => txt,
             });
-            data.board = Arc::new(self.game.get_board());
+            self.refresh_board(data);
             return true;
         }
         true
@@ -1,6 +1,6 @@
 use std::io::prelude::*;
 use std::net::{TcpListener, TcpStream};
-use std::sync::mpsc;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::Duration;
 
@@ -14,11 +14,11 @@ use crate::delegate::{action, Delegate};
 use crate::state::AppState;
 use crate::ui::main_ui;
 
-use maltebl_chess::{chess_game::*, piece_logic::Color, to_coords, to_notation};
+use maltebl_chess::{chess_game::*, piece_logic::Color, player::*, to_coords, to_notation};
 use tcp_chess_com::connection_lib as con;
 
 use clap::Clap;
-use druid::{AppLauncher, Env, ExtEventSink, PlatformError, WindowDesc};
+use druid::{AppLauncher, Env, PlatformError, WindowDesc};
 
 #[derive(Clap)]
 #[clap(
@@ -32,6 +32,9 @@ struct Opts {
     /// used with net_listen (white, first move), ie. --net_connect=0.0.0.0:8032
     #[clap(long)]
     net_connect: Option<String>,
+    /// play dark chess, where you only see what your pieces can reach
+    #[clap(long)]
+    dark: bool,
 }
 
 /**
@@ -67,9 +70,13 @@ white.network <-(>) black.network // black connects to white listener with tcp
 gui (<)-> delegate // gui submits commands to delegate, which may change data gui uses
     -> ctx.submit_command
     <- mutate data
-net (<)-> delegate // net may ask delegate to validate move, which responds back later
+runner -> delegate // the runner plays the game between the players, the delegate shows it
     -> event_sink.submit_command
-    <- net_sender/delegate_tx.send
+delegate -> human player // moves made in the gui
+    -> local_input.send
+net (<)-> remote player // net asks the remote player to validate a move, which responds back later
+    -> peer_tx.send
+    <- to_peer.send
 
 net <-> tcp // (unnecessary?) intermediate for logic
     -> tcp_tx
@@ -81,31 +88,43 @@ fn main() -> Result<(), PlatformError> {
     let opts: Opts = Opts::parse();
     let networked = opts.net_connect.is_some() || opts.net_listen.is_some();
 
-    let game = init_standard_chess();
-    let data = AppState::new(game.get_board());
+    let game = new_game(opts.dark);
+    let mut data = AppState::new(game.get_board());
 
-    let (delegate_tx, delegate_rx) = mpsc::channel();
-    let single_player = match networked {
+    let local_color = match networked {
         false => None,
         true => Some(match opts.net_listen.is_some() {
             true => Color::White,
             false => Color::Black,
         }),
     };
-    let net_sender = match networked {
-        false => None,
-        true => Some(delegate_tx),
-    };
-    let delegate = Delegate {
-        game,
-        single_player,
-        net_sender,
-    };
+    let mut players: Vec<Box<dyn Player>> = Vec::new();
+    let mut local_input = [None, None];
+    let mut peer = None;
+    for (index, &color) in [Color::White, Color::Black].iter().enumerate() {
+        if local_color.is_none_or(|local| local == color) {
+            let (input_tx, input_rx) = mpsc::channel();
+            local_input[index] = Some(input_tx);
+            players.push(Box::new(HumanPlayer::new(input_rx)));
+        } else {
+            let (to_peer, peer_rx) = mpsc::channel();
+            let (peer_tx, from_peer) = mpsc::channel();
+            peer = Some((peer_rx, peer_tx));
+            players.push(Box::new(RemotePlayer::new(to_peer, from_peer)));
+        }
+    }
+    let delegate = Delegate { game, local_input };
+    delegate.refresh_board(&mut data);
 
     let window = WindowDesc::new(main_ui)
         .title(|data: &AppState, _env: &Env| {
+            let opening = match &data.opening {
+                Some(opening) => format!("- {} ", opening),
+                None => "".to_owned(),
+            };
             format!(
-                "Chess {}",
+                "Chess {}{}",
+                opening,
                 match &data.message {
                     Some(t) => t.to_owned(),
                     None => "".to_owned(),
@@ -118,18 +137,50 @@ fn main() -> Result<(), PlatformError> {
     let app = AppLauncher::with_window(window);
     let event_sink = app.get_external_handle();
 
-    if networked {
+    if let Some((peer_rx, peer_tx)) = peer {
         println!("Networked.");
-        setup_networking(&opts, event_sink, delegate_rx);
+        setup_networking(&opts, peer_rx, peer_tx);
     }
 
+    // the runner plays its own copy of the game, the delegate follows along
+    let dark = opts.dark;
+    let black = players.pop().unwrap();
+    let white = players.pop().unwrap();
+    thread::spawn(move || {
+        let mut runner = GameRunner::new(new_game(dark), white, black);
+        let status = runner.run(|game, _| {
+            if let Some(played) = game.history().last() {
+                let coordinates = played.to_move().to_coordinates();
+                if let Some(err) = event_sink
+                    .submit_command(action::MOVE_PLAYED, coordinates, None)
+                    .err()
+                {
+                    println!("ERR: {:?}", err);
+                }
+            }
+        });
+        // a game that can't go on ends without a result
+        if status == GameStatus::Ongoing {
+            println!("The game was aborted");
+        }
+    });
+
     app.delegate(delegate).launch(data)?;
 
     Ok(())
 }
 
-fn setup_networking(opts: &Opts, event_sink: ExtEventSink, delegate_rx: mpsc::Receiver<String>) {
+fn new_game(dark: bool) -> ChessGame {
+    if dark {
+        init_dark_chess()
+    } else {
+        init_standard_chess()
+    }
+}
+
+fn setup_networking(opts: &Opts, peer_rx: Receiver<PeerMessage>, peer_tx: Sender<PeerMessage>) {
     let (net_tx, net_rx) = mpsc::channel::<con::Message>();
+    let dark = opts.dark;
 
     if let Some(addr) = opts.net_connect.as_ref() {
         let addr = addr.clone();
@@ -141,11 +192,11 @@ fn setup_networking(opts: &Opts, event_sink: ExtEventSink, delegate_rx: mpsc::Re
             println!("Awaiting remote's players initial move.");
 
             loop {
-                if let Some(err) = await_receive_move(&mut stream, &event_sink, &net_rx).err() {
+                if let Some(err) = await_receive_move(&mut stream, &peer_tx, &net_rx, dark).err() {
                     println!("ERR: {:?}", err);
                     break;
                 };
-                if let Some(err) = await_transmit_move(&mut stream, &event_sink, &net_rx).err() {
+                if let Some(err) = await_transmit_move(&mut stream, &peer_tx, &net_rx, dark).err() {
                     println!("ERR: {:?}", err);
                     break;
                 };
@@ -166,13 +217,16 @@ fn setup_networking(opts: &Opts, event_sink: ExtEventSink, delegate_rx: mpsc::Re
                 println!("Make a move as initial message.");
 
                 loop {
-                    if let Some(err) = await_transmit_move(&mut stream, &event_sink, &net_rx).err()
+                    if let Some(err) =
+                        await_transmit_move(&mut stream, &peer_tx, &net_rx, dark).err()
                     {
                         println!("ERR: {:?}", err);
                         break;
                     };
 
-                    if let Some(err) = await_receive_move(&mut stream, &event_sink, &net_rx).err() {
+                    if let Some(err) =
+                        await_receive_move(&mut stream, &peer_tx, &net_rx, dark).err()
+                    {
                         println!("ERR: {:?}", err);
                         break;
                     };
@@ -181,24 +235,34 @@ fn setup_networking(opts: &Opts, event_sink: ExtEventSink, delegate_rx: mpsc::Re
         });
     }
 
-    // input from delegate
+    // input from the remote player
     thread::spawn(move || {
-        for received in delegate_rx {
-            println!("from delegate: {:?}", received);
+        for received in peer_rx {
+            if !dark {
+                println!("from remote player: {:?}", received);
+            }
             thread::sleep(Duration::from_secs(1));
 
-            let message = match &received[..] {
-                "ok" => con::Message::Accept,
-                _ => {
-                    // TODO: error handling
-                    let mut parts = received
-                        .split(' ')
-                        .map(|a| to_coords(a.to_owned()).unwrap())
-                        .map(|(x, y)| con::Position::new(x as u8, y as u8));
-                    let origin = parts.next().unwrap();
-                    let target = parts.next().unwrap();
-
-                    con::Message::Move(con::Move::Standard { origin, target })
+            let message = match received {
+                PeerMessage::Accept => con::Message::Accept,
+                PeerMessage::Decline => con::Message::Decline,
+                PeerMessage::Move(coordinates) => {
+                    // the remote player sends legal moves in coordinates, e.g. e2e4 or e7e8q
+                    let square = |range: std::ops::Range<usize>| {
+                        let (x, y) = to_coords(coordinates[range].to_owned()).unwrap();
+                        con::Position::new(x as u8, y as u8)
+                    };
+                    let origin = square(0..2);
+                    let target = square(2..4);
+
+                    match coordinates.chars().nth(4) {
+                        Some(letter) => con::Message::Move(con::Move::Promotion {
+                            origin,
+                            target,
+                            kind: promotion_kind(letter),
+                        }),
+                        None => con::Message::Move(con::Move::Standard { origin, target }),
+                    }
                 }
             };
 
@@ -207,22 +271,66 @@ fn setup_networking(opts: &Opts, event_sink: ExtEventSink, delegate_rx: mpsc::Re
     });
 }
 
+fn promotion_kind(letter: char) -> con::PieceType {
+    match letter {
+        'r' => con::PieceType::Rook,
+        'b' => con::PieceType::Bishop,
+        'n' => con::PieceType::Knight,
+        _ => con::PieceType::Queen,
+    }
+}
+
+/// Move of the peer in the coordinates of `PeerMessage`, castling isn't sent by this client
+fn move_command(mov: &con::Move) -> Option<String> {
+    let square =
+        |position: &con::Position| to_notation((position.x as usize, position.y as usize)).ok();
+    match mov {
+        con::Move::Standard { origin, target } | con::Move::EnPassant { origin, target } => {
+            Some(format!("{}{}", square(origin)?, square(target)?))
+        }
+        con::Move::Promotion {
+            origin,
+            target,
+            kind,
+        } => {
+            let letter = match kind {
+                con::PieceType::Knight => 'n',
+                con::PieceType::Bishop => 'b',
+                con::PieceType::Rook => 'r',
+                con::PieceType::Queen => 'q',
+            };
+            Some(format!("{}{}{}", square(origin)?, square(target)?, letter))
+        }
+        con::Move::CastleKingside | con::Move::CastleQueenside => None,
+    }
+}
+
 enum ResponseKind {
     Noop,
     Message(con::Message),
     AwaitDelegate,
 }
 
+/// in dark chess the moves must not show up in the terminal either
+fn describe(message: &con::Message, dark: bool) -> String {
+    if dark {
+        format!("{:?}", message.get_type())
+    } else {
+        format!("{:?}", message)
+    }
+}
+
 // TODO: clean up message order flow to accept any order
 //  currently will be in an inconsistent/locked state if unexpected order
 
 fn await_transmit_move(
     stream: &mut TcpStream,
-    _event_sink: &ExtEventSink,
+    peer_tx: &Sender<PeerMessage>,
     net_rx: &mpsc::Receiver<con::Message>,
+    dark: bool,
 ) -> Result<(), String> {
     let message = net_rx.recv().unwrap();
-    println!("Message: {:?}", message);
+    println!("Message: {}", describe(&message, dark));
     let message = message.code();
     stream.write_all(&message[..]).unwrap();
     stream.flush().unwrap();
@@ -233,11 +341,16 @@ fn await_transmit_move(
     let message = con::Message::from_code(&buffer[..]);
 
     let response_kind = {
-        println!("Received: {:?}", message);
+        println!("Received: {}", describe(&message, dark));
 
         match message {
             con::Message::Accept => ResponseKind::Noop,
-            con::Message::Decline => unimplemented!(),
+            con::Message::Decline => {
+                peer_tx
+                    .send(PeerMessage::Decline)
+                    .map_err(|err| err.to_string())?;
+                ResponseKind::Noop
+            }
             _ => {
                 println!("Message out of order");
                 ResponseKind::Message(con::Message::Decline)
@@ -254,7 +367,7 @@ fn await_transmit_move(
         }
     };
     if let Some(response) = response {
-        println!("Response: {:?}", response);
+        println!("Response: {}", describe(&response, dark));
         let response = response.code();
         stream.write_all(&response[..]).unwrap();
         stream.flush().unwrap();
@@ -264,33 +377,32 @@ fn await_transmit_move(
 
 fn await_receive_move(
     stream: &mut TcpStream,
-    event_sink: &ExtEventSink,
+    peer_tx: &Sender<PeerMessage>,
     net_rx: &mpsc::Receiver<con::Message>,
+    dark: bool,
 ) -> Result<(), String> {
     let mut buffer = [0; 32];
     stream.read(&mut buffer).unwrap();
 
     let message = con::Message::from_code(&buffer[..]);
     let response_kind = {
-        println!("Received: {:?}", message);
+        println!("Received: {}", describe(&message, dark));
 
         match message {
             con::Message::Accept => ResponseKind::Noop,
             con::Message::Decline => ResponseKind::Noop,
-            con::Message::Move(con::Move::Standard { origin, target }) => {
-                // TODO: error handling, remote should not be able to crash client
-                let origin = to_notation((origin.x as usize, origin.y as usize)).unwrap();
-                let target = to_notation((target.x as usize, target.y as usize)).unwrap();
-                let command = format!("{} {}", origin, target);
-
-                let res = event_sink.submit_command(action::MAKE_MOVE_FROM_NET, command, None);
-                if let Some(err) = res.err() {
-                    return Err(err.to_string());
+            con::Message::Move(mov) => match move_command(&mov) {
+                Some(command) => {
+                    let res = peer_tx.send(PeerMessage::Move(command));
+                    if let Some(err) = res.err() {
+                        return Err(err.to_string());
+                    }
+
+                    // Accept/Decline is sent from the remote player
+                    ResponseKind::AwaitDelegate
                 }
-
-                // Accept/Decline is sent from delegate
-                ResponseKind::AwaitDelegate
-            }
+                None => ResponseKind::Message(con::Message::Decline),
+            },
             _ => ResponseKind::Message(con::Message::Decline),
         }
     };
@@ -304,7 +416,7 @@ fn await_receive_move(
         }
     };
     if let Some(response) = response {
-        println!("Response: {:?}", response);
+        println!("Response: {}", describe(&response, dark));
         let response = response.code();
         stream.write_all(&response[..]).unwrap();
         stream.flush().unwrap();
@@ -11,20 +11,29 @@ pub struct Position(pub i32, pub i32);
 #[derive(Data, Clone)]
 pub struct AppState {
     pub board: Arc<Board>,
+    /// spaces the local player can't see in dark chess, indexed like the board
+    pub hidden: Arc<[[bool; 8]; 8]>,
     pub origin: Option<Position>,
     pub message: Option<String>,
+    /// ECO code and name of the opening played so far
+    pub opening: Option<String>,
 }
 
 impl AppState {
     pub fn new(board: Board) -> Self {
         Self {
             board: Arc::new(board),
+            hidden: Arc::new([[false; 8]; 8]),
             origin: None,
             message: None,
+            opening: None,
         }
     }
     pub fn get_piece(&self, position: Position) -> &Option<Piece> {
         let cpos = (position.0 as usize, position.1 as usize);
         &self.board[cpos.1][cpos.0]
     }
+    pub fn is_hidden(&self, position: Position) -> bool {
+        self.hidden[position.1 as usize][position.0 as usize]
+    }
 }
@@ -3,9 +3,12 @@ use crate::state::Position;
 
 use druid::{widget::*, *};
 
+type HiddenCheck<T> = Box<dyn Fn(Position, &T) -> bool>;
+
 pub struct Tile<T: druid::Data, R: Widget<T>> {
     position: Position,
     inner: R,
+    is_hidden: Option<HiddenCheck<T>>,
     _t: std::marker::PhantomData<T>,
 }
 
@@ -14,10 +17,17 @@ impl<T: druid::Data, R: Widget<T>> Tile<T, R> {
         Self {
             position,
             inner,
+            is_hidden: None,
             _t: std::marker::PhantomData::default(),
         }
     }
 
+    /// cover the tile in fog whenever `f` says the position is hidden
+    pub fn hidden_when(mut self, f: impl Fn(Position, &T) -> bool + 'static) -> Self {
+        self.is_hidden = Some(Box::new(f));
+        self
+    }
+
     pub fn on_click(
         self,
         f: impl Fn(&mut EventCtx, &mut T, &Env) + 'static,
@@ -46,6 +56,11 @@ impl<T: druid::Data, R: Widget<T>> Widget<T> for Tile<T, R> {
         self.inner.lifecycle(ctx, event, data, env);
     }
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if let Some(is_hidden) = &self.is_hidden {
+            if is_hidden(self.position, old_data) != is_hidden(self.position, data) {
+                ctx.request_paint();
+            }
+        }
         self.inner.update(ctx, old_data, data, env);
     }
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
@@ -58,10 +73,16 @@ impl<T: druid::Data, R: Widget<T>> Widget<T> for Tile<T, R> {
 
         let bounds = ctx.size().to_rect();
         let is_active = ctx.is_active();
+        let is_hidden = match &self.is_hidden {
+            Some(is_hidden) => is_hidden(pos, data),
+            None => false,
+        };
         let colo = ColorUtil::hsl(
             0.1,
             0.2,
-            if is_active {
+            if is_hidden {
+                0.12
+            } else if is_active {
                 0.53
             } else if checkerboard {
                 0.3
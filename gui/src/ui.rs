@@ -60,40 +60,42 @@ fn make_tile(pos: Position) -> impl Widget<AppState> {
         }),
     );
 
-    let tile = Tile::new(pos, icon).on_click(move |ctx, data, _env| {
-        match data.origin {
-            None => {
-                data.origin = Some(pos);
-                data.message = Some(format!("{:?}", pos));
-                // data.origin_available_moves = Some(Arc::new(data.game.chess_board.get_moves(cpos)));
-            }
-            Some(prev) => {
-                if prev == pos {
-                    data.origin = None;
-                    data.message = None;
-                    return;
+    let tile = Tile::new(pos, icon)
+        .hidden_when(|pos, data: &AppState| data.is_hidden(pos))
+        .on_click(move |ctx, data, _env| {
+            match data.origin {
+                None => {
+                    data.origin = Some(pos);
+                    data.message = Some(format!("{:?}", pos));
+                    // data.origin_available_moves = Some(Arc::new(data.game.chess_board.get_moves(cpos)));
                 }
+                Some(prev) => {
+                    if prev == pos {
+                        data.origin = None;
+                        data.message = None;
+                        return;
+                    }
 
-                let cpos = (prev.0 as usize, prev.1 as usize);
-                let tpos = (pos.0 as usize, pos.1 as usize);
+                    let cpos = (prev.0 as usize, prev.1 as usize);
+                    let tpos = (pos.0 as usize, pos.1 as usize);
 
-                let mut doit = || -> Result<String, String> {
-                    let command = format!("{} {}", to_notation(cpos)?, to_notation(tpos)?);
-                    ctx.submit_command(Command::new(action::MAKE_MOVE, command), None);
-                    Ok("".to_owned())
-                };
+                    let mut doit = || -> Result<String, String> {
+                        let command = format!("{} {}", to_notation(cpos)?, to_notation(tpos)?);
+                        ctx.submit_command(Command::new(action::MAKE_MOVE, command), None);
+                        Ok("".to_owned())
+                    };
 
-                let txt = match doit() {
-                    Err(inner) => format!("Error: {}", inner),
-                    Ok(inner) => inner,
-                };
+                    let txt = match doit() {
+                        Err(inner) => format!("Error: {}", inner),
+                        Ok(inner) => inner,
+                    };
 
-                data.message = if !txt.is_empty() { Some(txt) } else { None };
+                    data.message = if !txt.is_empty() { Some(txt) } else { None };
 
-                data.origin = None;
+                    data.origin = None;
+                }
             }
-        }
-    });
+        });
 
     tile
 }
@@ -128,6 +130,13 @@ impl<T: Data> Tile2<T> {
                 piece_logic::Color::Black => ColorUtil::hsl(0.1, 0.3, 0.1),
             };
 
+            if let PieceType::Custom(symbol, _) = &t.0 {
+                return Label::new(symbol.to_string())
+                    .with_text_size(27.)
+                    .with_text_color(color)
+                    .center();
+            }
+
             let color = format!("{:?}", color);
             let color = &color[..color.len() - 2];
             let svg_data = piece_svg_colored(&t.0, color).parse::<SvgData>();
@@ -174,14 +183,15 @@ impl<T: Data> Widget<T> for Tile2<T> {
 }
 
 #[allow(dead_code)]
-fn piece_char(piece: &Piece) -> &str {
-    match piece.piece_type {
-        PieceType::King => "K",
-        PieceType::Queen => "Q",
-        PieceType::Rook => "R",
-        PieceType::Knight => "N",
-        PieceType::Bishop => "B",
-        PieceType::Pawn => "P",
+fn piece_char(piece: &Piece) -> String {
+    match &piece.piece_type {
+        PieceType::King => "K".to_owned(),
+        PieceType::Queen => "Q".to_owned(),
+        PieceType::Rook => "R".to_owned(),
+        PieceType::Knight => "N".to_owned(),
+        PieceType::Bishop => "B".to_owned(),
+        PieceType::Pawn => "P".to_owned(),
+        PieceType::Custom(symbol, _) => symbol.to_string(),
     }
 }
 
@@ -193,6 +203,7 @@ fn piece_svg_raw(piece: &PieceType) -> &str {
         PieceType::Knight => include_str!("../assets/n.svg"),
         PieceType::Bishop => include_str!("../assets/b.svg"),
         PieceType::Pawn => include_str!("../assets/p.svg"),
+        PieceType::Custom(_, _) => unreachable!("custom pieces are drawn as labels"),
     }
 }
 